@@ -0,0 +1,29 @@
+//! Compact hexadecimal formatting of byte strings.
+
+use core::fmt;
+
+/// Displays bytes as lowercase hex without separators, e.g. `00ff10`, the
+/// format `xxd -p` and Python's `bytes.hex()` produce.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn lowercase_without_separators() {
+        assert_eq!(format!("{}", Hex(&[0x00, 0xff, 0x10, 0xAB])), "00ff10ab");
+        assert_eq!(format!("{}", Hex(&[])), "");
+    }
+}
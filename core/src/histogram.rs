@@ -0,0 +1,168 @@
+//! Text histograms of per-iteration durations.
+//!
+//! Mean and standard deviation hide the shape of a timing distribution: a
+//! few slow iterations from interrupts look like a slightly wider spread.
+//! [`Histogram`] bins the samples into equal-width buckets on the stack so the
+//! firmware can log one bar per bucket:
+//!
+//! ```text
+//!     50..52     us |######################################## 97
+//!     52..54     us |                                         0
+//!     54..56     us |#                                        3
+//! ```
+
+use core::fmt;
+
+/// Width of the bar of the fullest bucket, in characters.
+pub const BAR_WIDTH: usize = 40;
+
+/// Counts of microsecond samples in `B` equal-width buckets from the smallest
+/// to the largest sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Histogram<const B: usize> {
+    /// Lower bound of the first bucket, the smallest sample.
+    pub min: u32,
+    /// Width of each bucket in microseconds, at least 1.
+    pub width: u32,
+    /// Number of samples in each bucket.
+    pub counts: [u32; B],
+}
+
+impl<const B: usize> Histogram<B> {
+    /// Bin `samples`, or `None` if there are none (or no buckets).
+    ///
+    /// The last bucket includes its upper bound, so the largest sample always
+    /// has a bucket. The buckets are at least 1 us wide, so the last ones stay
+    /// empty when the samples span fewer than `B` microseconds.
+    pub fn of(samples: &[u32]) -> Option<Self> {
+        let min = *samples.iter().min()?;
+        let max = *samples.iter().max()?;
+        if B == 0 {
+            return None;
+        }
+        let width = (max - min).div_ceil(B as u32).max(1);
+
+        let mut counts = [0; B];
+        for &sample in samples {
+            counts[(((sample - min) / width) as usize).min(B - 1)] += 1;
+        }
+        Some(Self { min, width, counts })
+    }
+
+    /// The buckets as displayable rows with bars scaled to the fullest one.
+    pub fn rows(&self) -> impl Iterator<Item = Row> + '_ {
+        let peak = self.counts.iter().copied().max().unwrap_or(0);
+        self.counts.iter().enumerate().map(move |(i, &count)| {
+            let start = self.min + i as u32 * self.width;
+            Row {
+                start,
+                end: start + self.width,
+                count,
+                peak,
+            }
+        })
+    }
+}
+
+/// One bucket of a [`Histogram`], displayed as its range in microseconds, a
+/// bar of `#` and the count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Row {
+    /// Lower bound in microseconds, inclusive.
+    pub start: u32,
+    /// Upper bound in microseconds, exclusive except in the last bucket.
+    pub end: u32,
+    /// Number of samples in the bucket.
+    pub count: u32,
+    /// Count of the fullest bucket, which gets a bar of [`BAR_WIDTH`].
+    pub peak: u32,
+}
+
+impl Row {
+    /// Length of the bar; any non-empty bucket gets at least one character
+    /// so rare outliers stay visible.
+    pub fn bar_len(&self) -> usize {
+        if self.count == 0 || self.peak == 0 {
+            return 0;
+        }
+        (self.count as usize * BAR_WIDTH / self.peak as usize).max(1)
+    }
+}
+
+impl fmt::Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bar = self.bar_len();
+        write!(
+            f,
+            "{:>6}..{:<6} us |{:#<bar$}{:pad$} {}",
+            self.start,
+            self.end,
+            "",
+            "",
+            self.count,
+            pad = BAR_WIDTH - bar
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn bins_samples_between_min_and_max() {
+        let histogram = Histogram::<4>::of(&[10, 11, 12, 13, 14, 15, 16, 17]).unwrap();
+        assert_eq!(histogram.min, 10);
+        assert_eq!(histogram.width, 2);
+        assert_eq!(histogram.counts, [2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn maximum_lands_in_last_bucket() {
+        let histogram = Histogram::<4>::of(&[0, 8]).unwrap();
+        assert_eq!(histogram.counts.iter().sum::<u32>(), 2);
+        assert_eq!(histogram.counts[0], 1);
+        assert_eq!(histogram.counts[3], 1);
+    }
+
+    #[test]
+    fn shows_bimodal_outliers() {
+        let mut samples = [50; 100];
+        samples[10] = 90;
+        samples[20] = 91;
+        let histogram = Histogram::<10>::of(&samples).unwrap();
+        let slow = ((90 - 50) / histogram.width) as usize;
+        assert_eq!(histogram.counts[0], 98);
+        assert_eq!(histogram.counts[slow], 2);
+        assert_eq!(histogram.counts.iter().sum::<u32>(), 100);
+    }
+
+    #[test]
+    fn constant_samples_fill_first_bucket() {
+        let histogram = Histogram::<3>::of(&[7, 7, 7]).unwrap();
+        assert_eq!(histogram.width, 1);
+        assert_eq!(histogram.counts, [3, 0, 0]);
+        assert!(Histogram::<3>::of(&[]).is_none());
+    }
+
+    #[test]
+    fn rows_scale_bars_to_fullest_bucket() {
+        let histogram = Histogram::<2>::of(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 1]).unwrap();
+        let rows: std::vec::Vec<_> = histogram.rows().collect();
+        assert_eq!(rows[0].bar_len(), BAR_WIDTH);
+        assert_eq!(rows[1].bar_len(), 4);
+        assert_eq!(
+            format!("{}", rows[1]),
+            format!("     1..2      us |####{:36} 1", "")
+        );
+    }
+
+    #[test]
+    fn rare_outlier_keeps_a_visible_bar() {
+        let mut samples = [5; 1000];
+        samples[0] = 6;
+        let histogram = Histogram::<2>::of(&samples).unwrap();
+        assert_eq!(histogram.rows().nth(1).unwrap().bar_len(), 1);
+    }
+}
@@ -0,0 +1,21 @@
+//! Hardware-independent parts of the ESP32-C6 crypto benchmarks.
+//!
+//! Everything here is plain `no_std` code without any `esp-hal` dependency,
+//! so besides being linked into the firmware it builds and runs its unit
+//! tests on the host:
+//!
+//! ```text
+//! cargo test-host
+//! ```
+
+#![no_std]
+
+#[cfg(test)]
+extern crate std;
+
+pub mod ghash;
+pub mod hex;
+pub mod histogram;
+pub mod report;
+pub mod stats;
+pub mod units;
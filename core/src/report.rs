@@ -0,0 +1,529 @@
+//! Benchmark results and their machine-readable encodings.
+//!
+//! [`Csv`], [`Json`] and [`Prometheus`] wrap a [`BenchmarkResult`] and format
+//! it as one CSV record, one JSON object or one Prometheus exposition line
+//! per [`Metric`], so the firmware can log it without allocating:
+//!
+//! ```text
+//! algorithm,data_size,iterations,time_us,throughput_mb_per_s
+//! AES-CTR,1024,100,50.52,20.27
+//! {"schema_version":1,"algorithm":"AES-CTR","data_size":1024,"iterations":100,"time_us":50.52,"throughput_mb_per_s":20.27}
+//! crypto_time_us{algo="aes-ctr",size="1024",iterations="100"} 50.52
+//! crypto_throughput_mbps{algo="aes-ctr",size="1024",iterations="100"} 20.27
+//! ```
+//!
+//! Algorithm labels have the form `<algorithm>-<mode or size>`, optionally
+//! followed by a variant in parentheses, e.g. `AES-CTR`, `AES-CTR (keystream)`
+//! or `RSA-2048 (e = 65537)`. [`sort`] puts results in a stable order so two
+//! runs can be compared line by line.
+//!
+//! A [`Comparison`] holds the hardware and software time of one operation,
+//! and [`ComparisonCsv`] formats one row per [`Backend`] with the speedup of
+//! the hardware:
+//!
+//! ```text
+//! algorithm,backend,data_size,throughput_mb_per_s,speedup
+//! AES-CTR,hardware,1024,20.27,12.30
+//! AES-CTR,software,1024,1.65,1.00
+//! ```
+
+use core::fmt;
+
+use crate::units::{Throughput, THROUGHPUT_UNIT};
+
+/// Version of the [`Json`] object layout, sent as its `schema_version` field.
+///
+/// Bumped whenever a field is added, removed, renamed or changes meaning, so
+/// parsers can tell the layouts apart.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Header naming the fields of every [`Csv`] record.
+pub const CSV_HEADER: &str = "algorithm,data_size,iterations,time_us,throughput_mb_per_s";
+
+/// One benchmark result: `iterations` runs over `data_size` bytes each.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchmarkResult<'a> {
+    /// Algorithm label, e.g. `AES-CTR`.
+    pub algorithm: &'a str,
+    /// Bytes processed per iteration.
+    pub data_size: usize,
+    /// Number of timed iterations.
+    pub iterations: usize,
+    /// Mean time per iteration in microseconds.
+    pub time_us: f64,
+}
+
+impl BenchmarkResult<'_> {
+    /// Throughput in MB/s, i.e. bytes per microsecond. Zero if no time was
+    /// measured.
+    pub fn throughput_mb_per_s(&self) -> f64 {
+        if self.time_us > 0.0 {
+            self.data_size as f64 / self.time_us
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Sort `results` by algorithm label, then data size, then iteration count.
+///
+/// A plain variant sorts before its parenthesized variants, since the label
+/// prefix compares lower, so e.g. `AES-CTR` comes before `AES-CTR (keystream)`,
+/// which comes before `RSA-2048`.
+pub fn sort(results: &mut [BenchmarkResult<'_>]) {
+    results.sort_unstable_by(|a, b| {
+        a.algorithm
+            .cmp(b.algorithm)
+            .then(a.data_size.cmp(&b.data_size))
+            .then(a.iterations.cmp(&b.iterations))
+            .then(a.time_us.total_cmp(&b.time_us))
+    });
+}
+
+/// Labels of the AES cipher modes compared side by side by [`ModeRow`], in
+/// column order.
+pub const AES_MODES: [&str; 3] = ["AES-CTR", "AES-CBC", "AES-ECB"];
+
+/// The throughput of each of [`AES_MODES`] at one data size, displayed on one
+/// line such as `1024B: CTR=12.30 CBC=11.80 ECB=12.50 MB/s`, with `n/a` for a
+/// mode without a result at that size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModeRow {
+    /// Bytes processed per iteration.
+    pub data_size: usize,
+    /// Throughput of each mode in MB/s, i.e. bytes per microsecond.
+    pub throughputs: [Option<f64>; AES_MODES.len()],
+}
+
+impl ModeRow {
+    /// A row for `data_size` without any results yet.
+    pub fn new(data_size: usize) -> Self {
+        Self {
+            data_size,
+            throughputs: [None; AES_MODES.len()],
+        }
+    }
+
+    /// The column of `algorithm` in [`AES_MODES`], if it is one of them.
+    pub fn column(algorithm: &str) -> Option<usize> {
+        AES_MODES.iter().position(|&mode| mode == algorithm)
+    }
+}
+
+impl fmt::Display for ModeRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}B:", self.data_size)?;
+        for (mode, throughput) in AES_MODES.iter().zip(self.throughputs) {
+            let mode = mode.strip_prefix("AES-").unwrap_or(mode);
+            match throughput {
+                Some(throughput) => write!(
+                    f,
+                    " {mode}={:.2}",
+                    Throughput(throughput).in_unit(THROUGHPUT_UNIT)
+                )?,
+                None => write!(f, " {mode}=n/a")?,
+            }
+        }
+        write!(f, " {}", THROUGHPUT_UNIT.symbol())
+    }
+}
+
+/// Formats a result as a CSV record with the fields of [`CSV_HEADER`].
+pub struct Csv<'r, 'a>(pub &'r BenchmarkResult<'a>);
+
+impl fmt::Display for Csv<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = self.0;
+        write_csv_field(f, result.algorithm)?;
+        write!(
+            f,
+            ",{},{},{:.2},{:.2}",
+            result.data_size,
+            result.iterations,
+            result.time_us,
+            result.throughput_mb_per_s()
+        )
+    }
+}
+
+/// Header naming the fields of every [`ComparisonCsv`] record.
+pub const COMPARISON_CSV_HEADER: &str = "algorithm,backend,data_size,throughput_mb_per_s,speedup";
+
+/// The implementation that produced one side of a [`Comparison`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// The crypto peripheral.
+    Hardware,
+    /// A pure-Rust implementation on the CPU.
+    Software,
+}
+
+impl Backend {
+    /// The name in the `backend` field of a [`ComparisonCsv`] record.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hardware => "hardware",
+            Self::Software => "software",
+        }
+    }
+}
+
+/// The mean time of one operation over `data_size` bytes on the peripheral
+/// and in software.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison<'a> {
+    /// Algorithm label without a backend, e.g. `AES-CTR`.
+    pub algorithm: &'a str,
+    /// Bytes processed per operation.
+    pub data_size: usize,
+    /// Mean time on the peripheral in microseconds.
+    pub hardware_us: f64,
+    /// Mean time in software in microseconds.
+    pub software_us: f64,
+}
+
+impl Comparison<'_> {
+    /// How many times faster the hardware is, the software time over the
+    /// hardware time. Zero if no hardware time was measured.
+    pub fn speedup(&self) -> f64 {
+        if self.hardware_us > 0.0 {
+            self.software_us / self.hardware_us
+        } else {
+            0.0
+        }
+    }
+
+    /// Throughput of `backend` in MB/s, i.e. bytes per microsecond. Zero if
+    /// no time was measured.
+    pub fn throughput_mb_per_s(&self, backend: Backend) -> f64 {
+        let time_us = match backend {
+            Backend::Hardware => self.hardware_us,
+            Backend::Software => self.software_us,
+        };
+        if time_us > 0.0 {
+            self.data_size as f64 / time_us
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Formats the `backend` side of a comparison as a CSV record with the
+/// fields of [`COMPARISON_CSV_HEADER`]. The speedup is relative to the
+/// software, so the software record always has 1.
+pub struct ComparisonCsv<'r, 'a>(pub &'r Comparison<'a>, pub Backend);
+
+impl fmt::Display for ComparisonCsv<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(comparison, backend) = self;
+        let speedup = match backend {
+            Backend::Hardware => comparison.speedup(),
+            Backend::Software => 1.0,
+        };
+        write_csv_field(f, comparison.algorithm)?;
+        write!(
+            f,
+            ",{},{},{:.2},{speedup:.2}",
+            backend.name(),
+            comparison.data_size,
+            comparison.throughput_mb_per_s(*backend)
+        )
+    }
+}
+
+/// Formats a result as a single-line JSON object, starting with the
+/// [`JSON_SCHEMA_VERSION`].
+pub struct Json<'r, 'a>(pub &'r BenchmarkResult<'a>);
+
+impl fmt::Display for Json<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = self.0;
+        write!(
+            f,
+            "{{\"schema_version\":{JSON_SCHEMA_VERSION},\"algorithm\":"
+        )?;
+        write_json_string(f, result.algorithm)?;
+        write!(
+            f,
+            ",\"data_size\":{},\"iterations\":{},\"time_us\":{:.2},\"throughput_mb_per_s\":{:.2}}}",
+            result.data_size,
+            result.iterations,
+            result.time_us,
+            result.throughput_mb_per_s()
+        )
+    }
+}
+
+/// A value of a result exported by [`Prometheus`], one time series each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// Mean time per iteration in microseconds.
+    TimeUs,
+    /// Throughput in MB/s, i.e. bytes per microsecond.
+    ThroughputMbps,
+}
+
+impl Metric {
+    /// The metric name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TimeUs => "crypto_time_us",
+            Self::ThroughputMbps => "crypto_throughput_mbps",
+        }
+    }
+
+    /// The `# TYPE` line declaring the metric, logged once before its
+    /// samples.
+    pub fn type_line(self) -> TypeLine {
+        TypeLine(self)
+    }
+}
+
+/// Formats the `# TYPE <name> gauge` line of a [`Metric`].
+pub struct TypeLine(Metric);
+
+impl fmt::Display for TypeLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "# TYPE {} gauge", self.0.name())
+    }
+}
+
+/// Formats one [`Metric`] of a result as a Prometheus exposition line. The
+/// algorithm label is lowercased and the data size and iteration count are
+/// labels too, so every result is its own series.
+pub struct Prometheus<'r, 'a>(pub &'r BenchmarkResult<'a>, pub Metric);
+
+impl fmt::Display for Prometheus<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(result, metric) = self;
+        write!(f, "{}{{algo=\"", metric.name())?;
+        write_prometheus_label(f, result.algorithm)?;
+        let value = match metric {
+            Metric::TimeUs => result.time_us,
+            Metric::ThroughputMbps => result.throughput_mb_per_s(),
+        };
+        write!(
+            f,
+            "\",size=\"{}\",iterations=\"{}\"}} {value:.2}",
+            result.data_size, result.iterations
+        )
+    }
+}
+
+/// Write `value` as a CSV field, quoting it (RFC 4180) if it contains a
+/// comma, quote or line break.
+fn write_csv_field(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    if !value.contains([',', '"', '\n', '\r']) {
+        return f.write_str(value);
+    }
+    f.write_str("\"")?;
+    for (i, part) in value.split('"').enumerate() {
+        if i > 0 {
+            f.write_str("\"\"")?;
+        }
+        f.write_str(part)?;
+    }
+    f.write_str("\"")
+}
+
+/// Write `value` as a quoted JSON string.
+fn write_json_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Write `value` lowercased as the content of a Prometheus label value,
+/// escaping backslashes, quotes and line feeds.
+fn write_prometheus_label(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            c => write!(f, "{}", c.to_ascii_lowercase())?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    const RESULT: BenchmarkResult = BenchmarkResult {
+        algorithm: "AES-CTR",
+        data_size: 1024,
+        iterations: 100,
+        time_us: 50.5,
+    };
+
+    #[test]
+    fn throughput_is_bytes_per_microsecond() {
+        assert_eq!(RESULT.throughput_mb_per_s(), 1024.0 / 50.5);
+        let untimed = BenchmarkResult {
+            time_us: 0.0,
+            ..RESULT
+        };
+        assert_eq!(untimed.throughput_mb_per_s(), 0.0);
+    }
+
+    #[test]
+    fn csv_record_matches_header() {
+        assert_eq!(format!("{}", Csv(&RESULT)), "AES-CTR,1024,100,50.50,20.28");
+        assert_eq!(
+            CSV_HEADER.split(',').count(),
+            format!("{}", Csv(&RESULT)).split(',').count()
+        );
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() {
+        let result = BenchmarkResult {
+            algorithm: "AES-CTR, \"loaded\"",
+            ..RESULT
+        };
+        assert_eq!(
+            format!("{}", Csv(&result)),
+            "\"AES-CTR, \"\"loaded\"\"\",1024,100,50.50,20.28"
+        );
+    }
+
+    #[test]
+    fn sorts_by_algorithm_then_size() {
+        let result = |algorithm, data_size| BenchmarkResult {
+            algorithm,
+            data_size,
+            ..RESULT
+        };
+        let mut results = [
+            result("SHA-256", 64),
+            result("AES-CTR (keystream)", 64),
+            result("RSA-2048", 256),
+            result("AES-CTR", 1024),
+            result("AES-CTR", 64),
+        ];
+        sort(&mut results);
+        let order: std::vec::Vec<_> = results.iter().map(|r| (r.algorithm, r.data_size)).collect();
+        assert_eq!(
+            order,
+            [
+                ("AES-CTR", 64),
+                ("AES-CTR", 1024),
+                ("AES-CTR (keystream)", 64),
+                ("RSA-2048", 256),
+                ("SHA-256", 64),
+            ]
+        );
+    }
+
+    const COMPARISON: Comparison = Comparison {
+        algorithm: "AES-CTR",
+        data_size: 1024,
+        hardware_us: 50.0,
+        software_us: 625.0,
+    };
+
+    #[test]
+    fn speedup_is_software_over_hardware_time() {
+        assert_eq!(COMPARISON.speedup(), 12.5);
+        let untimed = Comparison {
+            hardware_us: 0.0,
+            ..COMPARISON
+        };
+        assert_eq!(untimed.speedup(), 0.0);
+        assert_eq!(untimed.throughput_mb_per_s(Backend::Hardware), 0.0);
+    }
+
+    #[test]
+    fn comparison_csv_has_one_record_per_backend() {
+        assert_eq!(
+            format!("{}", ComparisonCsv(&COMPARISON, Backend::Hardware)),
+            "AES-CTR,hardware,1024,20.48,12.50"
+        );
+        assert_eq!(
+            format!("{}", ComparisonCsv(&COMPARISON, Backend::Software)),
+            "AES-CTR,software,1024,1.64,1.00"
+        );
+        assert_eq!(
+            COMPARISON_CSV_HEADER.split(',').count(),
+            format!("{}", ComparisonCsv(&COMPARISON, Backend::Software))
+                .split(',')
+                .count()
+        );
+    }
+
+    #[test]
+    fn json_object() {
+        assert_eq!(
+            format!("{}", Json(&RESULT)),
+            "{\"schema_version\":1,\"algorithm\":\"AES-CTR\",\"data_size\":1024,\"iterations\":100,\"time_us\":50.50,\"throughput_mb_per_s\":20.28}"
+        );
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        let result = BenchmarkResult {
+            algorithm: "a\"b\\c\n",
+            ..RESULT
+        };
+        assert!(format!("{}", Json(&result))
+            .starts_with("{\"schema_version\":1,\"algorithm\":\"a\\\"b\\\\c\\u000a\","));
+    }
+
+    #[test]
+    fn prometheus_lines() {
+        assert_eq!(
+            format!("{}", Prometheus(&RESULT, Metric::TimeUs)),
+            "crypto_time_us{algo=\"aes-ctr\",size=\"1024\",iterations=\"100\"} 50.50"
+        );
+        assert_eq!(
+            format!("{}", Prometheus(&RESULT, Metric::ThroughputMbps)),
+            "crypto_throughput_mbps{algo=\"aes-ctr\",size=\"1024\",iterations=\"100\"} 20.28"
+        );
+        assert_eq!(
+            format!("{}", Metric::ThroughputMbps.type_line()),
+            "# TYPE crypto_throughput_mbps gauge"
+        );
+    }
+
+    #[test]
+    fn prometheus_escapes_labels() {
+        let result = BenchmarkResult {
+            algorithm: "RSA-2048 (e = 65537) \"x\\y\n",
+            ..RESULT
+        };
+        assert!(format!("{}", Prometheus(&result, Metric::TimeUs))
+            .starts_with("crypto_time_us{algo=\"rsa-2048 (e = 65537) \\\"x\\\\y\\n\","));
+    }
+
+    #[test]
+    fn mode_row_columns() {
+        assert_eq!(ModeRow::column("AES-CTR"), Some(0));
+        assert_eq!(ModeRow::column("AES-ECB"), Some(2));
+        assert_eq!(ModeRow::column("AES-CTR (keystream)"), None);
+    }
+
+    #[test]
+    fn mode_row_on_one_line() {
+        let mut row = ModeRow::new(1024);
+        row.throughputs = [Some(12.3), Some(11.8), Some(12.5)];
+        let unit = THROUGHPUT_UNIT.symbol();
+        if THROUGHPUT_UNIT == crate::units::ThroughputUnit::MB {
+            assert_eq!(
+                format!("{row}"),
+                format!("1024B: CTR=12.30 CBC=11.80 ECB=12.50 {unit}")
+            );
+        }
+        row.throughputs[1] = None;
+        assert!(format!("{row}").contains(" CBC=n/a "));
+    }
+}
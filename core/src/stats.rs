@@ -0,0 +1,410 @@
+//! Per-iteration samples and the summary statistics reported from them.
+
+/// Fixed-capacity recorder for per-iteration durations in microseconds.
+pub struct Samples<const N: usize> {
+    samples: [u32; N],
+    len: usize,
+}
+
+impl<const N: usize> Samples<N> {
+    /// Create an empty recorder.
+    pub const fn new() -> Self {
+        Self {
+            samples: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Record one iteration that took `micros` microseconds. Samples beyond
+    /// the capacity are dropped.
+    pub fn push(&mut self, micros: u64) {
+        if self.len < N {
+            self.samples[self.len] = micros as u32;
+            self.len += 1;
+        }
+    }
+
+    /// The samples recorded so far.
+    pub fn as_slice(&self) -> &[u32] {
+        &self.samples[..self.len]
+    }
+
+    /// The samples recorded so far, mutably, e.g. to sort them for
+    /// [`BenchStats::of`].
+    pub fn as_mut_slice(&mut self) -> &mut [u32] {
+        &mut self.samples[..self.len]
+    }
+}
+
+impl<const N: usize> Default for Samples<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mean, spread and 95% confidence interval of a set of values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    /// Number of values.
+    pub count: usize,
+    /// Arithmetic mean.
+    pub mean: f64,
+    /// Sample standard deviation (Bessel-corrected).
+    pub std_dev: f64,
+    /// Standard error of the mean.
+    pub std_error: f64,
+    /// Half-width of the 95% confidence interval on the mean, i.e. the mean
+    /// lies in `mean ± margin_95`.
+    pub margin_95: f64,
+}
+
+impl Summary {
+    /// Summarize `values`. Fewer than two values yield a zero spread.
+    pub fn of(values: impl Iterator<Item = f64> + Clone) -> Self {
+        let (count, sum) = values
+            .clone()
+            .fold((0_usize, 0.0), |(n, sum), v| (n + 1, sum + v));
+        if count == 0 {
+            return Self::default();
+        }
+        let mean = sum / count as f64;
+        if count == 1 {
+            return Self {
+                count,
+                mean,
+                ..Self::default()
+            };
+        }
+
+        let squares: f64 = values.map(|v| (v - mean) * (v - mean)).sum();
+        let std_dev = libm::sqrt(squares / (count - 1) as f64);
+        Self::from_spread(count, mean, std_dev)
+    }
+
+    fn from_spread(count: usize, mean: f64, std_dev: f64) -> Self {
+        let std_error = std_dev / libm::sqrt(count as f64);
+        Self {
+            count,
+            mean,
+            std_dev,
+            std_error,
+            margin_95: t_critical_95(count) * std_error,
+        }
+    }
+
+    /// [`Summary::margin_95`] as a fraction of the mean, infinite for a zero
+    /// mean or fewer than two values.
+    pub fn relative_margin_95(&self) -> f64 {
+        if self.count < 2 || self.mean == 0.0 {
+            return f64::INFINITY;
+        }
+        self.margin_95 / libm::fabs(self.mean)
+    }
+}
+
+/// Distribution of per-iteration times in microseconds: the extremes, the
+/// median and 95th percentile a worst-case latency budget needs, and the
+/// mean and sample standard deviation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchStats {
+    /// Fastest iteration.
+    pub min: u32,
+    /// Slowest iteration.
+    pub max: u32,
+    /// Middle iteration, or the mean of the two middle ones.
+    pub median: f64,
+    /// 95th percentile by the nearest-rank method: 95% of the iterations
+    /// took at most this long.
+    pub p95: u32,
+    /// Arithmetic mean.
+    pub mean: f64,
+    /// Sample standard deviation (Bessel-corrected), zero for one sample.
+    pub std_dev: f64,
+}
+
+impl BenchStats {
+    /// The statistics of `samples`, or `None` if there are none. Sorts
+    /// `samples` in place, so nothing is allocated.
+    pub fn of(samples: &mut [u32]) -> Option<Self> {
+        let count = samples.len();
+        if count == 0 {
+            return None;
+        }
+        samples.sort_unstable();
+
+        let middle = count / 2;
+        let median = if count.is_multiple_of(2) {
+            (f64::from(samples[middle - 1]) + f64::from(samples[middle])) / 2.0
+        } else {
+            f64::from(samples[middle])
+        };
+        let p95 = samples[(count * 95).div_ceil(100) - 1];
+        let summary = Summary::of(samples.iter().map(|&us| f64::from(us)));
+        Some(Self {
+            min: samples[0],
+            max: samples[count - 1],
+            median,
+            p95,
+            mean: summary.mean,
+            std_dev: summary.std_dev,
+        })
+    }
+}
+
+/// Mean and variance updated one value at a time (Welford's algorithm), so a
+/// measurement loop can check its confidence interval after every iteration
+/// without keeping the samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Running {
+    count: usize,
+    mean: f64,
+    squares: f64,
+}
+
+impl Running {
+    /// Start with no values.
+    pub const fn new() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            squares: 0.0,
+        }
+    }
+
+    /// Add one value.
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squares += delta * (value - self.mean);
+    }
+
+    /// The statistics of the values so far, as [`Summary::of`] would compute
+    /// them.
+    pub fn summary(&self) -> Summary {
+        match self.count {
+            0 => Summary::default(),
+            1 => Summary {
+                count: 1,
+                mean: self.mean,
+                ..Summary::default()
+            },
+            count => Summary::from_spread(
+                count,
+                self.mean,
+                libm::sqrt(self.squares / (count - 1) as f64),
+            ),
+        }
+    }
+}
+
+/// Two-sided 95% critical value of Student's t distribution for a sample of
+/// `count` values (`count - 1` degrees of freedom).
+///
+/// Exact up to 30 degrees of freedom, then taken from the next tabulated
+/// value below, which errs on the wide side. Converges to the normal 1.96.
+pub fn t_critical_95(count: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+
+    match count.saturating_sub(1) {
+        0 => f64::INFINITY,
+        df @ 1..=30 => TABLE[df - 1],
+        31..=40 => 2.042,
+        41..=60 => 2.021,
+        61..=120 => 2.000,
+        121..=1000 => 1.980,
+        _ => 1.960,
+    }
+}
+
+/// Mean of `samples` without the `slowest` largest ones, or `None` if that
+/// leaves none. Sorts `samples` in place.
+///
+/// Dropping only the slow end removes interrupts and cache refills that a
+/// sustained workload amortizes, without letting fast runs pull the mean
+/// below what the hardware can keep up.
+pub fn trimmed_mean(samples: &mut [u32], slowest: usize) -> Option<f64> {
+    let kept = samples
+        .len()
+        .checked_sub(slowest)
+        .filter(|&kept| kept > 0)?;
+    samples.sort_unstable();
+    let sum: u64 = samples[..kept].iter().map(|&us| u64::from(us)).sum();
+    Some(sum as f64 / kept as f64)
+}
+
+/// The data size from which throughput stops increasing: the first of
+/// `sizes` after which every step to the next size gains less than
+/// `threshold` (relative, e.g. `0.05` for 5%) of throughput.
+///
+/// `throughputs` holds the throughput at each of `sizes`, which ascend.
+/// `None` if the last step still gains at least `threshold`, i.e. the sweep
+/// ends before the throughput saturates, or if there are fewer than two
+/// points.
+pub fn saturation_point(sizes: &[usize], throughputs: &[f64], threshold: f64) -> Option<usize> {
+    let points = sizes.len().min(throughputs.len());
+    if points < 2 {
+        return None;
+    }
+    let flat = |i: usize| throughputs[i + 1] - throughputs[i] < threshold * throughputs[i];
+    let first = (0..points - 1).rev().take_while(|&i| flat(i)).last()?;
+    Some(sizes[first])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn samples_drop_beyond_capacity() {
+        let mut samples = Samples::<2>::new();
+        assert!(samples.as_slice().is_empty());
+        samples.push(10);
+        samples.push(20);
+        samples.push(30);
+        assert_eq!(samples.as_slice(), &[10, 20]);
+    }
+
+    #[test]
+    fn trimmed_mean_drops_slowest() {
+        let mut samples = [10, 50, 12, 11, 90];
+        assert!(close(trimmed_mean(&mut samples, 2).unwrap(), 11.0));
+        assert_eq!(samples, [10, 11, 12, 50, 90]);
+        assert!(close(trimmed_mean(&mut samples, 0).unwrap(), 34.6));
+        assert_eq!(trimmed_mean(&mut samples, 5), None);
+        assert_eq!(trimmed_mean(&mut [], 0), None);
+    }
+
+    #[test]
+    fn bench_stats_sorts_and_ranks() {
+        let mut samples = [9, 3, 7, 5, 1];
+        let stats = BenchStats::of(&mut samples).unwrap();
+        assert_eq!(samples, [1, 3, 5, 7, 9]);
+        assert_eq!((stats.min, stats.max, stats.p95), (1, 9, 9));
+        assert!(close(stats.median, 5.0));
+        assert!(close(stats.mean, 5.0));
+        assert!(close(stats.std_dev, libm::sqrt(10.0)));
+        assert!(BenchStats::of(&mut []).is_none());
+    }
+
+    #[test]
+    fn bench_stats_p95_skips_the_slowest_five_percent() {
+        let mut samples = [10; 100];
+        for (i, sample) in samples[95..].iter_mut().enumerate() {
+            *sample = 100 + i as u32;
+        }
+        samples[0] = 4;
+        let stats = BenchStats::of(&mut samples).unwrap();
+        assert_eq!(stats.p95, 10);
+        assert_eq!((stats.min, stats.max), (4, 104));
+        assert!(close(stats.median, 10.0));
+    }
+
+    #[test]
+    fn bench_stats_median_of_even_count_averages_middle() {
+        let stats = BenchStats::of(&mut [4, 1, 2, 3]).unwrap();
+        assert!(close(stats.median, 2.5));
+        let single = BenchStats::of(&mut [7]).unwrap();
+        assert_eq!(single.std_dev, 0.0);
+        assert!(close(single.median, 7.0));
+    }
+
+    #[test]
+    fn summary_of_no_values_is_zero() {
+        assert_eq!(Summary::of(core::iter::empty()), Summary::default());
+    }
+
+    #[test]
+    fn summary_of_one_value_has_no_spread() {
+        let summary = Summary::of([4.0].into_iter());
+        assert_eq!(summary.count, 1);
+        assert_eq!(summary.mean, 4.0);
+        assert_eq!(summary.std_dev, 0.0);
+        assert_eq!(summary.margin_95, 0.0);
+    }
+
+    #[test]
+    fn summary_matches_hand_computed_values() {
+        let summary = Summary::of([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].into_iter());
+        assert_eq!(summary.count, 8);
+        assert!(close(summary.mean, 5.0));
+        // Sum of squared deviations is 32, over 7 degrees of freedom.
+        let std_dev = (32.0_f64 / 7.0).sqrt();
+        assert!(close(summary.std_dev, std_dev));
+        assert!(close(summary.std_error, std_dev / 8.0_f64.sqrt()));
+        assert!(close(summary.margin_95, 2.365 * std_dev / 8.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn running_summary_matches_summary_of() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let mut running = Running::new();
+        assert_eq!(running.summary(), Summary::default());
+        for value in values {
+            running.push(value);
+        }
+        let expected = Summary::of(values.into_iter());
+        let summary = running.summary();
+        assert_eq!(summary.count, expected.count);
+        assert!(close(summary.mean, expected.mean));
+        assert!(close(summary.std_dev, expected.std_dev));
+        assert!(close(summary.margin_95, expected.margin_95));
+    }
+
+    #[test]
+    fn relative_margin_needs_two_values_and_a_mean() {
+        let mut running = Running::new();
+        running.push(10.0);
+        assert_eq!(running.summary().relative_margin_95(), f64::INFINITY);
+        running.push(12.0);
+        let summary = running.summary();
+        assert!(close(
+            summary.relative_margin_95(),
+            summary.margin_95 / 11.0
+        ));
+        assert_eq!(
+            Summary::of([0.0, 0.0].into_iter()).relative_margin_95(),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn saturation_is_where_gains_stay_below_threshold() {
+        let sizes = [64, 128, 256, 512, 1024, 2048];
+        // A dip at 512 followed by a 10% gain does not count as saturated.
+        let throughputs = [5.0, 8.0, 10.0, 9.9, 10.9, 11.0];
+        assert_eq!(saturation_point(&sizes, &throughputs, 0.05), Some(1024));
+        assert_eq!(saturation_point(&sizes, &throughputs, 0.5), Some(128));
+    }
+
+    #[test]
+    fn no_saturation_while_still_rising() {
+        let sizes = [64, 128, 256];
+        assert_eq!(saturation_point(&sizes, &[1.0, 2.0, 4.0], 0.05), None);
+        assert_eq!(saturation_point(&sizes[..1], &[1.0], 0.05), None);
+    }
+
+    #[test]
+    fn t_critical_values() {
+        assert_eq!(t_critical_95(0), f64::INFINITY);
+        assert_eq!(t_critical_95(1), f64::INFINITY);
+        assert_eq!(t_critical_95(2), 12.706);
+        assert_eq!(t_critical_95(31), 2.042);
+        assert_eq!(t_critical_95(100), 2.000);
+        assert_eq!(t_critical_95(5000), 1.960);
+    }
+
+    #[test]
+    fn t_critical_values_never_increase() {
+        let values: std::vec::Vec<f64> = (2..2000).map(t_critical_95).collect();
+        assert!(values.windows(2).all(|pair| pair[1] <= pair[0]));
+    }
+}
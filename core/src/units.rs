@@ -0,0 +1,193 @@
+//! Human-readable formatting of measured values.
+
+use core::fmt;
+
+/// A duration in microseconds, displayed in the unit that suits its
+/// magnitude: `ns` below one microsecond, `us` below one millisecond, `ms`
+/// below one second and `s` above.
+///
+/// Nanoseconds are shown as whole numbers, the other units with two
+/// decimals. Negative values (differences) keep their sign, and the `+`
+/// flag forces it for positive ones, e.g. `format!("{:+}", delta)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Elapsed(pub f64);
+
+impl Elapsed {
+    /// A whole number of microseconds.
+    pub fn from_micros(micros: u64) -> Self {
+        Self(micros as f64)
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let micros = self.0;
+        let (value, unit, precision) = match micros.abs() {
+            m if m < 1.0 => (micros * 1000.0, "ns", 0),
+            m if m < 1000.0 => (micros, "us", 2),
+            m if m < 1_000_000.0 => (micros / 1000.0, "ms", 2),
+            _ => (micros / 1_000_000.0, "s", 2),
+        };
+        if f.sign_plus() {
+            write!(f, "{value:+.precision$} {unit}")
+        } else {
+            write!(f, "{value:.precision$} {unit}")
+        }
+    }
+}
+
+/// The megabyte of the logged throughputs: decimal MB (10^6 bytes) by
+/// default, or binary MiB (2^20 bytes) with the `iec-units` feature to match
+/// the power-of-two buffer sizes.
+pub const THROUGHPUT_UNIT: ThroughputUnit = if cfg!(feature = "iec-units") {
+    ThroughputUnit::MiB
+} else {
+    ThroughputUnit::MB
+};
+
+/// A convention for the megabyte in throughputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThroughputUnit {
+    /// 1,000,000 bytes (SI).
+    MB,
+    /// 1,048,576 bytes (IEC).
+    MiB,
+}
+
+impl ThroughputUnit {
+    /// Bytes in one megabyte of this unit.
+    pub const fn bytes(self) -> f64 {
+        match self {
+            Self::MB => 1_000_000.0,
+            Self::MiB => 1_048_576.0,
+        }
+    }
+
+    /// Symbol of the throughput, e.g. `MB/s`.
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::MB => "MB/s",
+            Self::MiB => "MiB/s",
+        }
+    }
+}
+
+/// A throughput in bytes per microsecond (decimal MB/s), displayed in
+/// [`THROUGHPUT_UNIT`] with two decimals unless the format gives a
+/// precision, e.g. `20.27 MB/s`, or `19.33 MiB/s` with `iec-units`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Throughput(pub f64);
+
+impl Throughput {
+    /// `bytes` processed in `micros` microseconds.
+    pub fn of(bytes: usize, micros: f64) -> Self {
+        Self(bytes as f64 / micros)
+    }
+
+    /// The throughput as a number of megabytes of `unit` per second.
+    pub fn in_unit(self, unit: ThroughputUnit) -> f64 {
+        self.0 * 1_000_000.0 / unit.bytes()
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(2);
+        write!(
+            f,
+            "{:.precision$} {}",
+            self.in_unit(THROUGHPUT_UNIT),
+            THROUGHPUT_UNIT.symbol()
+        )
+    }
+}
+
+/// The relative change of a measured value against a reference, displayed
+/// as a signed percentage with one decimal, e.g. `+3.2%` or `-5.1%`, or as
+/// `n/a` without a usable reference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Delta(pub Option<f64>);
+
+impl Delta {
+    /// The change from `reference` to `value`, `n/a` if there is no
+    /// reference or it is not positive.
+    pub fn between(reference: Option<f64>, value: f64) -> Self {
+        Self(
+            reference
+                .filter(|&reference| reference > 0.0)
+                .map(|reference| (value - reference) / reference * 100.0),
+        )
+    }
+}
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(percent) => write!(f, "{percent:+.1}%"),
+            None => f.write_str("n/a"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn picks_unit_by_magnitude() {
+        assert_eq!(format!("{}", Elapsed(0.25)), "250 ns");
+        assert_eq!(format!("{}", Elapsed(1.0)), "1.00 us");
+        assert_eq!(format!("{}", Elapsed(999.5)), "999.50 us");
+        assert_eq!(format!("{}", Elapsed(1000.0)), "1.00 ms");
+        assert_eq!(format!("{}", Elapsed::from_micros(219_000)), "219.00 ms");
+        assert_eq!(format!("{}", Elapsed::from_micros(60_000_000)), "60.00 s");
+    }
+
+    #[test]
+    fn keeps_sign_of_differences() {
+        assert_eq!(format!("{}", Elapsed(-1500.0)), "-1.50 ms");
+        assert_eq!(format!("{:+}", Elapsed(12.0)), "+12.00 us");
+        assert_eq!(format!("{:+}", Elapsed(-0.5)), "-500 ns");
+    }
+
+    #[test]
+    fn converts_throughput_between_units() {
+        let throughput = Throughput::of(1_048_576, 1_000_000.0);
+        assert_eq!(throughput.in_unit(ThroughputUnit::MB), 1.048576);
+        assert_eq!(throughput.in_unit(ThroughputUnit::MiB), 1.0);
+    }
+
+    #[test]
+    fn throughput_shows_configured_unit() {
+        let symbol = THROUGHPUT_UNIT.symbol();
+        let value = Throughput(20.27).in_unit(THROUGHPUT_UNIT);
+        assert_eq!(
+            format!("{}", Throughput(20.27)),
+            format!("{value:.2} {symbol}")
+        );
+        assert_eq!(
+            format!("{:.3}", Throughput(20.27)),
+            format!("{value:.3} {symbol}")
+        );
+    }
+
+    #[cfg(not(feature = "iec-units"))]
+    #[test]
+    fn throughput_defaults_to_decimal_megabytes() {
+        assert_eq!(format!("{}", Throughput(20.27)), "20.27 MB/s");
+    }
+
+    #[test]
+    fn delta_is_signed_percentage() {
+        assert_eq!(format!("{}", Delta::between(Some(100.0), 103.2)), "+3.2%");
+        assert_eq!(format!("{}", Delta::between(Some(200.0), 189.8)), "-5.1%");
+        assert_eq!(format!("{}", Delta::between(Some(50.0), 50.0)), "+0.0%");
+    }
+
+    #[test]
+    fn delta_without_reference_is_not_available() {
+        assert_eq!(format!("{}", Delta::between(None, 1.0)), "n/a");
+        assert_eq!(format!("{}", Delta::between(Some(0.0), 1.0)), "n/a");
+    }
+}
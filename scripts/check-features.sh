@@ -0,0 +1,61 @@
+#!/usr/bin/env bash
+# Run clippy with warnings denied over the feature combinations that compile
+# different code: every benchmark group on its own, every group paired with
+# every optional feature, and a few larger sets that enable several optional
+# features at once. A helper that only one group uses and that is compiled in
+# for another group fails here as dead code.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+groups="bench-aes bench-sha bench-rsa bench-rng bench-ecc"
+
+features="aes-dma-ch1 aes-dma-ch2 raw-timings aes-cpu-load dry-run sha-flash
+thresholds throughput-profile interrupt-compare datasheet-power power-sensor
+csv-output json-output prometheus-output flash-results baseline heap-pressure
+target-ci serial-abort quick rsa-stability hex-dump histogram soft-aes-check
+soft-blake3 soft-compare concurrent verify wfi-idle hmac-efuse button-trigger
+banners color rtt iec-units"
+
+combos=(
+    "bench-aes,bench-sha,bench-rsa,bench-rng,bench-ecc"
+    "bench-aes,bench-sha,raw-timings,thresholds"
+    "bench-rsa,raw-timings,thresholds,dry-run"
+    "bench-rng,bench-ecc,baseline,csv-output"
+    "bench-rsa,baseline,quick"
+    "bench-sha,flash-results,quick"
+    "bench-sha,serial-abort,throughput-profile"
+    "bench-sha,iec-units,thresholds,datasheet-power"
+    "bench-sha,prometheus-output,flash-results"
+    "bench-rsa,hex-dump,verify"
+    "hmac-efuse,bench-rng,dry-run"
+    "hmac-efuse,bench-sha,json-output"
+    "concurrent,dry-run,csv-output"
+    "rsa-stability,histogram"
+    "verify"
+)
+
+for group in $groups; do
+    combos+=("$group")
+    for feature in $features; do
+        combos+=("$group,$feature")
+    done
+done
+
+failed=0
+for combo in "${combos[@]}"; do
+    echo "== $combo"
+    if ! cargo clippy --no-default-features --features "$combo" -- -D warnings; then
+        failed=1
+    fi
+done
+
+# The default image, with and without the features that change its layout.
+for combo in "" "throughput-profile,power-sensor,csv-output" "soft-compare" "concurrent"; do
+    echo "== default${combo:+,$combo}"
+    if ! cargo clippy --features "$combo" -- -D warnings; then
+        failed=1
+    fi
+done
+
+exit "$failed"
@@ -0,0 +1,92 @@
+//! Aborting a benchmark run from the serial console.
+//!
+//! [`install`] takes over the USB serial/JTAG receiver and listens for
+//! [`ABORT_BYTES`] in its interrupt handler, which only sets a flag. The
+//! benchmarks poll [`requested`] between iterations and between benchmarks,
+//! so checking costs one atomic load and nothing is read from the serial
+//! port inside a timed loop. Received bytes only raise an interrupt while
+//! someone types, so an idle console does not disturb the measurements.
+//!
+//! After an aborted run the firmware prompts for [`RESTART_BYTE`], which
+//! [`take_restart`] reports, to start the next run.
+//!
+//! With `flash-results` the same receiver also takes [`DUMP_BYTE`], at any
+//! time; [`take_dump_request`] reports it whenever the firmware is between
+//! runs or idle, so the stored results can be printed without a reset. The
+//! module is compiled in with either feature.
+
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use critical_section::Mutex;
+use esp_hal::handler;
+use esp_hal::usb_serial_jtag::UsbSerialJtag;
+use esp_hal::Blocking;
+
+/// Bytes that request an abort: Ctrl-C (ETX) and `q`.
+pub const ABORT_BYTES: [u8; 2] = [0x03, b'q'];
+
+/// Byte that starts a new run from the prompt after an abort.
+pub const RESTART_BYTE: u8 = b'r';
+
+/// Byte that requests a dump of the results stored in flash.
+pub const DUMP_BYTE: u8 = b'd';
+
+static SERIAL: Mutex<RefCell<Option<UsbSerialJtag<'static, Blocking>>>> =
+    Mutex::new(RefCell::new(None));
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+static RESTART: AtomicBool = AtomicBool::new(false);
+
+static DUMP: AtomicBool = AtomicBool::new(false);
+
+/// Start listening for [`ABORT_BYTES`], [`RESTART_BYTE`] and [`DUMP_BYTE`]
+/// on `serial`.
+///
+/// Everything else received is discarded.
+pub fn install(mut serial: UsbSerialJtag<'static, Blocking>) {
+    critical_section::with(|cs| {
+        serial.set_interrupt_handler(on_receive);
+        serial.listen_rx_packet_recv_interrupt();
+        SERIAL.replace(cs, Some(serial));
+    });
+}
+
+/// Whether an abort was requested since the last [`clear`].
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Forget an earlier abort request, e.g. before starting another run.
+pub fn clear() {
+    REQUESTED.store(false, Ordering::Relaxed);
+}
+
+/// Whether [`RESTART_BYTE`] was received since the last call.
+pub fn take_restart() -> bool {
+    RESTART.swap(false, Ordering::Relaxed)
+}
+
+/// Whether [`DUMP_BYTE`] was received since the last call.
+pub fn take_dump_request() -> bool {
+    DUMP.swap(false, Ordering::Relaxed)
+}
+
+#[handler]
+fn on_receive() {
+    critical_section::with(|cs| {
+        if let Some(serial) = SERIAL.borrow_ref_mut(cs).as_mut() {
+            while let Ok(byte) = serial.read_byte() {
+                if ABORT_BYTES.contains(&byte) {
+                    REQUESTED.store(true, Ordering::Relaxed);
+                } else if byte == RESTART_BYTE {
+                    RESTART.store(true, Ordering::Relaxed);
+                } else if byte == DUMP_BYTE {
+                    DUMP.store(true, Ordering::Relaxed);
+                }
+            }
+            serial.reset_rx_packet_recv_interrupt();
+        }
+    });
+}
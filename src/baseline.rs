@@ -0,0 +1,64 @@
+//! Reference results that each run is reported against.
+//!
+//! With `baseline` every recorded result is compared with the table below at
+//! the end of the run and logged as the change in mean time per iteration, so
+//! a regression stands out in the log without any host tooling:
+//!
+//! ```text
+//! Baseline, AES-CTR, DataSize: 1024, Time: 52.10 us, Baseline: 50.52 us, Delta: +3.1%
+//! ```
+//!
+//! A positive delta means the run was slower than the baseline. Results
+//! without a table entry are logged with `Delta: n/a`.
+
+use log::info;
+
+use crate::report::BenchmarkResult;
+use crate::units::{Delta, Elapsed};
+
+/// Mean time per iteration in microseconds per (algorithm label, data size in
+/// bytes), taken from the README figures at maximum CPU clock. The AES-CTR
+/// entries are the data size divided by the README throughput. SHA-256 has no
+/// entries until its README figures are re-measured.
+pub const TIME_US: &[(&str, usize, f64)] = &[
+    ("AES-CTR", 64, 64.0 / 4.37),
+    ("AES-CTR", 128, 128.0 / 7.57),
+    ("AES-CTR", 256, 256.0 / 11.74),
+    ("AES-CTR", 512, 512.0 / 16.28),
+    ("AES-CTR", 1024, 1024.0 / 20.27),
+    ("AES-CTR", 2048, 2048.0 / 23.01),
+    ("AES-CTR", 4096, 4096.0 / 24.70),
+    ("AES-CTR", 8192, 8192.0 / 25.58),
+    ("AES-CTR", 16 * 1024, 16384.0 / 26.06),
+    ("AES-CTR", 32 * 1024, 32768.0 / 26.32),
+    ("RSA-2048", 256, 219_000.0),
+];
+
+/// The baseline time in microseconds for `label` at `data_size`, if any.
+pub fn time_us(label: &str, data_size: usize) -> Option<f64> {
+    TIME_US
+        .iter()
+        .find(|&&(l, size, _)| l == label && size == data_size)
+        .map(|&(_, _, time_us)| time_us)
+}
+
+/// Log `result` with its change against the baseline.
+pub fn report(result: &BenchmarkResult<'_>) {
+    let baseline = time_us(result.algorithm, result.data_size);
+    let delta = Delta::between(baseline, result.time_us);
+    match baseline {
+        Some(baseline) => info!(
+            "Baseline, {}, DataSize: {}, Time: {}, Baseline: {}, Delta: {delta}",
+            result.algorithm,
+            result.data_size,
+            Elapsed(result.time_us),
+            Elapsed(baseline)
+        ),
+        None => info!(
+            "Baseline, {}, DataSize: {}, Time: {}, Delta: {delta}",
+            result.algorithm,
+            result.data_size,
+            Elapsed(result.time_us)
+        ),
+    }
+}
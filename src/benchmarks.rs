@@ -0,0 +1,348 @@
+//! The benchmarks, one module per engine, and the helpers they share.
+//!
+//! [`aes`], [`sha`], [`rsa`], [`rng`], [`ecc`] and [`hmac`] hold the benchmarks
+//! of their engine, [`compare`] the software baselines and [`concurrent`] the
+//! engines run together; each is compiled in with the feature of its group.
+//! The suite in `src/bin/main.rs` creates the drivers and runs them in order.
+//! This module holds what they share: timing, logging a result and the
+//! benchmark in progress.
+//!
+//! Firmware that wants to show crypto figures itself, e.g. in its UI, can
+//! call the entry points instead of running the standalone suite. Each takes
+//! the driver, runs one measurement and hands the driver back together with
+//! a [`BenchmarkResult`]; nothing is logged:
+//!
+//! ```ignore
+//! let (target, result) = aes::run_aes_benchmark(target, 1024, CipherMode::Ctr, 100)
+//!     .map_err(|(error, _)| error)?;
+//! show(result.algorithm, result.throughput_mb_per_s());
+//! ```
+//!
+//! [`aes::run_aes_benchmark`], [`sha::run_sha256_benchmark`] and
+//! [`rsa::run_rsa_benchmark`] run the [`crate::harness::Benchmark`] adapter of
+//! their engine with [`measure_blocking`](crate::harness::measure_blocking),
+//! so each one keeps the CPU until its engine is done.
+
+use core::cell::Cell;
+
+use critical_section::Mutex;
+use esp_hal::time::{Duration, Instant};
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+use log::{error, info};
+
+#[cfg(feature = "serial-abort")]
+use crate::abort;
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+use crate::error::BenchError;
+#[cfg(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "bench-rsa",
+    feature = "verify"
+))]
+use crate::hex::Hex;
+#[cfg(all(
+    feature = "histogram",
+    any(feature = "bench-aes", feature = "bench-rsa")
+))]
+use crate::histogram::Histogram;
+use crate::report::BenchmarkResult;
+#[cfg(any(
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+use crate::results;
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+use crate::stats::{saturation_point, BenchStats, Summary};
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+use crate::units::{Delta, Elapsed};
+
+#[cfg(feature = "bench-aes")]
+pub mod aes;
+#[cfg(feature = "soft-compare")]
+pub mod compare;
+#[cfg(feature = "concurrent")]
+pub mod concurrent;
+#[cfg(feature = "bench-ecc")]
+pub mod ecc;
+#[cfg(feature = "hmac-efuse")]
+pub mod hmac;
+#[cfg(feature = "bench-rng")]
+pub mod rng;
+#[cfg(feature = "bench-rsa")]
+pub mod rsa;
+#[cfg(feature = "bench-sha")]
+pub mod sha;
+
+/// Log the outcome of the known-answer test `name`, with the expected and
+/// actual bytes if they differ, and return whether it passed.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+pub fn known_answer(name: &str, expected: &[u8], actual: &[u8]) -> bool {
+    let passed = expected == actual;
+    if passed {
+        info!("{name}: PASS");
+    } else {
+        error!(
+            "{name}: FAIL, expected {}, got {}",
+            Hex(expected),
+            Hex(actual)
+        );
+    }
+    passed
+}
+
+/// Decode a hex string into a byte array at compile time. Whitespace is
+/// skipped.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-ecc"))]
+pub const fn hex_bytes<const N: usize>(hex: &str) -> [u8; N] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+
+    let hex = hex.as_bytes();
+    let mut out = [0_u8; N];
+    let mut i = 0;
+    let mut n = 0;
+    while i < hex.len() {
+        if hex[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        assert!(n < 2 * N, "too many hex digits");
+        let value = nibble(hex[i]);
+        out[n / 2] |= if n % 2 == 0 { value << 4 } else { value };
+        n += 1;
+        i += 1;
+    }
+    assert!(n == 2 * N, "too few hex digits");
+    out
+}
+
+/// Size of the firmware blob of the AES-CTR and SHA-256 blob benchmarks,
+/// 1 MB, more than fits in RAM at once.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub const BLOB_SIZE: usize = 1024 * 1024;
+
+/// Relative throughput gain below which one step of a size sweep counts as
+/// flat for [`log_saturation`].
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub const SATURATION_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// Log the data size from which the `label` throughput of a sweep saturates,
+/// i.e. every further size gains less than [`SATURATION_THRESHOLD_PERCENT`],
+/// or that it still rises at the largest size.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub fn log_saturation(label: &str, data_sizes: &[usize], throughputs: &[f64]) {
+    match saturation_point(
+        data_sizes,
+        throughputs,
+        SATURATION_THRESHOLD_PERCENT / 100.0,
+    ) {
+        Some(size) => info!(
+            "{label} saturates at ~{size} bytes (gains below {SATURATION_THRESHOLD_PERCENT}% per step beyond)"
+        ),
+        None => info!(
+            "{label} does not saturate up to {} bytes",
+            data_sizes.last().copied().unwrap_or(0)
+        ),
+    }
+}
+
+/// Record `result` for the machine-readable output at the end of the run
+/// (see `log_results` of the suite). Does nothing without one of the `csv-output`,
+/// `json-output`, `prometheus-output` and `baseline` features, so the
+/// benchmarks can call it unconditionally.
+#[cfg_attr(
+    not(any(
+        feature = "csv-output",
+        feature = "json-output",
+        feature = "prometheus-output",
+        feature = "baseline"
+    )),
+    allow(unused_variables)
+)]
+pub fn log_result(result: &BenchmarkResult) {
+    #[cfg(any(
+        feature = "csv-output",
+        feature = "json-output",
+        feature = "prometheus-output",
+        feature = "baseline"
+    ))]
+    results::record(result);
+}
+
+/// Log the leading output `bytes` of a benchmark over `size` bytes as hex,
+/// for comparing against a reference by eye.
+#[cfg(all(
+    feature = "hex-dump",
+    any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa")
+))]
+pub fn log_hex(label: &str, size: usize, bytes: &[u8]) {
+    info!("{label}, DataSize: {size}, Output: {}", Hex(bytes));
+}
+
+/// Buckets of each per-iteration time histogram.
+#[cfg(all(
+    feature = "histogram",
+    any(feature = "bench-aes", feature = "bench-rsa")
+))]
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Log the per-iteration times `samples` of a benchmark over `size` bytes as
+/// a text histogram, one line per bucket, to show a distribution the mean
+/// and standard deviation hide, e.g. a few iterations slowed by interrupts.
+#[cfg(all(
+    feature = "histogram",
+    any(feature = "bench-aes", feature = "bench-rsa")
+))]
+pub fn log_histogram(label: &str, size: usize, samples: &[u32]) {
+    let Some(histogram) = Histogram::<HISTOGRAM_BUCKETS>::of(samples) else {
+        return;
+    };
+    info!(
+        "Histogram, {label}, DataSize: {size}, Samples: {}, Bucket: {} us",
+        samples.len(),
+        histogram.width
+    );
+    for row in histogram.rows() {
+        info!("{row}");
+    }
+}
+
+/// Per-iteration times of a sweep size for its log line, e.g. `Time: min
+/// 50.00 us, median 50.50 us, p95 52.00 us, max 61.00 us, mean 50.71 us,
+/// StdDev 1.42 us`.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub struct TimeDistribution<'a>(&'a BenchStats);
+
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+impl core::fmt::Display for TimeDistribution<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let stats = self.0;
+        write!(
+            f,
+            "Time: min {}, median {}, p95 {}, max {}, mean {}, StdDev {}",
+            Elapsed::from_micros(stats.min.into()),
+            Elapsed(stats.median),
+            Elapsed::from_micros(stats.p95.into()),
+            Elapsed::from_micros(stats.max.into()),
+            Elapsed(stats.mean),
+            Elapsed(stats.std_dev)
+        )
+    }
+}
+
+/// Run `f` once and return its result together with how long it took.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start_time = Instant::now();
+    let result = f();
+    (result, start_time.elapsed())
+}
+
+/// Readings [`timestamp_overhead`] takes the minimum of.
+pub const TIMESTAMP_OVERHEAD_READINGS: usize = 8;
+
+/// The cost of an `Instant::now()`/`elapsed()` pair, subtracted from
+/// per-iteration samples. The first reading pays for cache misses on the
+/// timer code, so this is the minimum of [`TIMESTAMP_OVERHEAD_READINGS`]
+/// back-to-back readings rather than a single cold one.
+pub fn timestamp_overhead() -> Duration {
+    (0..TIMESTAMP_OVERHEAD_READINGS)
+        .map(|_| {
+            let start_time = Instant::now();
+            start_time.elapsed()
+        })
+        .min()
+        .unwrap_or(Duration::ZERO)
+}
+
+/// Data size of the AES-CTR and SHA-256 cold start measurements.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub const COLD_START_SIZE: usize = 1024;
+
+/// Warm operations averaged after the cold one in each cold start
+/// measurement.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+pub const COLD_START_WARM_ITERATIONS: usize = 20;
+
+/// Log the first operation of `label` over `size` bytes (`cold_us`) next to
+/// the mean of the [`COLD_START_WARM_ITERATIONS`] after it (`warm_us`).
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+pub fn log_cold_start(label: &str, size: usize, cold_us: u64, warm_us: f64) {
+    info!(
+        "Cold start, {label}, DataSize: {size}, Cold: {}, Warm: {} (mean of {COLD_START_WARM_ITERATIONS}), Penalty: {:+} ({})",
+        Elapsed::from_micros(cold_us),
+        Elapsed(warm_us),
+        Elapsed(cold_us as f64 - warm_us),
+        Delta::between(Some(warm_us), cold_us as f64)
+    );
+}
+
+/// Gate and ungate cycles timed by each clock gating wake measurement.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub const WAKE_ITERATIONS: usize = 100;
+
+/// Data size of the operation timed after each ungating, one AES block and
+/// one SHA-256 block.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub const WAKE_SIZE: usize = 64;
+
+/// Log the time of creating the driver again after its clock was gated
+/// (`ungate`), of the first operation after it (`first`) and of an operation
+/// on the driver kept alive (`steady`), all in microseconds per operation.
+/// The wake latency is what ungating adds to the operation.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub fn log_wake(label: &str, ungate: &[u32], first: &[u32], steady: &[u32]) {
+    let mean = |samples: &[u32]| Summary::of(samples.iter().map(|&us| us as f64)).mean;
+    let (ungate, first, steady) = (mean(ungate), mean(first), mean(steady));
+    info!(
+        "Clock gating wake, {label}, DataSize: {WAKE_SIZE}, Ungate: {}, First operation: {}, Steady state: {}, Wake latency: {:+}",
+        Elapsed(ungate),
+        Elapsed(first),
+        Elapsed(steady),
+        Elapsed(ungate + first - steady)
+    );
+}
+
+/// The benchmark in progress and its data size, if it sweeps sizes.
+static CURRENT_BENCHMARK: Mutex<Cell<(&'static str, Option<usize>)>> =
+    Mutex::new(Cell::new(("start-up", None)));
+
+/// Record `benchmark` at `size` as in progress, so a panic names it.
+pub fn set_current_benchmark(benchmark: &'static str, size: Option<usize>) {
+    critical_section::with(|cs| CURRENT_BENCHMARK.borrow(cs).set((benchmark, size)));
+}
+
+/// The benchmark in progress and its data size, if it sweeps sizes, for a
+/// panic handler to name.
+pub fn current_benchmark() -> (&'static str, Option<usize>) {
+    critical_section::with(|cs| CURRENT_BENCHMARK.borrow(cs).get())
+}
+
+/// Log that the benchmark in progress failed with `error`; the suite goes on
+/// with the next one.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+pub fn failed(error: BenchError) {
+    let (benchmark, size) = current_benchmark();
+    match size {
+        Some(size) => error!("{benchmark} size={size} failed: {error}"),
+        None => error!("{benchmark} failed: {error}"),
+    }
+}
+
+/// Whether an abort of the run was requested over serial (`serial-abort`
+/// feature). Long benchmark loops check this between iterations and the
+/// suite between benchmarks.
+pub fn aborted() -> bool {
+    #[cfg(feature = "serial-abort")]
+    return abort::requested();
+    #[cfg(not(feature = "serial-abort"))]
+    false
+}
@@ -0,0 +1,3303 @@
+//! AES-256 benchmarks on the engine's DMA path (`bench-aes`).
+//!
+//! [`run_aes_benchmark`] measures one cipher mode on an [`AesTarget`] through
+//! the [`AesDmaBenchmark`] adapter and logs nothing. The other benchmarks run
+//! on an [`AesBench`], the driver together with the DMA buffers the whole
+//! suite shares, take it by value and hand it back, also after an error, so
+//! the next one can run; they log their figures as they go.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use esp_hal::aes::dma::{AesDma, CipherMode};
+use esp_hal::aes::{Aes, Key, Mode};
+use esp_hal::dma::{
+    BurstConfig, DmaDescriptor, DmaError, DmaRxBuf, DmaTxBuf, DmaTxBuffer, Preparation,
+    TransferDirection, CHUNK_SIZE,
+};
+use esp_hal::peripherals;
+#[cfg(feature = "bench-sha")]
+use esp_hal::sha::{Sha, Sha256};
+use esp_hal::time::{Duration, Instant};
+use log::{debug, error, info, warn};
+
+#[cfg(feature = "hex-dump")]
+use super::log_hex;
+#[cfg(feature = "histogram")]
+use super::log_histogram;
+#[cfg(feature = "bench-sha")]
+use super::sha::{benchmark_single_sha256, hmac_sha256, sha256_finish, sha256_update};
+use super::{
+    aborted, failed, hex_bytes, known_answer, log_cold_start, log_result, log_saturation, log_wake,
+    set_current_benchmark, timed, timestamp_overhead, TimeDistribution, BLOB_SIZE, COLD_START_SIZE,
+    COLD_START_WARM_ITERATIONS, WAKE_ITERATIONS, WAKE_SIZE,
+};
+#[cfg(feature = "datasheet-power")]
+use crate::datasheet_power;
+use crate::error::{BenchError, BusyWait};
+use crate::ghash::{self, Ghash};
+use crate::harness::{self, yield_now, Benchmark};
+#[cfg(feature = "raw-timings")]
+use crate::raw_timings;
+use crate::report::BenchmarkResult;
+use crate::stats::{BenchStats, Running, Samples, Summary};
+#[cfg(feature = "thresholds")]
+use crate::thresholds;
+use crate::units::{Delta, Elapsed, Throughput};
+
+/// AES-256 key of the AES benchmarks, all zeros.
+pub const AES_KEY: [u8; 32] = [0; 32];
+
+/// The AES DMA driver and the buffers an AES benchmark runs in.
+pub struct AesTarget<'d> {
+    /// The driver, bound to a DMA channel with `Aes::with_dma`.
+    pub aes: AesDma<'d>,
+    /// Receives the output of the engine.
+    pub output: DmaRxBuf,
+    /// Holds the data fed to the engine.
+    pub input: DmaTxBuf,
+}
+
+impl AesTarget<'_> {
+    /// Largest data size the buffers can hold.
+    pub fn capacity(&self) -> usize {
+        self.input.capacity().min(self.output.capacity())
+    }
+}
+
+/// Run the first `size` bytes of the input buffer through the AES engine in
+/// `mode` with [`AES_KEY`], `iterations` times, and return the mean time per
+/// run.
+///
+/// `size` is rounded down to whole blocks and limited to the buffer capacity;
+/// the result reports the size actually used, labelled e.g. `AES-CTR`. On an
+/// error the target is returned along with it, except after a transfer that
+/// did not finish within [`ENGINE_TIMEOUT`](crate::error::ENGINE_TIMEOUT),
+/// which takes the driver and buffers with it.
+pub fn run_aes_benchmark(
+    target: AesTarget<'_>,
+    size: usize,
+    mode: CipherMode,
+    iterations: usize,
+) -> Result<(AesTarget<'_>, BenchmarkResult<'static>), (BenchError, Option<AesTarget<'_>>)> {
+    let mut benchmark = AesDmaBenchmark::new(target, size, mode);
+    let result = harness::measure_blocking(&mut benchmark, iterations);
+    match (result, benchmark.into_target()) {
+        (Ok(result), Some(target)) => Ok((target, result)),
+        // Every run puts the target back unless its transfer hung, so this
+        // one does not come up.
+        (Ok(_), None) => Err((BenchError::TransferWait, None)),
+        (Err(error), target) => Err((error, target)),
+    }
+}
+
+/// Run exactly `data`, e.g. a firmware image embedded with `include_bytes!`,
+/// through the AES engine in `mode` with [`AES_KEY`],
+/// `iterations` times, and return the mean time per pass together with the
+/// spread of the per-pass times in microseconds.
+///
+/// Data larger than the buffers is processed in chunks of the buffer
+/// capacity, each copied into the input buffer before its transfer; a partial
+/// final block is padded with zeros. Only the transfers are timed. Every
+/// chunk starts from a fresh engine state, as with separate `process()`
+/// calls. The result reports `data.len()` as its size. On a DMA error the
+/// target is returned along with the error.
+pub fn run_aes_benchmark_on<'d>(
+    mut target: AesTarget<'d>,
+    data: &[u8],
+    mode: CipherMode,
+    iterations: usize,
+) -> Result<(AesTarget<'d>, BenchmarkResult<'static>, Summary), (DmaError, AesTarget<'d>)> {
+    let chunk_size = target.capacity() / 16 * 16;
+    let mut passes = Running::new();
+    let mut total_us = 0;
+
+    for _ in 0..iterations {
+        let mut pass_us = 0;
+        for chunk in data.chunks(chunk_size) {
+            let len = chunk.len().next_multiple_of(16);
+            let input = &mut target.input.as_mut_slice()[..len];
+            input[..chunk.len()].copy_from_slice(chunk);
+            input[chunk.len()..].fill(0);
+
+            let start_time = Instant::now();
+            let transfer = match target.aes.process(
+                len / 16,
+                target.output,
+                target.input,
+                Mode::Encryption256,
+                mode,
+                AES_KEY,
+            ) {
+                Ok(transfer) => transfer,
+                Err((error, aes, output, input)) => {
+                    return Err((error, AesTarget { aes, output, input }));
+                }
+            };
+            let (aes, output, input) = transfer.wait();
+            pass_us += start_time.elapsed().as_micros();
+            target = AesTarget { aes, output, input };
+        }
+        passes.push(pass_us as f64);
+        total_us += pass_us;
+    }
+
+    let result = BenchmarkResult {
+        algorithm: aes_label(mode),
+        data_size: data.len(),
+        iterations,
+        time_us: total_us as f64 / iterations.max(1) as f64,
+    };
+    Ok((target, result, passes.summary()))
+}
+
+/// The result label of an AES benchmark in `mode`.
+pub(crate) fn aes_label(mode: CipherMode) -> &'static str {
+    match mode {
+        CipherMode::Ecb => "AES-ECB",
+        CipherMode::Cbc => "AES-CBC",
+        CipherMode::Ofb => "AES-OFB",
+        CipherMode::Ctr => "AES-CTR",
+        CipherMode::Cfb8 => "AES-CFB8",
+        CipherMode::Cfb128 => "AES-CFB128",
+    }
+}
+
+/// The first `size` bytes of the input buffer through the AES engine in
+/// `mode`, AES-256 with [`AES_KEY`].
+pub struct AesDmaBenchmark<'d> {
+    target: Option<AesTarget<'d>>,
+    size: usize,
+    mode: CipherMode,
+}
+
+impl<'d> AesDmaBenchmark<'d> {
+    /// A benchmark of `size` bytes on `target`, rounded down to whole blocks
+    /// and limited to the buffer capacity.
+    pub fn new(target: AesTarget<'d>, size: usize, mode: CipherMode) -> Self {
+        let size = size.min(target.capacity()) / 16 * 16;
+        Self {
+            target: Some(target),
+            size,
+            mode,
+        }
+    }
+
+    /// Hand back the driver and buffers, or `None` after a transfer hung:
+    /// dropping it dropped them too, and the caller has to attach the AES
+    /// engine to its DMA channel again.
+    pub fn into_target(self) -> Option<AesTarget<'d>> {
+        self.target
+    }
+}
+
+impl<'l> Benchmark<'l> for AesDmaBenchmark<'_> {
+    fn setup(&mut self) {}
+
+    /// Fails with [`BenchError::TransferWait`] if the transfer is not done
+    /// within [`ENGINE_TIMEOUT`](crate::error::ENGINE_TIMEOUT), and so does
+    /// every run after that.
+    async fn run(&mut self) -> Result<(), BenchError> {
+        // The target is only missing after a hung transfer took it.
+        let AesTarget { aes, output, input } =
+            self.target.take().ok_or(BenchError::TransferWait)?;
+        let transfer = match aes.process(
+            self.size / 16,
+            output,
+            input,
+            Mode::Encryption256,
+            self.mode,
+            AES_KEY,
+        ) {
+            Ok(transfer) => transfer,
+            Err((error, aes, output, input)) => {
+                self.target = Some(AesTarget { aes, output, input });
+                return Err(BenchError::Process(error));
+            }
+        };
+        let mut busy = BusyWait::default();
+        while !transfer.is_done() {
+            if busy.expired() {
+                drop(transfer);
+                return Err(BenchError::TransferWait);
+            }
+            yield_now().await;
+        }
+        let (aes, output, input) = transfer.wait();
+        self.target = Some(AesTarget { aes, output, input });
+        Ok(())
+    }
+
+    fn report(&self, iterations: usize, time_us: f64) -> BenchmarkResult<'l> {
+        BenchmarkResult {
+            algorithm: aes_label(self.mode),
+            data_size: self.size,
+            iterations,
+            time_us,
+        }
+    }
+}
+
+/// Number of timed AES operations per data size, fewer for a `quick` run.
+pub const AES_ITERATIONS: usize = if cfg!(feature = "quick") { 10 } else { 100 };
+
+/// Name of the DMA channel the AES engine uses, selected by the
+/// `aes-dma-ch1` and `aes-dma-ch2` features (`DMA_CH0` otherwise).
+#[cfg(not(any(feature = "aes-dma-ch1", feature = "aes-dma-ch2")))]
+pub const AES_DMA_CHANNEL: &str = "DMA_CH0";
+#[cfg(feature = "aes-dma-ch1")]
+pub const AES_DMA_CHANNEL: &str = "DMA_CH1";
+#[cfg(feature = "aes-dma-ch2")]
+pub const AES_DMA_CHANNEL: &str = "DMA_CH2";
+
+/// Largest DMA buffer the AES benchmarks try to allocate, which is also the
+/// largest data size they run.
+pub const MAX_DMA_BUFFER_SIZE: usize = 32 * 1024;
+
+/// Smallest DMA buffer accepted before giving up on the AES benchmarks.
+pub const MIN_DMA_BUFFER_SIZE: usize = 64;
+
+/// The AES DMA driver together with the DMA buffers shared by all AES
+/// benchmarks.
+pub struct AesBench<'d> {
+    pub(crate) aes: AesDma<'d>,
+    pub(crate) output: DmaRxBuf,
+    pub(crate) input: DmaTxBuf,
+}
+
+/// What the AES helpers return: `T`, by default the bench itself, or the
+/// error together with a bench that can run the next benchmark. The bench is
+/// `None` if a transfer hung and the heap had no room left for the buffers
+/// to attach the engine again.
+pub type AesResult<'d, T = AesBench<'d>> = Result<T, (BenchError, Option<AesBench<'d>>)>;
+
+impl<'d> AesBench<'d> {
+    /// Allocate the DMA buffers on the heap, starting at `max_size` and
+    /// halving the size until the allocation succeeds.
+    ///
+    /// Fails with [`BenchError::DmaBufferAlloc`] if not even
+    /// [`MIN_DMA_BUFFER_SIZE`] bytes fit.
+    pub fn allocate(aes: AesDma<'d>, max_size: usize) -> Result<Self, BenchError> {
+        let mut size = max_size;
+        while size >= MIN_DMA_BUFFER_SIZE {
+            if let Some((output, input)) = allocate_dma_buffers(size) {
+                if size < max_size {
+                    warn!("Could not allocate {max_size} byte DMA buffers, using {size} bytes");
+                } else {
+                    info!("Allocated {size} byte DMA buffers");
+                }
+                return Ok(Self { aes, output, input });
+            }
+            size /= 2;
+        }
+        Err(BenchError::DmaBufferAlloc {
+            size: MIN_DMA_BUFFER_SIZE,
+        })
+    }
+
+    /// Free the DMA buffers and return the driver.
+    #[cfg(not(feature = "button-trigger"))]
+    pub fn release(self) -> AesDma<'d> {
+        free_dma_buffers(self.output, self.input);
+        self.aes
+    }
+
+    /// Largest data size the buffers can hold.
+    pub fn capacity(&self) -> usize {
+        self.input.capacity().min(self.output.capacity())
+    }
+
+    /// Encrypt the first `len` bytes of the input buffer in CTR mode, calling
+    /// `while_busy` repeatedly until the transfer completes.
+    ///
+    /// The hardware starts from an all-zero initial counter block; see
+    /// [`AesBench::encrypt_ctr_with_iv`] for other IVs.
+    pub fn encrypt(self, len: usize, while_busy: impl FnMut()) -> AesResult<'d> {
+        self.transform(len, CipherMode::Ctr, AES_KEY, while_busy)
+    }
+
+    /// Like [`AesBench::encrypt`], but with a pair of DMA buffers of `len`
+    /// bytes allocated for this call in place of the shared ones and freed
+    /// again afterwards, as code that does not keep its buffers would.
+    fn encrypt_with_fresh_buffers(mut self, len: usize) -> AesResult<'d> {
+        let Some((output, input)) = allocate_dma_buffers(len) else {
+            return Err((BenchError::DmaBufferAlloc { size: len }, Some(self)));
+        };
+        let shared_output = core::mem::replace(&mut self.output, output);
+        let shared_input = core::mem::replace(&mut self.input, input);
+        let (result, mut bench) = match self.encrypt(len, || {}) {
+            Ok(bench) => (Ok(()), bench),
+            Err((error, Some(bench))) => (Err(error), bench),
+            Err((error, None)) => {
+                free_dma_buffers(shared_output, shared_input);
+                return Err((error, None));
+            }
+        };
+        // After a hung transfer these are the unused buffers of `reattach`
+        // rather than the ones the channel may still write to.
+        let output = core::mem::replace(&mut bench.output, shared_output);
+        let input = core::mem::replace(&mut bench.input, shared_input);
+        free_dma_buffers(output, input);
+        match result {
+            Ok(()) => Ok(bench),
+            Err(error) => Err((error, Some(bench))),
+        }
+    }
+
+    /// Encrypt `data` in place with AES-256-CTR under `key`, starting from the
+    /// initial counter block `iv`.
+    ///
+    /// The HAL resets the engine for every DMA transfer and so always starts
+    /// the hardware counter from zero. To support any IV, the counter blocks
+    /// are generated on the CPU, encrypted with AES-ECB over DMA and XORed
+    /// into `data`. As in OpenSSL's `aes-256-ctr` and NIST SP 800-38A, `iv` is
+    /// the first counter block, interpreted as a big-endian 128-bit integer
+    /// that is incremented for each following block; the key bytes are used
+    /// in the order given.
+    pub fn encrypt_ctr_with_iv(
+        self,
+        key: [u8; 32],
+        iv: &[u8; 16],
+        data: &mut [u8],
+    ) -> AesResult<'d> {
+        let mut counter = u128::from_be_bytes(*iv);
+        self.encrypt_counter_blocks(key, data, || {
+            let block = counter.to_be_bytes();
+            counter = counter.wrapping_add(1);
+            block
+        })
+    }
+
+    /// Encrypt `data` in place with AES-256-CTR under `key` with the GCM
+    /// counter block layout: the 96-bit `nonce` followed by a big-endian
+    /// 32-bit block counter starting at `counter`.
+    ///
+    /// Only the counter is incremented, wrapping around to zero after
+    /// `u32::MAX` without carrying into the nonce, as `inc32` in NIST SP
+    /// 800-38D. Like [`AesBench::encrypt_ctr_with_iv`] the counter blocks
+    /// are generated on the CPU, since the hardware counter is always the
+    /// whole 128-bit block from zero.
+    pub fn encrypt_ctr32(
+        self,
+        key: [u8; 32],
+        nonce: &[u8; 12],
+        mut counter: u32,
+        data: &mut [u8],
+    ) -> AesResult<'d> {
+        let mut block = [0_u8; 16];
+        block[..12].copy_from_slice(nonce);
+        self.encrypt_counter_blocks(key, data, || {
+            block[12..].copy_from_slice(&counter.to_be_bytes());
+            counter = counter.wrapping_add(1);
+            block
+        })
+    }
+
+    /// XOR `data` with the encryption of the counter blocks `next_block`
+    /// returns one after the other, encrypted with AES-ECB over DMA in
+    /// chunks of the buffer capacity.
+    fn encrypt_counter_blocks(
+        mut self,
+        key: [u8; 32],
+        data: &mut [u8],
+        mut next_block: impl FnMut() -> [u8; 16],
+    ) -> AesResult<'d> {
+        let chunk_size = self.capacity() / 16 * 16;
+        for chunk in data.chunks_mut(chunk_size) {
+            let blocks = chunk.len().div_ceil(16);
+            for block in self.input.as_mut_slice()[..blocks * 16]
+                .as_chunks_mut::<16>()
+                .0
+            {
+                *block = next_block();
+            }
+            self = self.transform(blocks * 16, CipherMode::Ecb, key, || {})?;
+            for (byte, keystream) in chunk.iter_mut().zip(self.output.as_slice()) {
+                *byte ^= keystream;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Encrypt `data` in place with AES-256-GCM under `key` and the 96-bit
+    /// `nonce`, authenticating `aad` as well, and write the 16-byte tag.
+    ///
+    /// The keystream comes from [`AesBench::encrypt_ctr32`] starting at the
+    /// second counter block; GHASH runs on the CPU (see
+    /// [`crate::ghash`]).
+    fn encrypt_gcm(
+        mut self,
+        key: [u8; 32],
+        nonce: &[u8; 12],
+        aad: &[u8],
+        data: &mut [u8],
+        tag: &mut [u8; 16],
+    ) -> AesResult<'d> {
+        let j0 = ghash::pre_counter_block(nonce);
+
+        // The hash subkey H = E(0) and the tag mask E(J0) in one transfer.
+        self.input.as_mut_slice()[..16].fill(0);
+        self.input.as_mut_slice()[16..32].copy_from_slice(&j0);
+        self = self.transform(32, CipherMode::Ecb, key, || {})?;
+        let (h, mask) = self.output.as_slice()[..32].split_at(16);
+        let mut hash = Ghash::new(h.try_into().unwrap());
+        tag.copy_from_slice(mask);
+
+        self = self.encrypt_ctr32(key, nonce, 2, data)?;
+
+        hash.update(aad);
+        hash.update(data);
+        for (byte, hashed) in tag.iter_mut().zip(hash.finish(aad.len(), data.len())) {
+            *byte ^= hashed;
+        }
+        Ok(self)
+    }
+
+    /// Compute the AES-CMAC (NIST SP 800-38B, RFC 4493) of `data` under
+    /// `key`, AES-128 or AES-256 by its size, into `tag`.
+    ///
+    /// CMAC is a CBC-MAC with a zero IV whose final block is masked with a
+    /// subkey derived from E(0), so the engine runs in CBC mode and the tag
+    /// is the last output block. The HAL starts every transfer from a zero
+    /// IV, so a message larger than the buffers is split into chunks and the
+    /// first block of each chunk is XORed with the last output block of the
+    /// previous one instead.
+    fn cmac<K: Into<Key> + Copy>(
+        mut self,
+        key: K,
+        data: &[u8],
+        tag: &mut [u8; 16],
+    ) -> AesResult<'d> {
+        fn xor_block(block: &mut [u8; 16], value: u128) {
+            *block = (u128::from_be_bytes(*block) ^ value).to_be_bytes();
+        }
+
+        // The subkey doubling in GF(2^128) of SP 800-38B, 6.1.
+        fn double(value: u128) -> u128 {
+            (value << 1) ^ if value >> 127 == 1 { 0x87 } else { 0 }
+        }
+
+        self.input.as_mut_slice()[..16].fill(0);
+        self = self.transform(16, CipherMode::Ecb, key, || {})?;
+        let k1 = double(u128::from_be_bytes(
+            self.output.as_slice()[..16].try_into().unwrap(),
+        ));
+        let subkey = if !data.is_empty() && data.len().is_multiple_of(16) {
+            k1
+        } else {
+            double(k1)
+        };
+
+        let padded_len = data.len().div_ceil(16).max(1) * 16;
+        let chunk_size = self.capacity() / 16 * 16;
+        let mut chaining = 0_u128;
+        let mut offset = 0;
+        while offset < padded_len {
+            let len = chunk_size.min(padded_len - offset);
+            let input = &mut self.input.as_mut_slice()[..len];
+            let copied = data.len().saturating_sub(offset).min(len);
+            input[..copied].copy_from_slice(&data[offset..offset + copied]);
+            if copied < len {
+                input[copied] = 0x80;
+                input[copied + 1..].fill(0);
+            }
+            let blocks = input.as_chunks_mut::<16>().0;
+            xor_block(&mut blocks[0], chaining);
+            if offset + len == padded_len {
+                xor_block(blocks.last_mut().unwrap(), subkey);
+            }
+
+            self = self.transform(len, CipherMode::Cbc, key, || {})?;
+            chaining =
+                u128::from_be_bytes(self.output.as_slice()[len - 16..len].try_into().unwrap());
+            offset += len;
+        }
+        *tag = chaining.to_be_bytes();
+        Ok(self)
+    }
+
+    /// Run `len` bytes of the input buffer through the engine in
+    /// `cipher_mode` with the key size of `key`, calling `while_busy` until
+    /// the transfer completes.
+    ///
+    /// The engine works on whole blocks, so a partial final block is
+    /// processed in full; for CTR the first `len` output bytes are then the
+    /// ciphertext of the first `len` input bytes.
+    fn transform(
+        self,
+        len: usize,
+        cipher_mode: CipherMode,
+        key: impl Into<Key>,
+        mut while_busy: impl FnMut(),
+    ) -> AesResult<'d> {
+        let key = key.into();
+        let mode = match key {
+            Key::Key16(_) => Mode::Encryption128,
+            Key::Key32(_) => Mode::Encryption256,
+        };
+        let capacity = self.capacity();
+        let transfer = match self.aes.process(
+            len.div_ceil(16),
+            self.output,
+            self.input,
+            mode,
+            cipher_mode,
+            key,
+        ) {
+            Ok(transfer) => transfer,
+            Err((error, aes, output, input)) => {
+                return Err((
+                    BenchError::Process(error),
+                    Some(Self { aes, output, input }),
+                ));
+            }
+        };
+        let mut busy = BusyWait::default();
+        while !transfer.is_done() {
+            while_busy();
+            if busy.expired() {
+                drop(transfer);
+                return Err(Self::hung(capacity));
+            }
+        }
+        let (aes, output, input) = transfer.wait();
+        Ok(Self { aes, output, input })
+    }
+
+    /// The error of a transfer that hung, with the bench of [`reattach`] to
+    /// go on with. If that fails, the hang is logged and its error returned
+    /// instead.
+    ///
+    /// [`reattach`]: AesBench::reattach
+    fn hung(capacity: usize) -> (BenchError, Option<Self>) {
+        match Self::reattach(capacity) {
+            Ok(bench) => (BenchError::TransferWait, Some(bench)),
+            Err(error) => {
+                failed(BenchError::TransferWait);
+                (error, None)
+            }
+        }
+    }
+
+    /// A fresh driver and buffers of up to `capacity` bytes after a transfer
+    /// hung.
+    ///
+    /// Dropping the hung transfer stops the DMA channel but also drops the
+    /// driver and the buffers with it; the buffers stay leaked, since the
+    /// channel may still have been writing to them. Fails with
+    /// [`BenchError::DmaBufferAlloc`] if the heap has no room left for new
+    /// ones.
+    pub(crate) fn reattach(capacity: usize) -> Result<Self, BenchError> {
+        // SAFETY: the driver owning the AES peripheral and the DMA channel was
+        // dropped together with the hung transfer.
+        let aes = unsafe { attach_aes_dma() };
+        Self::allocate(aes, capacity)
+    }
+}
+
+/// Allocate word-aligned RX and TX DMA buffers of `size` bytes and their
+/// descriptors on the heap, or `None` if the heap is too small.
+///
+/// The DMA buffer types need `'static` slices, so the allocations are leaked
+/// until [`free_dma_buffers`] takes them back.
+fn allocate_dma_buffers(size: usize) -> Option<(DmaRxBuf, DmaTxBuf)> {
+    fn try_box<T: Copy>(len: usize, value: T) -> Option<Box<[T]>> {
+        let mut vec = Vec::new();
+        vec.try_reserve_exact(len).ok()?;
+        vec.resize(len, value);
+        Some(vec.into_boxed_slice())
+    }
+
+    let descriptor_count = size.div_ceil(CHUNK_SIZE);
+    let rx_buffer = try_box(size.div_ceil(4), 0_u32)?;
+    let tx_buffer = try_box(size.div_ceil(4), 0_u32)?;
+    let rx_descriptors = try_box(descriptor_count, DmaDescriptor::EMPTY)?;
+    let tx_descriptors = try_box(descriptor_count, DmaDescriptor::EMPTY)?;
+
+    // SAFETY: the leaked `u32` buffers are valid for `size` bytes until
+    // `free_dma_buffers` and are not referenced anywhere else.
+    let as_bytes = |buffer: Box<[u32]>| unsafe {
+        core::slice::from_raw_parts_mut(Box::leak(buffer).as_mut_ptr().cast::<u8>(), size)
+    };
+    let output = DmaRxBuf::new(Box::leak(rx_descriptors), as_bytes(rx_buffer)).ok()?;
+    let input = DmaTxBuf::new(Box::leak(tx_descriptors), as_bytes(tx_buffer)).ok()?;
+    Some((output, input))
+}
+
+/// Return the buffers and descriptors of [`allocate_dma_buffers`] to the
+/// heap.
+///
+/// Taking the buffers by value guarantees that no transfer still uses them.
+fn free_dma_buffers(output: DmaRxBuf, input: DmaTxBuf) {
+    let (rx_descriptors, rx_buffer) = output.split();
+    free_dma_buffer(rx_descriptors, rx_buffer);
+    free_input_buffer(input);
+}
+
+/// Return the input buffer of [`allocate_dma_buffers`] and its descriptors
+/// to the heap on its own, when the output buffer has to stay leaked.
+fn free_input_buffer(input: DmaTxBuf) {
+    let (descriptors, buffer) = input.split();
+    free_dma_buffer(descriptors, buffer);
+}
+
+/// Return one buffer of [`allocate_dma_buffers`] and its descriptors to the
+/// heap, once the `DmaRxBuf` or `DmaTxBuf` that referenced them was split.
+fn free_dma_buffer(descriptors: &'static mut [DmaDescriptor], buffer: &'static mut [u8]) {
+    // SAFETY: `allocate_dma_buffers` leaked these slices from boxes of
+    // exactly this length; the byte buffer was a `u32` box of
+    // `len.div_ceil(4)` words. The `DmaRxBuf` or `DmaTxBuf` that referenced
+    // them was consumed by `split`, so nothing refers to them any more.
+    unsafe {
+        drop(Box::from_raw(descriptors as *mut [DmaDescriptor]));
+        drop(Box::from_raw(core::ptr::slice_from_raw_parts_mut(
+            buffer.as_mut_ptr().cast::<u32>(),
+            buffer.len().div_ceil(4),
+        )));
+    }
+}
+
+/// Check [`AesBench::encrypt_ctr_with_iv`] against the CTR-AES256.Encrypt
+/// vector of NIST SP 800-38A (F.5.5), which is also what
+/// `openssl enc -aes-256-ctr -K <key> -iv <iv>` produces for this input.
+///
+/// Returns the AES instance and whether the ciphertext matched.
+fn self_test_aes_ctr_iv(bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, bool)> {
+    const KEY: [u8; 32] =
+        hex_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+    const IV: [u8; 16] = hex_bytes("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+    const PLAINTEXT: [u8; 64] = hex_bytes(
+        "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+         30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+    );
+    const CIPHERTEXT: [u8; 64] = hex_bytes(
+        "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5\
+         2b0930daa23de94ce87017ba2d84988ddfc9c58db67aada613c2dd08457941a6",
+    );
+
+    let mut data = PLAINTEXT;
+    let bench = bench.encrypt_ctr_with_iv(KEY, &IV, &mut data)?;
+    Ok((bench, data == CIPHERTEXT))
+}
+
+/// Check [`AesBench::encrypt_gcm`] against test case 16 of the original GCM
+/// specification (McGrew and Viega), AES-256 with additional data and a
+/// partial final block.
+///
+/// Returns the AES instance and whether the ciphertext and tag matched.
+fn self_test_aes_gcm(bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, bool)> {
+    const KEY: [u8; 32] =
+        hex_bytes("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308");
+    const NONCE: [u8; 12] = hex_bytes("cafebabefacedbaddecaf888");
+    const AAD: [u8; 20] = hex_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+    const PLAINTEXT: [u8; 60] = hex_bytes(
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+         1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+    );
+    const CIPHERTEXT: [u8; 60] = hex_bytes(
+        "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+         8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+    );
+    const TAG: [u8; 16] = hex_bytes("76fc6ece0f4e1768cddf8853bb2d551b");
+
+    let mut data = PLAINTEXT;
+    let mut tag = [0_u8; 16];
+    let bench = bench.encrypt_gcm(KEY, &NONCE, &AAD, &mut data, &mut tag)?;
+    Ok((bench, data == CIPHERTEXT && tag == TAG))
+}
+
+/// Check that [`AesBench::encrypt_ctr32`] wraps its 32-bit counter without
+/// carrying into the nonce: four blocks from counter `0xFFFF_FFFE` have to
+/// match two blocks of [`AesBench::encrypt_ctr_with_iv`] from
+/// `nonce || fffffffe` followed by two from `nonce || 00000000`.
+///
+/// Returns the AES instance and whether the keystreams matched.
+fn self_test_aes_ctr32_wrap(bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, bool)> {
+    const NONCE: [u8; 12] = hex_bytes("cafebabefacedbaddecaf888");
+    const IV: [u8; 16] = hex_bytes("cafebabefacedbaddecaf888fffffffe");
+    const WRAPPED_IV: [u8; 16] = hex_bytes("cafebabefacedbaddecaf88800000000");
+
+    let mut keystream = [0_u8; 64];
+    let bench = bench.encrypt_ctr32(AES_KEY, &NONCE, 0xFFFF_FFFE, &mut keystream)?;
+    let mut expected = [0_u8; 64];
+    let (before, after) = expected.split_at_mut(32);
+    let bench = bench.encrypt_ctr_with_iv(AES_KEY, &IV, before)?;
+    let bench = bench.encrypt_ctr_with_iv(AES_KEY, &WRAPPED_IV, after)?;
+    Ok((bench, keystream == expected))
+}
+
+/// Check [`AesBench::cmac`] against the four AES-128 examples in section 4 of
+/// RFC 4493, which cover an empty message, a single block and complete and
+/// partial final blocks.
+///
+/// Returns the AES instance and whether every tag matched.
+fn self_test_aes_cmac(mut bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, bool)> {
+    const KEY: [u8; 16] = hex_bytes("2b7e151628aed2a6abf7158809cf4f3c");
+    const MESSAGE: [u8; 64] = hex_bytes(
+        "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+         30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+    );
+    const EXAMPLES: [(usize, [u8; 16]); 4] = [
+        (0, hex_bytes("bb1d6929e95937287fa37d129b756746")),
+        (16, hex_bytes("070a16b46b4d4144f79bdd9dd04a287c")),
+        (40, hex_bytes("dfa66747de9ae63030ca32611497c827")),
+        (64, hex_bytes("51f0bebf7e3b9d92fc49741779363cfe")),
+    ];
+
+    let mut passed = true;
+    for (len, expected) in EXAMPLES {
+        let mut tag = [0_u8; 16];
+        bench = bench.cmac(KEY, &MESSAGE[..len], &mut tag)?;
+        passed &= tag == expected;
+    }
+    Ok((bench, passed))
+}
+
+/// Check the AES-CTR path the throughput benchmarks time, a CTR transfer
+/// through `AesBench::transform` into the shared DMA buffers, with the key
+/// and plaintext of the CTR-AES256.Encrypt vector of NIST SP 800-38A (F.5.5).
+///
+/// The hardware counter always starts from the all-zero block, so the
+/// published ciphertext for the vector's counter cannot come out of this
+/// path; the expected ciphertext is the one for a zero counter block, as
+/// `openssl enc -aes-256-ctr -iv 00000000000000000000000000000000` gives.
+/// The published ciphertext is checked by `self_test_aes_ctr_iv`.
+///
+/// Returns the AES instance and whether the ciphertext matched.
+pub fn verify_aes_ctr(mut bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, bool)> {
+    const KEY: [u8; 32] =
+        hex_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+    const PLAINTEXT: [u8; 64] = hex_bytes(
+        "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+         30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+    );
+    const CIPHERTEXT: [u8; 64] = hex_bytes(
+        "8ea94863ba8fe940fe7032d13083bf7e3f38940a1579b3875e60c37ceb91dfb5\
+         27c63f97d00036c49c1dfb9161c39afcbe9218a879799e723852f46d728e8f3e",
+    );
+
+    bench.input.as_mut_slice()[..PLAINTEXT.len()].copy_from_slice(&PLAINTEXT);
+    let bench = bench.transform(PLAINTEXT.len(), CipherMode::Ctr, KEY, || {})?;
+    let passed = known_answer(
+        "AES-CTR DMA known-answer test (NIST SP 800-38A F.5.5 key, zero counter)",
+        &CIPHERTEXT,
+        &bench.output.as_slice()[..CIPHERTEXT.len()],
+    );
+    Ok((bench, passed))
+}
+
+/// Bytes of keystream compared with the software AES in each variant of
+/// the AES-CTR cross-check, 256 counter blocks.
+#[cfg(feature = "soft-aes-check")]
+pub const CROSS_CHECK_SIZE: usize = 4096;
+
+/// Initial counter block of the IV variant of the AES-CTR cross-check: the
+/// low 64 bits overflow after 16 blocks, so a counter that does not carry
+/// across 64-bit words or increments in the wrong byte order diverges there.
+#[cfg(feature = "soft-aes-check")]
+const CROSS_CHECK_IV: [u8; 16] = hex_bytes("000102030405060708fffffffffffff0");
+
+/// Compare the AES-CTR keystream of the engine with the software AES of the
+/// `aes` crate over [`CROSS_CHECK_SIZE`] bytes, once from the all-zero
+/// counter block of [`AesBench::encrypt`] and once from [`CROSS_CHECK_IV`]
+/// through [`AesBench::encrypt_ctr_with_iv`].
+///
+/// Both encrypt zeros, so the output is the keystream itself. Logs the first
+/// mismatching byte offset of each variant, if any, and returns the AES
+/// instance and whether both matched.
+#[cfg(feature = "soft-aes-check")]
+fn cross_check_aes_ctr(mut bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, bool)> {
+    use aes::cipher::{BlockEncrypt, KeyInit};
+    use aes::Aes256;
+
+    /// Offset of the first byte of `keystream` that differs from the software
+    /// CTR keystream under `key` from the counter block `iv`.
+    fn first_mismatch(key: &[u8; 32], iv: &[u8; 16], keystream: &[u8]) -> Option<usize> {
+        let cipher = Aes256::new(key.into());
+        let mut counter = u128::from_be_bytes(*iv);
+        keystream.chunks(16).enumerate().find_map(|(i, hardware)| {
+            let mut block = counter.to_be_bytes().into();
+            cipher.encrypt_block(&mut block);
+            counter = counter.wrapping_add(1);
+            let offset = hardware.iter().zip(&block).position(|(a, b)| a != b)?;
+            Some(i * 16 + offset)
+        })
+    }
+
+    let size = CROSS_CHECK_SIZE.min(bench.capacity());
+    bench.input.as_mut_slice()[..size].fill(0);
+    bench = bench.encrypt(size, || {})?;
+    let hardware = first_mismatch(&AES_KEY, &[0; 16], &bench.output.as_slice()[..size]);
+
+    let mut keystream = [0_u8; CROSS_CHECK_SIZE];
+    bench = bench.encrypt_ctr_with_iv(AES_KEY, &CROSS_CHECK_IV, &mut keystream)?;
+    let with_iv = first_mismatch(&AES_KEY, &CROSS_CHECK_IV, &keystream);
+
+    for (variant, len, mismatch) in [
+        ("hardware counter", size, hardware),
+        ("CPU counter from IV", CROSS_CHECK_SIZE, with_iv),
+    ] {
+        match mismatch {
+            None => info!("AES-CTR software cross-check ({variant}), DataSize: {len}: PASS"),
+            Some(offset) => error!(
+                "AES-CTR software cross-check ({variant}), DataSize: {len}: FAIL, first mismatch at offset {offset} (block {})",
+                offset / 16
+            ),
+        }
+    }
+    Ok((bench, hardware.is_none() && with_iv.is_none()))
+}
+
+/// Run the AES-CTR sweep over `data_sizes` and return the throughput in MB/s
+/// of each size.
+pub fn benchmark_aes_dma<'d>(
+    bench: AesBench<'d>,
+    data_sizes: &[usize],
+) -> AesResult<'d, (AesBench<'d>, Vec<f64>)> {
+    // Pre-warm the AES DMA
+    let (mut bench, _, _, _) = benchmark_single_aes_dma(bench, 64)?;
+    let mut throughputs = Vec::with_capacity(data_sizes.len());
+
+    // Benchmark for each data size
+    for &size in data_sizes {
+        if aborted() {
+            break;
+        }
+        set_current_benchmark("AES-CTR", Some(size));
+        let throughput;
+        let summary;
+        let times;
+        #[cfg(not(feature = "target-ci"))]
+        {
+            (bench, throughput, summary, times) = benchmark_single_aes_dma(bench, size)?;
+        }
+        #[cfg(feature = "target-ci")]
+        {
+            (bench, throughput, summary, times) = benchmark_single_aes_dma_to_target(bench, size)?;
+        }
+        match times {
+            Some(times) => info!(
+                "AES-CTR, DataSize: {size}, Throughput: {} (elapsed), Mean: {} ± {} (95% CI), StdDev: {}, {}",
+                Throughput(throughput / 1_000_000.0),
+                Throughput(summary.mean),
+                Throughput(summary.margin_95),
+                Throughput(summary.std_dev),
+                TimeDistribution(&times)
+            ),
+            None => info!(
+                "AES-CTR, DataSize: {size}, Throughput: {} (elapsed), Mean: {} ± {} (95% CI), StdDev: {}",
+                Throughput(throughput / 1_000_000.0),
+                Throughput(summary.mean),
+                Throughput(summary.margin_95),
+                Throughput(summary.std_dev)
+            ),
+        }
+        #[cfg(feature = "target-ci")]
+        log_target_ci("AES-CTR", size, &summary);
+        #[cfg(feature = "thresholds")]
+        thresholds::check_throughput("AES-CTR", size, throughput / 1_000_000.0);
+        #[cfg(feature = "datasheet-power")]
+        datasheet_power::log_throughput(
+            "AES-CTR",
+            size,
+            throughput / 1_000_000.0,
+            datasheet_power::AES_POWER_MW,
+        );
+        log_result(&BenchmarkResult {
+            algorithm: "AES-CTR",
+            data_size: size,
+            iterations: summary.count,
+            time_us: size as f64 / throughput * 1_000_000.0,
+        });
+        #[cfg(feature = "hex-dump")]
+        log_hex("AES-CTR", size, &bench.output.as_slice()[..size.min(16)]);
+        throughputs.push(throughput / 1_000_000.0);
+    }
+
+    Ok((bench, throughputs))
+}
+
+/// Measure the memory bandwidth ceiling for the AES sweep: the CPU copying
+/// each of `data_sizes` bytes from the AES input buffer to the output buffer,
+/// the same traffic the DMA moves for an encryption. Returns the throughput
+/// in MB/s of each size.
+pub fn benchmark_memcpy<'d>(
+    mut bench: AesBench<'d>,
+    data_sizes: &[usize],
+) -> (AesBench<'d>, Vec<f64>) {
+    let mut throughputs = Vec::with_capacity(data_sizes.len());
+    for &size in data_sizes {
+        let (input, output) = (bench.input.as_slice(), bench.output.as_mut_slice());
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            output[..size].copy_from_slice(core::hint::black_box(&input[..size]));
+            core::hint::black_box(&mut output[..size]);
+        }
+        let elapsed = start_time.elapsed();
+
+        let throughput = (AES_ITERATIONS * size) as f64 / elapsed.as_micros().max(1) as f64;
+        info!(
+            "memcpy, DataSize: {size}, Throughput: {}",
+            Throughput(throughput)
+        );
+        throughputs.push(throughput);
+    }
+    (bench, throughputs)
+}
+
+/// Log the AES-CTR throughput of each of `data_sizes` as a percentage of the
+/// memcpy throughput at the same size. Close to 100% means the engine runs at
+/// the speed of memory and is memory-bound; well below means the cipher
+/// itself is the bottleneck.
+pub fn log_memcpy_efficiency(data_sizes: &[usize], aes: &[f64], memcpy: &[f64]) {
+    for ((size, aes), memcpy) in data_sizes.iter().zip(aes).zip(memcpy) {
+        info!(
+            "AES-CTR, DataSize: {size}, {:.0}% of memcpy bandwidth ({} / {})",
+            aes / memcpy * 100.0,
+            Throughput(*aes),
+            Throughput(*memcpy)
+        );
+    }
+}
+
+/// Iteration counts of the iteration count sweep, ending at the largest.
+pub const ITERATION_SWEEP_COUNTS: [usize; 5] = [10, 50, 100, 500, 1000];
+
+/// Data size of the iteration count sweep.
+pub const ITERATION_SWEEP_SIZE: usize = 1024;
+
+/// Run [`ITERATION_SWEEP_SIZE`] byte AES-CTR encryptions at each of
+/// [`ITERATION_SWEEP_COUNTS`] individually timed iterations, a fresh run per
+/// count, and log a table of how the mean, standard deviation and 95%
+/// confidence interval settle as the count grows, to help pick iteration
+/// counts such as [`AES_ITERATIONS`].
+pub fn benchmark_iteration_sweep(mut bench: AesBench<'_>) -> AesResult<'_> {
+    const MAX_COUNT: usize = ITERATION_SWEEP_COUNTS[ITERATION_SWEEP_COUNTS.len() - 1];
+    if bench.capacity() < ITERATION_SWEEP_SIZE {
+        warn!(
+            "Skipping the iteration count sweep, it needs {ITERATION_SWEEP_SIZE} byte DMA buffers"
+        );
+        return Ok(bench);
+    }
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(ITERATION_SWEEP_SIZE, || {})?;
+
+    info!("Iteration sweep, AES-CTR, DataSize: {ITERATION_SWEEP_SIZE}");
+    info!("  Iterations |  Mean (us) | StdDev (us) | 95% CI");
+    for count in ITERATION_SWEEP_COUNTS {
+        let mut samples = Samples::<MAX_COUNT>::new();
+        for _ in 0..count {
+            let (result, elapsed) = timed(|| bench.encrypt(ITERATION_SWEEP_SIZE, || {}));
+            bench = result?;
+            samples.push(elapsed.as_micros());
+        }
+        let summary = Summary::of(samples.as_slice().iter().map(|&us| us as f64));
+        info!(
+            "  {count:>10} | {:>10.2} | {:>11.2} | ±{:.2}%",
+            summary.mean,
+            summary.std_dev,
+            summary.relative_margin_95() * 100.0
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data size of the sector benchmark, one flash sector.
+pub const SECTOR_SIZE: usize = 4096;
+
+/// Encryptions timed by the sector benchmark, ten times the sweep's.
+pub const SECTOR_ITERATIONS: usize = 1000;
+
+/// Measure AES-CTR at exactly one flash sector ([`SECTOR_SIZE`] bytes) with
+/// [`SECTOR_ITERATIONS`] individually timed encryptions, for a tighter
+/// figure at the size OTA and storage encryption work in than the sweep
+/// gives.
+pub fn benchmark_aes_sector(mut bench: AesBench<'_>) -> AesResult<'_> {
+    if bench.capacity() < SECTOR_SIZE {
+        warn!(
+            "AES-CTR sector: DMA buffers of {} bytes are smaller than a sector",
+            bench.capacity()
+        );
+        return Ok(bench);
+    }
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(SECTOR_SIZE, || {})?;
+
+    let mut samples = Samples::<SECTOR_ITERATIONS>::new();
+    for _ in 0..SECTOR_ITERATIONS {
+        if aborted() {
+            break;
+        }
+        let start_time = Instant::now();
+        bench = bench.encrypt(SECTOR_SIZE, || {})?;
+        samples.push(start_time.elapsed().as_micros());
+    }
+
+    let times = samples.as_slice();
+    let summary = Summary::of(times.iter().map(|&us| us as f64));
+    info!(
+        "AES-CTR sector, DataSize: {SECTOR_SIZE}, Iterations: {}, Time: {} ± {} (95% CI, ±{:.2}%), StdDev: {}, Min: {}, Max: {}, Throughput: {}",
+        summary.count,
+        Elapsed(summary.mean),
+        Elapsed(summary.margin_95),
+        summary.relative_margin_95() * 100.0,
+        Elapsed(summary.std_dev),
+        Elapsed::from_micros(times.iter().copied().min().unwrap_or(0).into()),
+        Elapsed::from_micros(times.iter().copied().max().unwrap_or(0).into()),
+        Throughput::of(SECTOR_SIZE, summary.mean)
+    );
+    #[cfg(feature = "raw-timings")]
+    raw_timings::emit_frame("AES-CTR (sector)", SECTOR_SIZE, times);
+    #[cfg(feature = "histogram")]
+    log_histogram("AES-CTR (sector)", SECTOR_SIZE, times);
+    log_result(&BenchmarkResult {
+        algorithm: "AES-CTR (sector)",
+        data_size: SECTOR_SIZE,
+        iterations: summary.count,
+        time_us: summary.mean,
+    });
+
+    Ok(bench)
+}
+
+/// Measure the step in cost where a message stops fitting the DMA buffers:
+/// AES-CTR over one byte less than their capacity, exactly the capacity and
+/// one byte more (32767, 32768 and 32769 bytes with the full 32 KB buffers),
+/// [`AES_ITERATIONS`] times each.
+///
+/// Messages are split into transfers of at most the capacity and every
+/// transfer is padded to whole blocks, so the last size takes a second
+/// transfer of a single block. Each size logs its transfers, blocks and DMA
+/// descriptors and the time per byte with its jump from the previous size;
+/// a block count other than `size.div_ceil(16)` is logged as an error.
+pub fn benchmark_aes_buffer_boundary(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let capacity = bench.capacity() / 16 * 16;
+    let transfers = |size: usize| {
+        (0..size.div_ceil(capacity))
+            .map(move |i| (size - i * capacity).min(capacity).next_multiple_of(16))
+    };
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(capacity, || {})?;
+
+    let mut previous_ns = None;
+    for size in [capacity - 1, capacity, capacity + 1] {
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            for len in transfers(size) {
+                bench = bench.encrypt(len, || {})?;
+            }
+        }
+        let time_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+        let per_byte_ns = time_us * 1000.0 / size as f64;
+
+        let blocks: usize = transfers(size).map(|len| len / 16).sum();
+        if blocks != size.div_ceil(16) {
+            error!(
+                "AES-CTR buffer boundary, DataSize: {size}: {blocks} blocks processed, expected {}",
+                size.div_ceil(16)
+            );
+        }
+        info!(
+            "AES-CTR buffer boundary, DataSize: {size}, Transfers: {}, Blocks: {blocks}, Descriptors: {}, Time: {}, Per byte: {per_byte_ns:.2} ns, Jump: {}",
+            transfers(size).count(),
+            transfers(size).map(|len| len.div_ceil(CHUNK_SIZE)).sum::<usize>(),
+            Elapsed(time_us),
+            Delta::between(previous_ns, per_byte_ns)
+        );
+        previous_ns = Some(per_byte_ns);
+    }
+
+    Ok(bench)
+}
+
+/// Sizes of the extra DMA buffer pairs the heap budget diagnostic tries to
+/// allocate next to the AES buffers.
+pub const HEAP_PROBE_SIZES: [usize; 6] = [1024, 2048, 4096, 8192, 16 * 1024, 32 * 1024];
+
+/// Heap taken by a pair of [`allocate_dma_buffers`] of `size` bytes: two
+/// word-aligned buffers and their descriptors, without allocator overhead.
+fn dma_buffer_footprint(size: usize) -> usize {
+    2 * (size.next_multiple_of(4) + size.div_ceil(CHUNK_SIZE) * size_of::<DmaDescriptor>())
+}
+
+/// Report how much of the heap the DMA buffers need, to size the heap.
+///
+/// The heap is set once at boot, so instead of shrinking it this allocates
+/// an extra RX/TX pair of each of [`HEAP_PROBE_SIZES`] on top of the AES
+/// buffers of `capacity` bytes and everything else in use, logging whether
+/// it fits and the heap it took, and frees it again. The largest pair that
+/// fits is the headroom left. The smallest workable heap is the heap in use
+/// apart from the AES buffers plus a pair of [`MAX_DMA_BUFFER_SIZE`] bytes.
+pub fn benchmark_heap_budget(capacity: usize) {
+    let heap_size = esp_alloc::HEAP.used() + esp_alloc::HEAP.free();
+    let in_use = esp_alloc::HEAP.used();
+    let mut largest = None;
+
+    for size in HEAP_PROBE_SIZES {
+        let before = esp_alloc::HEAP.used();
+        match allocate_dma_buffers(size) {
+            Some((output, input)) => {
+                let taken = esp_alloc::HEAP.used() - before;
+                free_dma_buffers(output, input);
+                info!("DMA buffer allocation, Size: {size}, Heap taken: {taken} bytes, Fits: yes");
+                largest = Some(size);
+            }
+            None => info!(
+                "DMA buffer allocation, Size: {size}, Needs: {} bytes, Free: {} bytes, Fits: no",
+                dma_buffer_footprint(size),
+                esp_alloc::HEAP.free()
+            ),
+        }
+    }
+
+    let others = in_use.saturating_sub(dma_buffer_footprint(capacity));
+    info!(
+        "Heap budget, Heap: {heap_size} bytes, In use: {in_use} bytes ({capacity} byte AES buffers: {} bytes), Largest extra pair: {}, Smallest heap for {MAX_DMA_BUFFER_SIZE} byte buffers: about {} bytes",
+        dma_buffer_footprint(capacity),
+        largest.unwrap_or(0),
+        others + dma_buffer_footprint(MAX_DMA_BUFFER_SIZE)
+    );
+}
+
+/// Data sizes of the AES-CTR buffer reuse benchmark; an extra pair of DMA
+/// buffers of each has to fit next to the shared ones.
+pub const BUFFER_REUSE_SIZES: [usize; 3] = [64, 1024, 4096];
+
+/// Compare [`AES_ITERATIONS`] AES-CTR encryptions of each of
+/// [`BUFFER_REUSE_SIZES`] in the shared DMA buffers, allocated once for the
+/// whole suite, with the same encryptions each allocating and freeing its own
+/// buffers, and log the time allocation adds and what reuse saves.
+pub fn benchmark_aes_buffer_reuse(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    for size in BUFFER_REUSE_SIZES {
+        if size > bench.capacity() {
+            break;
+        }
+        let (reused_us, fresh_us);
+        (bench, reused_us, fresh_us) = aes_reused_and_fresh_us(bench, size)?;
+        info!(
+            "AES-CTR buffer reuse, DataSize: {size}, Reused buffers: {}, Allocate per call: {}, Allocation: {}, Savings: {:.1}%",
+            Elapsed(reused_us),
+            Elapsed(fresh_us),
+            Elapsed(fresh_us - reused_us),
+            (fresh_us - reused_us) / fresh_us.max(f64::MIN_POSITIVE) * 100.0
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Mean time of [`AES_ITERATIONS`] AES-CTR encryptions of `size` bytes in
+/// the shared DMA buffers, and of as many each allocating and freeing its
+/// own buffers, in microseconds.
+fn aes_reused_and_fresh_us(
+    mut bench: AesBench<'_>,
+    size: usize,
+) -> AesResult<'_, (AesBench<'_>, f64, f64)> {
+    let start_time = Instant::now();
+    for _ in 0..AES_ITERATIONS {
+        bench = bench.encrypt(size, || {})?;
+    }
+    let reused_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+    let start_time = Instant::now();
+    for _ in 0..AES_ITERATIONS {
+        bench = bench.encrypt_with_fresh_buffers(size)?;
+    }
+    let fresh_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+    Ok((bench, reused_us, fresh_us))
+}
+
+/// Size of each block the heap pressure benchmark fills the heap with.
+#[cfg(feature = "heap-pressure")]
+pub const HEAP_PRESSURE_BLOCK: usize = 1024;
+
+/// Heap left free by the heap pressure benchmark on top of the DMA buffers
+/// of the largest size, for the allocator's own overhead.
+#[cfg(feature = "heap-pressure")]
+pub const HEAP_PRESSURE_HEADROOM: usize = 1024;
+
+/// Repeat the buffer reuse measurement for each of [`BUFFER_REUSE_SIZES`]
+/// with the heap nearly full: [`HEAP_PRESSURE_BLOCK`] byte blocks take all of
+/// it but the extra DMA buffers of the largest size and
+/// [`HEAP_PRESSURE_HEADROOM`]. Logs both runs and the change of the
+/// allocation cost, then frees the blocks.
+#[cfg(feature = "heap-pressure")]
+pub fn benchmark_aes_heap_pressure(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let sizes = BUFFER_REUSE_SIZES.map(|size| (size <= bench.capacity()).then_some(size));
+    let Some(largest) = sizes.iter().flatten().copied().max() else {
+        return Ok(bench);
+    };
+    let mut unloaded = [(0.0, 0.0); BUFFER_REUSE_SIZES.len()];
+    for (times, size) in unloaded.iter_mut().zip(sizes) {
+        if let Some(size) = size {
+            let (reused_us, fresh_us);
+            (bench, reused_us, fresh_us) = aes_reused_and_fresh_us(bench, size)?;
+            *times = (reused_us, fresh_us);
+        }
+    }
+
+    let free_before = esp_alloc::HEAP.free();
+    let reserve = dma_buffer_footprint(largest) + HEAP_PRESSURE_HEADROOM;
+    let mut filler: Vec<Vec<u8>> = Vec::with_capacity(free_before / HEAP_PRESSURE_BLOCK);
+    while filler.len() < filler.capacity()
+        && esp_alloc::HEAP.free() >= reserve + HEAP_PRESSURE_BLOCK
+    {
+        let mut block = Vec::new();
+        if block.try_reserve_exact(HEAP_PRESSURE_BLOCK).is_err() {
+            break;
+        }
+        block.resize(HEAP_PRESSURE_BLOCK, 0_u8);
+        filler.push(block);
+    }
+    let free_after = esp_alloc::HEAP.free();
+
+    for ((reused_us, fresh_us), size) in unloaded.into_iter().zip(sizes) {
+        let Some(size) = size else {
+            continue;
+        };
+        let (pressed_reused_us, pressed_fresh_us);
+        (bench, pressed_reused_us, pressed_fresh_us) = aes_reused_and_fresh_us(bench, size)?;
+        let allocation_us = fresh_us - reused_us;
+        let pressed_allocation_us = pressed_fresh_us - pressed_reused_us;
+        info!(
+            "AES-CTR heap pressure, DataSize: {size}, Free heap: {free_before} -> {free_after} bytes, Reused buffers: {} -> {}, Allocation: {} -> {} ({})",
+            Elapsed(reused_us),
+            Elapsed(pressed_reused_us),
+            Elapsed(allocation_us),
+            Elapsed(pressed_allocation_us),
+            Delta::between(Some(allocation_us), pressed_allocation_us)
+        );
+    }
+    drop(filler);
+
+    Ok(bench)
+}
+
+/// AES-CTR operations timed with each logging pattern by the logger jitter
+/// benchmark.
+pub const LOG_JITTER_ITERATIONS: usize = 50;
+
+/// Data size of each operation of the logger jitter benchmark.
+pub const LOG_JITTER_SIZE: usize = 1024;
+
+/// Measure how much logging between operations perturbs their timing: time
+/// [`LOG_JITTER_ITERATIONS`] AES-CTR encryptions of [`LOG_JITTER_SIZE`]
+/// bytes with each time logged only after the last one, then with an
+/// `info!` line after every encryption, and compare the spread.
+///
+/// The deferred lines are emitted too, so both patterns log the same.
+pub fn benchmark_log_jitter(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(LOG_JITTER_SIZE, || {})?;
+
+    let mut deferred = Samples::<LOG_JITTER_ITERATIONS>::new();
+    for _ in 0..LOG_JITTER_ITERATIONS {
+        let (result, elapsed) = timed(|| bench.encrypt(LOG_JITTER_SIZE, || {}));
+        bench = result?;
+        deferred.push(elapsed.as_micros());
+    }
+    for (i, &us) in deferred.as_slice().iter().enumerate() {
+        info!("Logger jitter sample {i} (deferred), Time: {us} us");
+    }
+
+    let mut interspersed = Samples::<LOG_JITTER_ITERATIONS>::new();
+    for i in 0..LOG_JITTER_ITERATIONS {
+        let (result, elapsed) = timed(|| bench.encrypt(LOG_JITTER_SIZE, || {}));
+        bench = result?;
+        interspersed.push(elapsed.as_micros());
+        info!(
+            "Logger jitter sample {i} (interspersed), Time: {} us",
+            elapsed.as_micros()
+        );
+    }
+
+    let spread = |samples: &Samples<LOG_JITTER_ITERATIONS>| {
+        let summary = Summary::of(samples.as_slice().iter().map(|&us| us as f64));
+        let max = samples.as_slice().iter().copied().max().unwrap_or(0);
+        (summary, max)
+    };
+    let (deferred, deferred_max) = spread(&deferred);
+    let (interspersed, interspersed_max) = spread(&interspersed);
+    info!(
+        "Logger jitter (AES-CTR, DataSize: {LOG_JITTER_SIZE}), Deferred: mean {}, std dev {}, max {}, Interspersed: mean {}, std dev {}, max {}, Mean delta: {}, Std dev delta: {:+}",
+        Elapsed(deferred.mean),
+        Elapsed(deferred.std_dev),
+        Elapsed::from_micros(deferred_max.into()),
+        Elapsed(interspersed.mean),
+        Elapsed(interspersed.std_dev),
+        Elapsed::from_micros(interspersed_max.into()),
+        Delta::between(Some(deferred.mean), interspersed.mean),
+        Elapsed(interspersed.std_dev - deferred.std_dev)
+    );
+
+    Ok(bench)
+}
+
+/// Data sizes of the AES-CTR 96/32 counter layout benchmark.
+pub const CTR32_SIZES: [usize; 3] = [64, 1024, 4096];
+
+/// Benchmark AES-256-CTR with the GCM counter layout of
+/// [`AesBench::encrypt_ctr32`], a 96-bit nonce and a 32-bit counter, over
+/// each of [`CTR32_SIZES`] [`AES_ITERATIONS`] times, and log it next to the
+/// full 128-bit counter of [`AesBench::encrypt_ctr_with_iv`].
+///
+/// Both generate the counter blocks on the CPU, so the delta is the cost of
+/// the layout alone. Its correctness is checked at startup, against GCM test
+/// case 16 and across the 32-bit wrap.
+pub fn benchmark_aes_ctr32(mut bench: AesBench<'_>) -> AesResult<'_> {
+    const MAX_SIZE: usize = CTR32_SIZES[CTR32_SIZES.len() - 1];
+    let mut data = [0xAB_u8; MAX_SIZE];
+    let nonce = [0_u8; 12];
+    let iv = [0_u8; 16];
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt_ctr32(AES_KEY, &nonce, 1, &mut data[..64])?;
+
+    for size in CTR32_SIZES {
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.encrypt_ctr32(AES_KEY, &nonce, 1, &mut data[..size])?;
+        }
+        let ctr32_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.encrypt_ctr_with_iv(AES_KEY, &iv, &mut data[..size])?;
+        }
+        let ctr128_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR 96/32 layout (AES-256), DataSize: {size}, Time: {}, Throughput: {}, 128-bit counter: {}, Delta: {}",
+            Elapsed(ctr32_us),
+            Throughput::of(size, ctr32_us),
+            Throughput::of(size, ctr128_us),
+            Delta::between(Some(ctr128_us), ctr32_us)
+        );
+        log_result(&BenchmarkResult {
+            algorithm: "AES-CTR (96/32)",
+            data_size: size,
+            iterations: AES_ITERATIONS,
+            time_us: ctr32_us,
+        });
+    }
+
+    Ok(bench)
+}
+
+/// Encrypt a [`BLOB_SIZE`] firmware blob end to end with AES-256-CTR in
+/// chunks of the DMA buffer capacity, as an OTA update streaming the image
+/// from flash would, and log the total time and effective throughput.
+///
+/// The hardware counter restarts from zero with every transfer, so the
+/// chunks encrypted that way do not form one keystream; the blob is also
+/// encrypted with [`AesBench::encrypt_ctr_with_iv`], whose counter runs on
+/// across the chunks, for the correct ciphertext.
+pub fn benchmark_aes_blob(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let chunk_size = bench.capacity() / 16 * 16;
+    let mut chunk = [0xAB_u8; MAX_DMA_BUFFER_SIZE];
+    let chunk = &mut chunk[..chunk_size];
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(chunk_size, || {})?;
+
+    let start_time = Instant::now();
+    for _ in 0..BLOB_SIZE.div_ceil(chunk_size) {
+        bench = bench.encrypt(chunk_size, || {})?;
+    }
+    let hardware_us = start_time.elapsed().as_micros() as f64;
+
+    let start_time = Instant::now();
+    for offset in (0..BLOB_SIZE).step_by(chunk_size) {
+        let counter = (offset / 16) as u128;
+        bench = bench.encrypt_ctr_with_iv(AES_KEY, &counter.to_be_bytes(), chunk)?;
+    }
+    let with_iv_us = start_time.elapsed().as_micros() as f64;
+
+    info!(
+        "AES-CTR 1 MB blob (AES-256, {chunk_size} byte chunks), Hardware counter: {} ({}), Continuous counter: {} ({})",
+        Elapsed(hardware_us),
+        Throughput::of(BLOB_SIZE, hardware_us),
+        Elapsed(with_iv_us),
+        Throughput::of(BLOB_SIZE, with_iv_us)
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "AES-CTR (1 MB blob)",
+        data_size: BLOB_SIZE,
+        iterations: 1,
+        time_us: with_iv_us,
+    });
+
+    Ok(bench)
+}
+
+/// Benchmark AES-CTR as a keystream generator.
+///
+/// Encrypting an all-zero buffer in CTR mode outputs the raw keystream, so
+/// this is the CTR sweep with the input zeroed first, reported as keystream
+/// throughput for stream-cipher use.
+pub fn benchmark_aes_keystream<'d>(mut bench: AesBench<'d>, data_sizes: &[usize]) -> AesResult<'d> {
+    bench.input.as_mut_slice().fill(0);
+
+    // Pre-warm the AES DMA
+    let (mut bench, _, _, _) = benchmark_single_aes_dma(bench, 64)?;
+
+    for &size in data_sizes {
+        let throughput;
+        let summary;
+        (bench, throughput, summary, _) = benchmark_single_aes_dma(bench, size)?;
+        info!(
+            "AES-CTR (keystream), DataSize: {size}, Throughput: {}, Mean: {} ± {} (95% CI), StdDev: {}",
+            Throughput(throughput / 1_000_000.0),
+            Throughput(summary.mean),
+            Throughput(summary.margin_95),
+            Throughput(summary.std_dev)
+        );
+        #[cfg(feature = "thresholds")]
+        thresholds::check_throughput("AES-CTR (keystream)", size, throughput / 1_000_000.0);
+        log_result(&BenchmarkResult {
+            algorithm: "AES-CTR (keystream)",
+            data_size: size,
+            iterations: AES_ITERATIONS,
+            time_us: size as f64 / throughput * 1_000_000.0,
+        });
+        #[cfg(feature = "hex-dump")]
+        log_hex(
+            "AES-CTR (keystream)",
+            size,
+            &bench.output.as_slice()[..size.min(16)],
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the output retention benchmark.
+pub const RETENTION_SIZES: [usize; 3] = [64, 1024, 4096];
+
+/// Compare AES-CTR encryptions whose output is discarded, left in the DMA
+/// output buffer for the next transfer to overwrite as keystream or DRBG
+/// output would be, with encryptions whose output is retained by copying it
+/// out of the DMA buffer into one the caller keeps, over [`AES_ITERATIONS`]
+/// encryptions of each of [`RETENTION_SIZES`].
+///
+/// The DMA writes the output either way, so the difference is the copy.
+pub fn benchmark_aes_output_retention(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let mut retained = [0_u8; RETENTION_SIZES[RETENTION_SIZES.len() - 1]];
+    let capacity = bench.capacity();
+    for size in RETENTION_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+        }
+        let discarded_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+            retained[..size].copy_from_slice(&bench.output.as_slice()[..size]);
+            core::hint::black_box(&mut retained);
+        }
+        let retained_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR output retention, DataSize: {size}, Discarded: {}, Retained: {}, Delta: {}, Throughput: {} / {}",
+            Elapsed(discarded_us),
+            Elapsed(retained_us),
+            Delta::between(Some(discarded_us), retained_us),
+            Throughput::of(size, discarded_us),
+            Throughput::of(size, retained_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the pre-filled DMA input benchmark.
+pub const PREFILLED_SIZES: [usize; 4] = [256, 1024, 4096, 16 * 1024];
+
+/// Compare AES-CTR on data that first has to be copied into the DMA input
+/// buffer, as from an ordinary slice, with data that is already there, as
+/// when another peripheral such as SPI received it by DMA into the buffer,
+/// over [`AES_ITERATIONS`] encryptions of each of [`PREFILLED_SIZES`].
+pub fn benchmark_aes_prefilled_input(mut bench: AesBench<'_>) -> AesResult<'_> {
+    const MAX_SIZE: usize = PREFILLED_SIZES[PREFILLED_SIZES.len() - 1];
+    let received: Vec<u8> = (0..MAX_SIZE).map(|i| i as u8).collect();
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let capacity = bench.capacity();
+    for size in PREFILLED_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench.input.as_mut_slice()[..size].copy_from_slice(&received[..size]);
+            bench = bench.encrypt(size, || {})?;
+        }
+        let copied_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+        }
+        let prefilled_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR pre-filled DMA input, DataSize: {size}, Pre-filled: {}, Copy then encrypt: {}, Savings: {} ({}), Throughput: {}",
+            Elapsed(prefilled_us),
+            Elapsed(copied_us),
+            Elapsed(copied_us - prefilled_us),
+            Delta::between(Some(copied_us), prefilled_us),
+            Throughput::of(size, prefilled_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the partial final block benchmark, none a multiple of the
+/// block size.
+pub const PARTIAL_BLOCK_SIZES: [usize; 3] = [100, 1000, 1500];
+
+/// Benchmark AES-CTR on lengths with a partial final block, as for
+/// variable-length payloads: [`AES_ITERATIONS`] encryptions of each of
+/// [`PARTIAL_BLOCK_SIZES`] through the hardware counter, which encrypts the
+/// last block in full and keeps only its first bytes. Logs the throughput
+/// and the cost per byte, and whether the ciphertext matches
+/// [`AesBench::encrypt_ctr_with_iv`] from the all-zero counter block, the
+/// path checked against NIST SP 800-38A.
+pub fn benchmark_aes_partial_block(mut bench: AesBench<'_>) -> AesResult<'_> {
+    const MAX_SIZE: usize = PARTIAL_BLOCK_SIZES[PARTIAL_BLOCK_SIZES.len() - 1];
+    let plaintext: Vec<u8> = (0..MAX_SIZE).map(|i| i as u8).collect();
+    let mut expected = [0_u8; MAX_SIZE];
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let capacity = bench.capacity();
+    for size in PARTIAL_BLOCK_SIZES
+        .into_iter()
+        .filter(|&size| size.next_multiple_of(16) <= capacity)
+    {
+        bench.input.as_mut_slice()[..size].copy_from_slice(&plaintext[..size]);
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+        }
+        let elapsed_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        let expected = &mut expected[..size];
+        expected.copy_from_slice(&plaintext[..size]);
+        bench = bench.encrypt_ctr_with_iv(AES_KEY, &[0; 16], expected)?;
+        bench.input.as_mut_slice()[..size].copy_from_slice(&plaintext[..size]);
+        bench = bench.encrypt(size, || {})?;
+        let matches = bench.output.as_slice()[..size] == *expected;
+        if !matches {
+            error!("AES-CTR partial final block, DataSize: {size}: ciphertext differs from the reference");
+        }
+
+        info!(
+            "AES-CTR partial final block, DataSize: {size} ({} blocks, last {} bytes), Time: {}, Throughput: {}, Per byte: {:.1} ns, Matches reference: {}",
+            size.div_ceil(16),
+            size % 16,
+            Elapsed(elapsed_us),
+            Throughput::of(size, elapsed_us),
+            elapsed_us * 1000.0 / size as f64,
+            if matches { "yes" } else { "no" }
+        );
+        log_result(&BenchmarkResult {
+            algorithm: "AES-CTR (partial block)",
+            data_size: size,
+            iterations: AES_ITERATIONS,
+            time_us: elapsed_us,
+        });
+    }
+
+    Ok(bench)
+}
+
+/// Benchmark the two cipher feedback modes of the engine, CFB8 (one byte of
+/// feedback per block operation) and CFB128 (a full block), at each of
+/// `data_sizes`.
+///
+/// CFB8 runs the block cipher once per byte instead of once per 16 bytes, so
+/// its throughput shows the cost of supporting legacy protocols built on it.
+pub fn benchmark_aes_cfb<'d>(mut bench: AesBench<'d>, data_sizes: &[usize]) -> AesResult<'d> {
+    // Pre-warm the AES DMA
+    bench = bench.transform(64, CipherMode::Cfb8, AES_KEY, || {})?;
+
+    for &size in data_sizes {
+        let cfb8;
+        let cfb128;
+        (bench, cfb8) = benchmark_aes_dma_mode(bench, CipherMode::Cfb8, size)?;
+        (bench, cfb128) = benchmark_aes_dma_mode(bench, CipherMode::Cfb128, size)?;
+        info!(
+            "AES-CFB, DataSize: {size}, CFB8: {}, CFB128: {}, CFB128 / CFB8: {:.1}x",
+            Throughput(cfb8),
+            Throughput(cfb128),
+            cfb128 / cfb8
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Benchmark the two block modes the engine offers besides CTR, AES-CBC and
+/// AES-ECB, at each of `data_sizes`.
+///
+/// The results are recorded under `AES-CBC` and `AES-ECB`, so the results
+/// pass can line them up with the `AES-CTR` sweep in one row per size.
+pub fn benchmark_aes_modes<'d>(mut bench: AesBench<'d>, data_sizes: &[usize]) -> AesResult<'d> {
+    // Pre-warm the AES DMA
+    bench = bench.transform(64, CipherMode::Cbc, AES_KEY, || {})?;
+
+    for &size in data_sizes {
+        let cbc;
+        let ecb;
+        (bench, cbc) = benchmark_aes_dma_mode(bench, CipherMode::Cbc, size)?;
+        (bench, ecb) = benchmark_aes_dma_mode(bench, CipherMode::Ecb, size)?;
+        info!(
+            "AES-CBC / AES-ECB, DataSize: {size}, CBC: {}, ECB: {}",
+            Throughput(cbc),
+            Throughput(ecb)
+        );
+        for (algorithm, throughput) in [("AES-CBC", cbc), ("AES-ECB", ecb)] {
+            log_result(&BenchmarkResult {
+                algorithm,
+                data_size: size,
+                iterations: AES_ITERATIONS,
+                time_us: size as f64 / throughput,
+            });
+        }
+    }
+
+    Ok(bench)
+}
+
+/// Benchmark AES-256-CMAC over each of `data_sizes`, the MAC to use when the
+/// AES engine is already in use and another hash path is not wanted.
+///
+/// The time includes copying the message into the DMA buffer and deriving
+/// the subkeys, which costs one extra block operation per MAC.
+pub fn benchmark_aes_cmac<'d>(mut bench: AesBench<'d>, data_sizes: &[usize]) -> AesResult<'d> {
+    let message = [0xAB_u8; MAX_DMA_BUFFER_SIZE];
+    let mut tag = [0_u8; 16];
+
+    // Pre-warm the AES DMA
+    bench = bench.cmac(AES_KEY, &message[..64], &mut tag)?;
+
+    for &size in data_sizes {
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            bench = bench.cmac(AES_KEY, &message[..size], &mut tag)?;
+        }
+        let elapsed_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+        info!(
+            "AES-CMAC, DataSize: {size}, Time: {}, Throughput: {}",
+            Elapsed(elapsed_us),
+            Throughput::of(size, elapsed_us)
+        );
+        #[cfg(feature = "hex-dump")]
+        log_hex("AES-CMAC", size, &tag);
+    }
+
+    Ok(bench)
+}
+
+/// Encrypt `size` bytes [`AES_ITERATIONS`] times in `cipher_mode` and return
+/// the throughput in MB/s.
+fn benchmark_aes_dma_mode(
+    mut bench: AesBench<'_>,
+    cipher_mode: CipherMode,
+    size: usize,
+) -> AesResult<'_, (AesBench<'_>, f64)> {
+    let start_time = Instant::now();
+    for _ in 0..AES_ITERATIONS {
+        bench = bench.transform(size, cipher_mode, AES_KEY, || {})?;
+    }
+    let elapsed = start_time.elapsed();
+    Ok((
+        bench,
+        (AES_ITERATIONS * size) as f64 / elapsed.as_micros() as f64,
+    ))
+}
+
+/// Benchmark AES-CTR on both sides of every DMA descriptor boundary of the
+/// allocated chain.
+///
+/// One descriptor covers at most [`CHUNK_SIZE`] bytes, so the largest AES
+/// transfer that fits a single descriptor is the largest multiple of the
+/// block size below it, and each further [`CHUNK_SIZE`] bytes chain another
+/// descriptor. Each boundary is measured at the largest size that still fits
+/// and at one block more, which shows the cost of chaining.
+pub fn benchmark_dma_descriptor_chain(bench: AesBench<'_>) -> AesResult<'_> {
+    let capacity = bench.capacity();
+    let chain_length = capacity.div_ceil(CHUNK_SIZE);
+    info!(
+        "DMA descriptor capacity: {CHUNK_SIZE} bytes, largest single-descriptor AES transfer: {} bytes, chain: {chain_length} descriptors ({capacity} bytes)",
+        CHUNK_SIZE / 16 * 16
+    );
+
+    // Pre-warm the AES DMA
+    let (mut bench, _, _, _) = benchmark_single_aes_dma(bench, 64)?;
+
+    for descriptors in 1..=chain_length {
+        let fits = (descriptors * CHUNK_SIZE).min(capacity) / 16 * 16;
+        for size in [fits, fits + 16] {
+            if size > capacity {
+                continue;
+            }
+            let throughput;
+            (bench, throughput, _, _) = benchmark_single_aes_dma(bench, size)?;
+            info!(
+                "AES-CTR descriptor chain, DataSize: {size}, Descriptors: {}, Time: {}, Throughput: {}",
+                size.div_ceil(CHUNK_SIZE),
+                Elapsed(size as f64 / throughput * 1_000_000.0),
+                Throughput(throughput / 1_000_000.0)
+            );
+        }
+    }
+
+    Ok(bench)
+}
+
+/// Packet size distribution of the mixed workload benchmark as (size in
+/// bytes, weight) pairs: per round, each size is encrypted `weight` times.
+/// Edit the table to match the traffic of an application; sizes larger than
+/// the DMA buffers are skipped.
+pub const MIXED_WORKLOAD: [(usize, usize); 7] = [
+    (64, 50),
+    (128, 20),
+    (256, 10),
+    (512, 8),
+    (1024, 6),
+    (4096, 4),
+    (16 * 1024, 2),
+];
+
+/// Number of passes over [`MIXED_WORKLOAD`].
+pub const MIXED_WORKLOAD_ROUNDS: usize = 10;
+
+/// Encrypt a weighted mix of packet sizes with AES-CTR and report the
+/// aggregate throughput, which no single-size figure of the sweep gives for
+/// traffic dominated by small packets.
+///
+/// Each round walks [`MIXED_WORKLOAD`] and encrypts every size as often as
+/// its weight.
+pub fn benchmark_aes_mixed_workload(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let capacity = bench.capacity();
+    let mut packets = 0;
+    let mut bytes = 0;
+    let start_time = Instant::now();
+    for _ in 0..MIXED_WORKLOAD_ROUNDS {
+        for (size, weight) in MIXED_WORKLOAD {
+            if size > capacity {
+                continue;
+            }
+            for _ in 0..weight {
+                bench = bench.encrypt(size, || {})?;
+            }
+            packets += weight;
+            bytes += weight * size;
+        }
+    }
+    let elapsed_us = start_time.elapsed().as_micros() as f64;
+
+    info!(
+        "AES-CTR mixed workload, Packets: {packets}, Mean size: {:.0} bytes, Time: {}, Throughput: {}, Packets/s: {:.0}",
+        bytes as f64 / packets.max(1) as f64,
+        Elapsed(elapsed_us),
+        Throughput::of(bytes, elapsed_us),
+        packets as f64 / elapsed_us * 1_000_000.0
+    );
+
+    Ok(bench)
+}
+
+/// Data sizes of the per-iteration rekeying benchmark.
+pub const REKEY_SIZES: [usize; 3] = [16, 64, 1024];
+
+/// Encryptions per data size and key schedule in the rekeying benchmark.
+pub const REKEY_ITERATIONS: usize = 1000;
+
+/// AES-256 keys the rekeying benchmark cycles through, one per encryption.
+pub const REKEY_KEYS: [[u8; 32]; 4] = [[0x11; 32], [0x22; 32], [0x33; 32], [0x44; 32]];
+
+/// Compare AES-CTR with [`AES_KEY`] for every encryption against a fresh
+/// key from [`REKEY_KEYS`] for every encryption, as in per-packet keying.
+///
+/// The HAL writes the key registers on every `process()` call either way,
+/// so a difference would come from the engine itself, e.g. a key expansion
+/// it skips while the key is unchanged.
+pub fn benchmark_aes_rekey(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    for size in REKEY_SIZES {
+        let start_time = Instant::now();
+        for _ in 0..REKEY_ITERATIONS {
+            bench = bench.transform(size, CipherMode::Ctr, AES_KEY, || {})?;
+        }
+        let fixed_us = start_time.elapsed().as_micros() as f64 / REKEY_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for key in REKEY_KEYS.iter().cycle().take(REKEY_ITERATIONS) {
+            bench = bench.transform(size, CipherMode::Ctr, *key, || {})?;
+        }
+        let rekeyed_us = start_time.elapsed().as_micros() as f64 / REKEY_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR (new key per call), DataSize: {size}, Time: {}, Fixed key: {}, Delta: {}, Throughput: {}",
+            Elapsed(rekeyed_us),
+            Elapsed(fixed_us),
+            Delta::between(Some(fixed_us), rekeyed_us),
+            Throughput::of(size, rekeyed_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Key transitions timed by the rekey latency benchmark.
+pub const REKEY_TRANSITIONS: usize = 200;
+
+/// Encryptions with the current key before each timed transition, so the
+/// engine is in the steady state of a session.
+pub const REKEY_SETTLE_ITERATIONS: usize = 8;
+
+/// Measure the latency of a single rekey in the middle of a session: after
+/// [`REKEY_SETTLE_ITERATIONS`] single-block encryptions with one key of
+/// [`REKEY_KEYS`], one more encryption with the same key is timed, then the
+/// first encryption with the next key. The cost of the transition is the
+/// difference of the two means over [`REKEY_TRANSITIONS`] transitions.
+///
+/// Unlike [`benchmark_aes_rekey`], which changes the key on every call, this
+/// times the one operation right after a key change, as for a protocol
+/// rekeying within a session.
+pub fn benchmark_aes_rekey_latency(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let mut same_key = Samples::<REKEY_TRANSITIONS>::new();
+    let mut new_key = Samples::<REKEY_TRANSITIONS>::new();
+    let mut keys = REKEY_KEYS.iter().cycle();
+    let mut key = *keys.next().unwrap();
+
+    for _ in 0..REKEY_TRANSITIONS {
+        for _ in 0..REKEY_SETTLE_ITERATIONS {
+            bench = bench.transform(16, CipherMode::Ctr, key, || {})?;
+        }
+        let (result, elapsed) = timed(|| bench.transform(16, CipherMode::Ctr, key, || {}));
+        bench = result?;
+        same_key.push(elapsed.as_micros());
+
+        key = *keys.next().unwrap();
+        let (result, elapsed) = timed(|| bench.transform(16, CipherMode::Ctr, key, || {}));
+        bench = result?;
+        new_key.push(elapsed.as_micros());
+    }
+
+    let same = Summary::of(same_key.as_slice().iter().map(|&us| us as f64));
+    let new = Summary::of(new_key.as_slice().iter().map(|&us| us as f64));
+    info!(
+        "AES-CTR rekey latency, DataSize: 16, Same key: {}, New key: {}, Rekey cost: {:.2} us",
+        Elapsed(same.mean),
+        Elapsed(new.mean),
+        new.mean - same.mean
+    );
+
+    Ok(bench)
+}
+
+/// Byte offsets of the key within a 16-byte aligned buffer in the key
+/// alignment benchmark: 16-byte and word aligned, then misaligned.
+pub const KEY_ALIGNMENT_OFFSETS: [usize; 6] = [0, 4, 8, 1, 2, 3];
+
+/// Compare single-block AES-CTR encryptions ([`REKEY_ITERATIONS`] each) with
+/// the key read from each of [`KEY_ALIGNMENT_OFFSETS`] into a larger buffer,
+/// as for a key embedded at an arbitrary position in a struct, against the
+/// 16-byte aligned key.
+///
+/// The HAL copies the key into its `Key` enum and writes the key registers
+/// one word at a time, assembling each word from four bytes, so it needs no
+/// particular alignment; a delta would be the cost of copying a misaligned
+/// key on the CPU.
+pub fn benchmark_aes_key_alignment(mut bench: AesBench<'_>) -> AesResult<'_> {
+    use core::hint::black_box;
+
+    #[repr(C, align(16))]
+    struct Aligned([u8; 48]);
+
+    // Pre-warm the AES DMA
+    bench = bench.transform(16, CipherMode::Ctr, AES_KEY, || {})?;
+
+    let mut buffer = Aligned([0; 48]);
+    let mut aligned_us = None;
+    for offset in KEY_ALIGNMENT_OFFSETS {
+        buffer.0.fill(0);
+        buffer.0[offset..offset + 32].copy_from_slice(&AES_KEY);
+        let key_bytes = &buffer.0[offset..offset + 32];
+
+        let start_time = Instant::now();
+        for _ in 0..REKEY_ITERATIONS {
+            let key: [u8; 32] = black_box(key_bytes).try_into().unwrap();
+            bench = bench.transform(16, CipherMode::Ctr, key, || {})?;
+        }
+        let time_us = start_time.elapsed().as_micros() as f64 / REKEY_ITERATIONS as f64;
+
+        let alignment = match (key_bytes.as_ptr() as usize) % 16 {
+            0 => "16-byte aligned",
+            4 | 8 | 12 => "word aligned",
+            _ => "misaligned",
+        };
+        info!(
+            "AES-CTR key alignment, Offset: {offset} ({alignment}), DataSize: 16, Time: {}, Delta: {}",
+            Elapsed(time_us),
+            Delta::between(Some(*aligned_us.get_or_insert(time_us)), time_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the zeroization benchmark.
+pub const ZEROIZE_SIZES: [usize; 3] = [64, 1024, 16384];
+
+/// Overwrite `buffer` with zeros in a way the compiler cannot drop as a dead
+/// store, like the `zeroize` crate: one volatile write per byte and a fence
+/// so later code cannot be reordered before the wipe.
+fn zeroize(buffer: &mut [u8]) {
+    for byte in buffer.iter_mut() {
+        // SAFETY: `byte` is a valid, aligned and exclusive reference.
+        unsafe { core::ptr::write_volatile(byte, 0) };
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Measure the cost of secure cleanup after each AES-CTR encryption: wiping
+/// a copy of the key and the used part of the DMA input and output buffers
+/// with `zeroize`, [`AES_ITERATIONS`] times per size of
+/// [`ZEROIZE_SIZES`], against the same encryptions without the wipe.
+///
+/// The wipe does not reach the copy of the key in the engine's key
+/// registers, which the HAL leaves in place until the next `process()`.
+pub fn benchmark_aes_zeroize(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    for size in ZEROIZE_SIZES {
+        if size > bench.capacity() {
+            break;
+        }
+
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            let key = AES_KEY;
+            bench = bench.transform(size, CipherMode::Ctr, key, || {})?;
+        }
+        let plain_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            let mut key = AES_KEY;
+            bench = bench.transform(size, CipherMode::Ctr, key, || {})?;
+            zeroize(&mut key);
+            zeroize(&mut bench.input.as_mut_slice()[..size]);
+            zeroize(&mut bench.output.as_mut_slice()[..size]);
+        }
+        let wiped_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR zeroize (key, input and output), DataSize: {size}, Time: {}, Without: {}, Wipe: {}, Overhead: {}",
+            Elapsed(wiped_us),
+            Elapsed(plain_us),
+            Elapsed(wiped_us - plain_us),
+            Delta::between(Some(plain_us), wiped_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Total data size of the scatter-gather benchmark.
+pub const SCATTER_GATHER_SIZE: usize = 4096;
+
+/// Numbers of equal segments the scatter-gather benchmark splits
+/// [`SCATTER_GATHER_SIZE`] into. Every segment has to fit one descriptor, so
+/// at least two are needed; the largest count gives 64-byte segments.
+pub const SCATTER_GATHER_SEGMENTS: [usize; 6] = [2, 4, 8, 16, 32, 64];
+
+/// A DMA transmit buffer gathering its data from separately allocated heap
+/// segments, with one descriptor per segment, as for a message that is
+/// fragmented in memory.
+struct ScatterTxBuf {
+    segments: Vec<Box<[u32]>>,
+    descriptors: Box<[DmaDescriptor]>,
+}
+
+impl ScatterTxBuf {
+    /// Copy `data` into `count` equal segments. A segment has to be a
+    /// multiple of 4 bytes and at most [`CHUNK_SIZE`].
+    fn new(data: &[u8], count: usize) -> Self {
+        let segments = data
+            .chunks(data.len() / count)
+            .map(|segment| {
+                let words = segment.as_chunks::<4>().0;
+                words.iter().map(|word| u32::from_ne_bytes(*word)).collect()
+            })
+            .collect();
+        let descriptors = core::iter::repeat_n(DmaDescriptor::EMPTY, count).collect();
+        Self {
+            segments,
+            descriptors,
+        }
+    }
+}
+
+// SAFETY: the descriptors and segments are heap allocations owned by the
+// buffer, which the transfer holds as its view until it completes, so they
+// stay valid and in place while the DMA reads them.
+unsafe impl DmaTxBuffer for ScatterTxBuf {
+    type View = Self;
+
+    fn prepare(&mut self) -> Preparation {
+        let head = self.descriptors.as_mut_ptr();
+        let count = self.segments.len();
+        for (i, (descriptor, segment)) in self
+            .descriptors
+            .iter_mut()
+            .zip(&mut self.segments)
+            .enumerate()
+        {
+            let len = segment.len() * 4;
+            descriptor.buffer = segment.as_mut_ptr().cast();
+            descriptor.set_size(len);
+            descriptor.set_length(len);
+            descriptor.reset_for_tx(i + 1 == count);
+            descriptor.next = if i + 1 < count {
+                head.wrapping_add(i + 1)
+            } else {
+                core::ptr::null_mut()
+            };
+        }
+
+        Preparation {
+            start: head,
+            direction: TransferDirection::Out,
+            burst_transfer: BurstConfig::default(),
+            check_owner: None,
+            auto_write_back: false,
+        }
+    }
+
+    fn into_view(self) -> Self {
+        self
+    }
+
+    fn from_view(view: Self) -> Self {
+        view
+    }
+}
+
+/// Encrypt [`SCATTER_GATHER_SIZE`] bytes with AES-CTR from the contiguous
+/// input buffer and then gathered from [`SCATTER_GATHER_SEGMENTS`] separate
+/// segments in one transfer, and log the overhead of each segmentation
+/// against the contiguous buffer.
+///
+/// Both runs encrypt the same data, so the output of each scattered
+/// transfer is checked against the contiguous one. A scattered transfer
+/// that is not done within [`ENGINE_TIMEOUT`](crate::error::ENGINE_TIMEOUT)
+/// fails with [`BenchError::TransferWait`], like the contiguous ones.
+pub fn benchmark_aes_scatter_gather(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let capacity = bench.capacity();
+    if capacity < SCATTER_GATHER_SIZE {
+        warn!("Skipping the scatter-gather benchmark, it needs {SCATTER_GATHER_SIZE} byte DMA buffers");
+        return Ok(bench);
+    }
+
+    let data: Vec<u8> = (0..SCATTER_GATHER_SIZE).map(|i| i as u8).collect();
+    bench.input.as_mut_slice()[..SCATTER_GATHER_SIZE].copy_from_slice(&data);
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let start_time = Instant::now();
+    for _ in 0..AES_ITERATIONS {
+        bench = bench.encrypt(SCATTER_GATHER_SIZE, || {})?;
+    }
+    let contiguous_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+    let expected = bench.output.as_slice()[..SCATTER_GATHER_SIZE].to_vec();
+    info!(
+        "AES-CTR contiguous, DataSize: {SCATTER_GATHER_SIZE}, Descriptors: {}, Time: {}, Throughput: {}",
+        SCATTER_GATHER_SIZE.div_ceil(CHUNK_SIZE),
+        Elapsed(contiguous_us),
+        Throughput::of(SCATTER_GATHER_SIZE, contiguous_us)
+    );
+
+    for count in SCATTER_GATHER_SEGMENTS {
+        let mut segments = ScatterTxBuf::new(&data, count);
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            let AesBench { aes, output, input } = bench;
+            let transfer = match aes.process(
+                SCATTER_GATHER_SIZE / 16,
+                output,
+                segments,
+                Mode::Encryption256,
+                CipherMode::Ctr,
+                AES_KEY,
+            ) {
+                Ok(transfer) => transfer,
+                Err((error, aes, output, _)) => {
+                    return Err((
+                        BenchError::Process(error),
+                        Some(AesBench { aes, output, input }),
+                    ));
+                }
+            };
+            let mut busy = BusyWait::default();
+            while !transfer.is_done() {
+                if busy.expired() {
+                    // Dropping the transfer stops the channel before it frees
+                    // the segments; the output buffer stays leaked, as the
+                    // channel may still have been writing to it.
+                    drop(transfer);
+                    free_input_buffer(input);
+                    return Err(AesBench::hung(capacity));
+                }
+            }
+            let (aes, output, returned) = transfer.wait();
+            bench = AesBench { aes, output, input };
+            segments = returned;
+        }
+        let scattered_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR scatter-gather, DataSize: {SCATTER_GATHER_SIZE}, Segments: {count}, Time: {}, Contiguous: {}, Overhead: {}, Throughput: {}, Match: {}",
+            Elapsed(scattered_us),
+            Elapsed(contiguous_us),
+            Delta::between(Some(contiguous_us), scattered_us),
+            Throughput::of(SCATTER_GATHER_SIZE, scattered_us),
+            bench.output.as_slice()[..SCATTER_GATHER_SIZE] == expected[..]
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the DMA re-attachment benchmark.
+pub const REATTACH_SIZES: [usize; 3] = [16, 1024, 4096];
+
+/// Encryptions per data size and attachment strategy in the DMA
+/// re-attachment benchmark.
+pub const REATTACH_ITERATIONS: usize = 1000;
+
+/// Create a new AES DMA driver on the AES peripheral and
+/// [`AES_DMA_CHANNEL`], as `main` does at start-up.
+///
+/// # Safety
+///
+/// No other driver may own the AES peripheral or the channel.
+unsafe fn attach_aes_dma<'d>() -> AesDma<'d> {
+    // SAFETY: the caller guarantees exclusive use of both peripherals.
+    unsafe {
+        #[cfg(not(any(feature = "aes-dma-ch1", feature = "aes-dma-ch2")))]
+        let channel = peripherals::DMA_CH0::steal();
+        #[cfg(feature = "aes-dma-ch1")]
+        let channel = peripherals::DMA_CH1::steal();
+        #[cfg(feature = "aes-dma-ch2")]
+        let channel = peripherals::DMA_CH2::steal();
+        Aes::new(peripherals::AES::steal()).with_dma(channel)
+    }
+}
+
+/// Compare AES-CTR on the driver attached once at start-up against creating
+/// it with `Aes::new(...).with_dma(...)` before every encryption and
+/// dropping it afterwards, as an application sharing the DMA channel with
+/// other peripherals would.
+///
+/// Dropping the last driver also gates the AES peripheral clock, so the
+/// re-attached figure includes enabling and resetting the peripheral again.
+pub fn benchmark_aes_reattach(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let capacity = bench.capacity();
+    for size in REATTACH_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let start_time = Instant::now();
+        for _ in 0..REATTACH_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+        }
+        let attached_us = start_time.elapsed().as_micros() as f64 / REATTACH_ITERATIONS as f64;
+
+        let AesBench {
+            aes,
+            mut output,
+            mut input,
+        } = bench;
+        drop(aes);
+        let start_time = Instant::now();
+        for _ in 0..REATTACH_ITERATIONS {
+            // SAFETY: the previous driver has been dropped.
+            let aes = unsafe { attach_aes_dma() };
+            let detached = AesBench { aes, output, input }.encrypt(size, || {})?;
+            (output, input) = (detached.output, detached.input);
+        }
+        let reattached_us = start_time.elapsed().as_micros() as f64 / REATTACH_ITERATIONS as f64;
+        // SAFETY: the last driver of the loop has been dropped.
+        bench = AesBench {
+            aes: unsafe { attach_aes_dma() },
+            output,
+            input,
+        };
+
+        info!(
+            "AES-CTR (attach DMA per call), DataSize: {size}, Time: {}, Attached once: {}, Attach cost: {:+}, Delta: {}",
+            Elapsed(reattached_us),
+            Elapsed(attached_us),
+            Elapsed(reattached_us - attached_us),
+            Delta::between(Some(attached_us), reattached_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the DMA burst benchmark.
+pub const BURST_SIZES: [usize; 3] = [64, 1024, 16 * 1024];
+
+/// The burst settings of the DMA burst benchmark, with their TX (input) and
+/// RX (output) configuration. The first one is the HAL default.
+pub const BURST_SETTINGS: [(&str, BurstConfig, BurstConfig); 4] = [
+    ("off", BurstConfig::Disabled, BurstConfig::Disabled),
+    ("TX", BurstConfig::Enabled, BurstConfig::Disabled),
+    ("RX", BurstConfig::Disabled, BurstConfig::Enabled),
+    ("TX+RX", BurstConfig::Enabled, BurstConfig::Enabled),
+];
+
+/// Compare AES-CTR throughput with each [`BURST_SETTINGS`] entry applied to
+/// the input and output DMA buffers, and log the fastest setting per size.
+///
+/// On the ESP32-C6 the only knob is whether the channel reads or writes
+/// internal memory in bursts; the burst length is fixed, and the selectable
+/// lengths only exist for PSRAM on other chips. With bursts enabled the
+/// output buffer has to be word-aligned, which the heap buffers are. The
+/// buffers are set back to the default afterwards.
+pub fn benchmark_aes_dma_burst(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let capacity = bench.capacity();
+    for size in BURST_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let mut default_us = None;
+        let mut expected = Vec::new();
+        let mut best: Option<(&str, f64)> = None;
+        for (label, tx, rx) in BURST_SETTINGS {
+            if let Err(error) = bench
+                .input
+                .set_burst_config(tx)
+                .and_then(|()| bench.output.set_burst_config(rx))
+            {
+                warn!("AES-CTR DMA burst {label}: not supported ({error:?})");
+                continue;
+            }
+
+            let start_time = Instant::now();
+            for _ in 0..AES_ITERATIONS {
+                bench = bench.encrypt(size, || {})?;
+            }
+            let time_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+
+            let output = &bench.output.as_slice()[..size];
+            if expected.is_empty() {
+                expected = output.to_vec();
+            }
+            info!(
+                "AES-CTR DMA burst, DataSize: {size}, Burst: {label}, Time: {}, Throughput: {}, Delta: {}, Match: {}",
+                Elapsed(time_us),
+                Throughput::of(size, time_us),
+                Delta::between(Some(*default_us.get_or_insert(time_us)), time_us),
+                output == expected
+            );
+            if best.is_none_or(|(_, best_us)| time_us < best_us) {
+                best = Some((label, time_us));
+            }
+        }
+        if let Some((label, time_us)) = best {
+            info!(
+                "AES-CTR DMA burst, DataSize: {size}, Fastest: {label} ({})",
+                Throughput::of(size, time_us)
+            );
+        }
+    }
+
+    if let Err(error) = bench
+        .input
+        .set_burst_config(BurstConfig::default())
+        .and_then(|()| bench.output.set_burst_config(BurstConfig::default()))
+    {
+        warn!("AES DMA buffers not reset to the default burst setting ({error:?})");
+    }
+    Ok(bench)
+}
+
+/// Data encrypted at each step of the blocks-per-call sweep, limited to the
+/// DMA buffer size.
+pub const BLOCKS_PER_CALL_TOTAL: usize = 32 * 1024;
+
+/// Encrypt [`BLOCKS_PER_CALL_TOTAL`] bytes with AES-CTR in calls of 1, 2, 4,
+/// ... blocks up to the whole buffer at once, then log the call size with
+/// the best throughput and the smallest one within
+/// [`SATURATION_THRESHOLD_PERCENT`](super::SATURATION_THRESHOLD_PERCENT) steps of it.
+///
+/// The total stays the same, so the curve shows where the fixed cost of each
+/// `process()` call stops mattering and larger chunks no longer pay off,
+/// e.g. whether one 32 KB call beats eight 4 KB calls.
+pub fn benchmark_aes_blocks_per_call(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let total = BLOCKS_PER_CALL_TOTAL.min(bench.capacity()) / 16 * 16;
+    let mut call_sizes = Vec::new();
+    let mut throughputs = Vec::new();
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let mut blocks = 1;
+    while blocks * 16 <= total {
+        let calls = total / (blocks * 16);
+        let start_time = Instant::now();
+        for _ in 0..calls {
+            bench = bench.encrypt(blocks * 16, || {})?;
+        }
+        let elapsed_us = start_time.elapsed().as_micros() as f64;
+        let throughput = (calls * blocks * 16) as f64 / elapsed_us;
+        info!(
+            "AES-CTR blocks per call, Blocks: {blocks}, Calls: {calls}, Per call: {}, Throughput: {}",
+            Elapsed(elapsed_us / calls as f64),
+            Throughput(throughput)
+        );
+        call_sizes.push(blocks * 16);
+        throughputs.push(throughput);
+        blocks *= 2;
+    }
+
+    if let Some((size, best)) = call_sizes
+        .iter()
+        .zip(&throughputs)
+        .max_by(|a, b| a.1.total_cmp(b.1))
+    {
+        info!(
+            "AES-CTR optimal call size for {total} bytes: {size} bytes ({} calls, {})",
+            total / size,
+            Throughput(*best)
+        );
+    }
+    log_saturation("AES-CTR call size", &call_sizes, &throughputs);
+
+    Ok(bench)
+}
+
+/// Single-block encryptions timed by the single block latency benchmark.
+pub const SINGLE_BLOCK_ITERATIONS: usize = 10_000;
+
+/// Time one 16-byte AES-CTR `process()` call, the smallest DMA operation,
+/// averaged over [`SINGLE_BLOCK_ITERATIONS`] calls.
+///
+/// This is the floor of the size sweep: the fixed cost every message pays,
+/// however short.
+pub fn benchmark_aes_single_block(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(16, || {})?;
+
+    let start_time = Instant::now();
+    for _ in 0..SINGLE_BLOCK_ITERATIONS {
+        bench = bench.encrypt(16, || {})?;
+    }
+    let time_us = start_time.elapsed().as_micros() as f64 / SINGLE_BLOCK_ITERATIONS as f64;
+
+    info!(
+        "AES-CTR single block, DataSize: 16, Iterations: {SINGLE_BLOCK_ITERATIONS}, Latency: {:.0} ns, Throughput: {}",
+        time_us * 1000.0,
+        Throughput::of(16, time_us)
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "AES-CTR (single block)",
+        data_size: 16,
+        iterations: SINGLE_BLOCK_ITERATIONS,
+        time_us,
+    });
+
+    Ok(bench)
+}
+
+/// Benchmark AES-CTR with DMA while the CPU runs a memory-bound workload.
+///
+/// Each data size is measured twice: once with the CPU idle-polling for
+/// completion (the regular benchmark) and once with the CPU hammering SRAM
+/// while the transfer is in flight. The difference shows how much the DMA
+/// throughput suffers from CPU/bus contention.
+#[cfg(feature = "aes-cpu-load")]
+pub fn benchmark_aes_dma_under_load<'d>(
+    bench: AesBench<'d>,
+    data_sizes: &[usize],
+) -> AesResult<'d> {
+    let mut scratch = [0_u32; 256];
+
+    // Pre-warm the AES DMA
+    let (mut bench, _, _, _) = benchmark_single_aes_dma(bench, 64)?;
+
+    for &size in data_sizes {
+        let idle;
+        let loaded;
+        (bench, idle, _, _) = benchmark_single_aes_dma(bench, size)?;
+        (bench, loaded, _, _) =
+            benchmark_single_aes_dma_with(bench, size, || cpu_load_step(&mut scratch))?;
+        info!(
+            "AES-CTR (CPU loaded), DataSize: {size}, Throughput: {}, Idle: {}, Change: {:+.1}%",
+            Throughput(loaded / 1_000_000.0),
+            Throughput(idle / 1_000_000.0),
+            (loaded - idle) / idle * 100.0
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the DMA completion polling benchmark.
+pub const POLLING_SIZES: [usize; 3] = [1024, 4096, 16 * 1024];
+
+/// Measure how much of an AES-CTR DMA transfer the CPU spends spinning on
+/// `is_done()`: from the first poll after `process()` returns to completion,
+/// with the number of polls, over [`AES_ITERATIONS`] transfers of each of
+/// [`POLLING_SIZES`].
+///
+/// The polling CPU cycles are the polling time at the configured CPU clock,
+/// the time an interrupt or async completion would hand back to the
+/// application.
+pub fn benchmark_aes_dma_polling(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(64, || {})?;
+
+    let cpu_mhz = esp_hal::clock::Clocks::get().cpu_clock.as_mhz() as f64;
+    let capacity = bench.capacity();
+    for size in POLLING_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let mut polls = 0_u64;
+        let mut polling = Duration::ZERO;
+        let start_time = Instant::now();
+        for _ in 0..AES_ITERATIONS {
+            let mut first_poll = None;
+            bench = bench.encrypt(size, || {
+                polls += 1;
+                first_poll.get_or_insert_with(Instant::now);
+            })?;
+            if let Some(first_poll) = first_poll {
+                polling += first_poll.elapsed();
+            }
+        }
+        let transfer_us = start_time.elapsed().as_micros() as f64 / AES_ITERATIONS as f64;
+        let polling_us = polling.as_micros() as f64 / AES_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR DMA polling, DataSize: {size}, Transfer: {}, Polling: {} ({:.1}%), Polls: {:.0}, Polling cycles: {:.0} per transfer",
+            Elapsed(transfer_us),
+            Elapsed(polling_us),
+            polling_us / transfer_us * 100.0,
+            polls as f64 / AES_ITERATIONS as f64,
+            polling_us * cpu_mhz
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Benchmark AES-CTR with DMA with interrupts enabled and again with them
+/// masked inside a critical section, reporting the difference caused by
+/// interrupt handling.
+#[cfg(feature = "interrupt-compare")]
+pub fn benchmark_aes_dma_interrupts_masked<'d>(
+    bench: AesBench<'d>,
+    data_sizes: &[usize],
+) -> AesResult<'d> {
+    // Pre-warm the AES DMA
+    let (mut bench, _, _, _) = benchmark_single_aes_dma(bench, 64)?;
+
+    for &size in data_sizes {
+        let enabled;
+        let masked;
+        (bench, enabled, _, _) = benchmark_single_aes_dma(bench, size)?;
+        (bench, masked, _, _) = critical_section::with(|_| benchmark_single_aes_dma(bench, size))?;
+        info!(
+            "AES-CTR (interrupts masked), DataSize: {size}, Throughput: {}, Enabled: {}, Change: {:+.1}%",
+            Throughput(masked / 1_000_000.0),
+            Throughput(enabled / 1_000_000.0),
+            (masked - enabled) / enabled * 100.0
+        );
+    }
+
+    Ok(bench)
+}
+
+/// One step of the competing CPU workload: a read-modify-write sweep over an
+/// SRAM scratch buffer, so the core contends with the DMA for the bus.
+#[cfg(feature = "aes-cpu-load")]
+fn cpu_load_step(scratch: &mut [u32]) {
+    for (i, word) in scratch.iter_mut().enumerate() {
+        *word = word.wrapping_mul(0x9E37_79B9).rotate_left(5) ^ i as u32;
+    }
+    core::hint::black_box(scratch);
+}
+
+/// Benchmark AES-CTR with DMA using a fixed buffer size.
+/// This function processes the data repeatedly and measures throughput.
+/// # Arguments
+/// * `bench` - The AES DMA instance and buffers to use for processing.
+/// * `buffer_size` - The size of the buffer to use for each AES operation,
+///   limited to the capacity of the DMA buffers.
+/// # Returns
+/// A tuple containing the AES DMA instance, the throughput in bytes per second
+/// over all iterations, a summary of the per-iteration throughput in MB/s,
+/// and the distribution of the per-iteration times less the
+/// [`timestamp_overhead`].
+///
+fn benchmark_single_aes_dma(
+    bench: AesBench<'_>,
+    buffer_size: usize,
+) -> AesResult<'_, (AesBench<'_>, f64, Summary, Option<BenchStats>)> {
+    benchmark_single_aes_dma_with(bench, buffer_size, || {})
+}
+
+/// Half-width of the 95% confidence interval, as a percentage of the mean,
+/// that the AES-CTR sweep iterates towards with `target-ci`.
+#[cfg(feature = "target-ci")]
+pub const TARGET_CI_PERCENT: f64 = 2.0;
+
+/// Fewest iterations per data size with `target-ci`, so that a few lucky
+/// samples cannot end the measurement.
+#[cfg(feature = "target-ci")]
+pub const MIN_ITERATIONS: usize = 10;
+
+/// Most iterations per data size with `target-ci`.
+#[cfg(feature = "target-ci")]
+pub const MAX_ITERATIONS: usize = 10_000;
+
+/// Longest time spent on one data size with `target-ci`, in milliseconds.
+#[cfg(feature = "target-ci")]
+pub const MAX_TIME_MS: u64 = 2_000;
+
+/// Same as [`benchmark_single_aes_dma`], but keeps iterating until the 95%
+/// confidence interval of the per-iteration throughput is within
+/// [`TARGET_CI_PERCENT`] of the mean, after at least [`MIN_ITERATIONS`] and
+/// at most [`MAX_ITERATIONS`] iterations or [`MAX_TIME_MS`].
+///
+/// Noisy sizes get more iterations and stable ones fewer; the summary's
+/// `count` is the number actually run. The samples are not kept, so there
+/// is no per-iteration time distribution.
+#[cfg(feature = "target-ci")]
+fn benchmark_single_aes_dma_to_target(
+    mut bench: AesBench<'_>,
+    buffer_size: usize,
+) -> AesResult<'_, (AesBench<'_>, f64, Summary, Option<BenchStats>)> {
+    let actual_buffer_size = buffer_size.min(bench.capacity());
+    let max_time = Duration::from_millis(MAX_TIME_MS);
+
+    let mut running = Running::new();
+    let start_time = Instant::now();
+    let summary = loop {
+        let iteration_start = Instant::now();
+        bench = bench.encrypt(actual_buffer_size, || {})?;
+        let micros = iteration_start.elapsed().as_micros();
+        // Bytes per microsecond is MB/s.
+        running.push(actual_buffer_size as f64 / micros.max(1) as f64);
+
+        let summary = running.summary();
+        let converged = summary.relative_margin_95() * 100.0 <= TARGET_CI_PERCENT;
+        if summary.count >= MIN_ITERATIONS
+            && (converged || summary.count >= MAX_ITERATIONS || start_time.elapsed() >= max_time)
+        {
+            break summary;
+        }
+    };
+    let elapsed = start_time.elapsed();
+
+    let throughput =
+        (summary.count * actual_buffer_size) as f64 / elapsed.as_micros() as f64 * 1_000_000.0;
+    Ok((bench, throughput, summary, None))
+}
+
+/// Log the iterations a `target-ci` measurement took and the confidence
+/// interval it reached, with a warning if a cap stopped it short of
+/// [`TARGET_CI_PERCENT`].
+#[cfg(feature = "target-ci")]
+fn log_target_ci(label: &str, size: usize, summary: &Summary) {
+    let percent = summary.relative_margin_95() * 100.0;
+    if percent <= TARGET_CI_PERCENT {
+        info!(
+            "{label}, DataSize: {size}, Iterations: {}, CI: ±{percent:.2}% (target ±{TARGET_CI_PERCENT}%)",
+            summary.count
+        );
+    } else {
+        warn!(
+            "{label}, DataSize: {size}, Iterations: {}, CI: ±{percent:.2}%, target ±{TARGET_CI_PERCENT}% not reached",
+            summary.count
+        );
+    }
+}
+
+/// Data size used by the AES timing data-independence check.
+pub const PATTERN_DATA_SIZE: usize = 4096;
+
+/// Spread of the per-pattern mean times, as a percentage of the overall mean,
+/// above which the data-independence check flags the result.
+pub const PATTERN_SPREAD_LIMIT_PERCENT: f64 = 2.0;
+
+/// Check that AES-CTR timing does not depend on the plaintext.
+///
+/// The same data size is encrypted with several distinct input patterns and
+/// the mean per-iteration time of each is compared. The hardware engine is
+/// expected to take the same time regardless of content, so a spread above
+/// [`PATTERN_SPREAD_LIMIT_PERCENT`] is logged as a warning.
+pub fn benchmark_aes_data_independence(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let size = PATTERN_DATA_SIZE.min(bench.capacity());
+
+    let mut seed = 0x2545_F491_u32;
+    let patterns: [(&str, &mut dyn FnMut(usize) -> u8); 4] = [
+        ("zeros", &mut |_| 0x00),
+        ("ones", &mut |_| 0xFF),
+        ("alternating", &mut |i| if i % 2 == 0 { 0x00 } else { 0xFF }),
+        ("random", &mut |_| {
+            // xorshift32
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        }),
+    ];
+
+    let mut means = [0.0_f64; 4];
+    for ((name, pattern), mean) in patterns.into_iter().zip(&mut means) {
+        for (i, byte) in bench.input.as_mut_slice()[..size].iter_mut().enumerate() {
+            *byte = pattern(i);
+        }
+
+        let mut samples = Samples::<AES_ITERATIONS>::new();
+        for _ in 0..AES_ITERATIONS {
+            let start_time = Instant::now();
+            bench = bench.encrypt(size, || {})?;
+            samples.push(start_time.elapsed().as_micros());
+        }
+
+        let summary = Summary::of(samples.as_slice().iter().map(|&us| us as f64));
+        info!(
+            "AES-CTR (input: {name}), DataSize: {size}, Time: {} ± {} (95% CI)",
+            Elapsed(summary.mean),
+            Elapsed(summary.margin_95)
+        );
+        *mean = summary.mean;
+    }
+    bench.input.as_mut_slice().fill(0);
+
+    let min = means.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = means.iter().copied().fold(0.0, f64::max);
+    let overall = means.iter().sum::<f64>() / means.len() as f64;
+    let spread = (max - min) / overall * 100.0;
+    if spread > PATTERN_SPREAD_LIMIT_PERCENT {
+        warn!(
+            "AES-CTR timing depends on input data: spread {} ({spread:.1}%) exceeds {PATTERN_SPREAD_LIMIT_PERCENT}%",
+            Elapsed(max - min)
+        );
+    } else {
+        info!(
+            "AES-CTR data independence, Spread: {} ({spread:.1}%)",
+            Elapsed(max - min)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Largest TLS 1.3 record plaintext (RFC 8446, section 5.1).
+pub const TLS_RECORD_SIZE: usize = 16 * 1024;
+
+/// Number of timed records in the TLS record benchmark.
+pub const TLS_RECORD_ITERATIONS: usize = 10;
+
+/// Benchmark protecting full-size TLS 1.3 records with AES-256-GCM.
+///
+/// Each record covers the per-record work of RFC 8446, section 5.2: filling
+/// the record buffer with the plaintext and the content type byte, deriving
+/// the nonce from the static IV and the sequence number, and encrypting with
+/// the 5-byte record header as additional data. Reports the time per record,
+/// records per second and the plaintext throughput.
+pub fn benchmark_tls_record(mut bench: AesBench<'_>) -> AesResult<'_> {
+    const STATIC_IV: [u8; 12] = [0x3C; 12];
+    const CONTENT_TYPE_APPLICATION_DATA: u8 = 0x17;
+    const RECORD_LEN: usize = TLS_RECORD_SIZE + 1 + 16;
+    const HEADER: [u8; 5] = [
+        CONTENT_TYPE_APPLICATION_DATA,
+        0x03,
+        0x03,
+        (RECORD_LEN >> 8) as u8,
+        RECORD_LEN as u8,
+    ];
+
+    let mut record = [0_u8; TLS_RECORD_SIZE + 1];
+    let mut tag = [0_u8; 16];
+
+    let start_time = Instant::now();
+    for sequence in 0..TLS_RECORD_ITERATIONS as u64 {
+        record[..TLS_RECORD_SIZE].fill(0xAB);
+        record[TLS_RECORD_SIZE] = CONTENT_TYPE_APPLICATION_DATA;
+
+        let mut nonce = STATIC_IV;
+        for (byte, sequence_byte) in nonce[4..].iter_mut().zip(sequence.to_be_bytes()) {
+            *byte ^= sequence_byte;
+        }
+
+        bench = bench.encrypt_gcm(AES_KEY, &nonce, &HEADER, &mut record, &mut tag)?;
+    }
+    let elapsed = start_time.elapsed();
+
+    #[cfg(feature = "raw-timings")]
+    raw_timings::emit_frame("TLS-Record", TLS_RECORD_SIZE, &[elapsed.as_micros() as u32]);
+
+    let per_record = elapsed.as_micros() as f64 / TLS_RECORD_ITERATIONS as f64;
+    info!(
+        "TLS 1.3 record (AES-256-GCM), Plaintext: {TLS_RECORD_SIZE}, Time: {}/record, Records/s: {:.1}, Throughput: {}",
+        Elapsed(per_record),
+        1_000_000.0 / per_record,
+        Throughput::of(TLS_RECORD_SIZE, per_record)
+    );
+
+    Ok(bench)
+}
+
+/// Payload encrypted by the AES-GCM additional data benchmark.
+pub const GCM_PAYLOAD_SIZE: usize = 1024;
+
+/// Additional data sizes of the AES-GCM additional data benchmark.
+pub const GCM_AAD_SIZES: [usize; 6] = [0, 16, 64, 256, 1024, 4096];
+
+/// Number of timed operations per additional data size.
+pub const GCM_ITERATIONS: usize = 20;
+
+/// Benchmark AES-256-GCM over a [`GCM_PAYLOAD_SIZE`] payload with each of
+/// [`GCM_AAD_SIZES`] bytes of additional data, as in a record format with an
+/// authenticated but unencrypted header.
+///
+/// Each line reports the total time, its increase over no additional data,
+/// and the time GHASH alone takes over the additional data.
+pub fn benchmark_aes_gcm_aad(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let aad = [0x5A_u8; 4096];
+    let mut payload = [0xAB_u8; GCM_PAYLOAD_SIZE];
+    let mut tag = [0_u8; 16];
+    let nonce = [0_u8; 12];
+    let h = [0x42_u8; 16];
+
+    // Pre-warm the AES DMA
+    bench = bench.encrypt_gcm(AES_KEY, &nonce, &[], &mut payload, &mut tag)?;
+
+    let mut baseline_us = 0.0;
+    for aad_size in GCM_AAD_SIZES {
+        let aad = &aad[..aad_size];
+
+        let start_time = Instant::now();
+        for _ in 0..GCM_ITERATIONS {
+            bench = bench.encrypt_gcm(AES_KEY, &nonce, aad, &mut payload, &mut tag)?;
+        }
+        let total_us = start_time.elapsed().as_micros() as f64 / GCM_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..GCM_ITERATIONS {
+            let mut hash = Ghash::new(&h);
+            hash.update(aad);
+            core::hint::black_box(hash.finish(aad.len(), 0));
+        }
+        let ghash_us = start_time.elapsed().as_micros() as f64 / GCM_ITERATIONS as f64;
+
+        if aad_size == 0 {
+            baseline_us = total_us;
+        }
+        info!(
+            "AES-GCM, AAD: {aad_size}, Payload: {GCM_PAYLOAD_SIZE}, Time: {}, AAD cost: {:+}, GHASH(AAD): {}",
+            Elapsed(total_us),
+            Elapsed(total_us - baseline_us),
+            Elapsed(ghash_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Size of one slot of the streaming ring buffer.
+pub const STREAM_SLOT_SIZE: usize = 1024;
+
+/// Number of slots in the streaming ring buffer.
+pub const STREAM_SLOTS: usize = 8;
+
+/// How long the streaming benchmark keeps the stream running.
+pub const STREAM_DURATION: Duration = Duration::from_secs(1);
+
+/// Benchmark encrypting a continuous stream through a ring buffer of
+/// [`STREAM_SLOTS`] slots for [`STREAM_DURATION`].
+///
+/// Each step refills the next slot with fresh data, as a producer such as a
+/// sensor would, and encrypts it in place with AES-256-CTR. The counter runs
+/// on across slots, so the slots form one continuous CTR stream. The producer
+/// is never blocked, so the sustained throughput is what the encryption side
+/// can keep up with in steady state; the slowest slot is the latency budget a
+/// producer has to buffer for.
+pub fn benchmark_aes_stream(mut bench: AesBench<'_>) -> AesResult<'_> {
+    let mut ring = [0_u8; STREAM_SLOT_SIZE * STREAM_SLOTS];
+    let mut counter = 0_u128;
+    let mut slots = 0_usize;
+    let mut max_slot = Duration::from_micros(0);
+
+    let stream_start = Instant::now();
+    while stream_start.elapsed() < STREAM_DURATION {
+        let slot = ring[slots % STREAM_SLOTS * STREAM_SLOT_SIZE..]
+            .first_chunk_mut::<STREAM_SLOT_SIZE>()
+            .unwrap();
+        let slot_start = Instant::now();
+        slot.fill(slots as u8);
+        bench = bench.encrypt_ctr_with_iv(AES_KEY, &counter.to_be_bytes(), slot)?;
+        max_slot = max_slot.max(slot_start.elapsed());
+
+        counter = counter.wrapping_add((STREAM_SLOT_SIZE / 16) as u128);
+        slots += 1;
+    }
+    let elapsed = stream_start.elapsed();
+
+    let bytes = slots * STREAM_SLOT_SIZE;
+    info!(
+        "AES-CTR stream (AES-256, ring buffer {STREAM_SLOTS} x {STREAM_SLOT_SIZE}), Duration: {}, Bytes: {bytes}, Sustained: {}, Slowest slot: {}",
+        Elapsed::from_micros(elapsed.as_micros()),
+        Throughput::of(bytes, elapsed.as_micros() as f64),
+        Elapsed::from_micros(max_slot.as_micros())
+    );
+
+    Ok(bench)
+}
+
+/// Number of timed records per data size in the encrypt-then-MAC benchmark.
+#[cfg(feature = "bench-sha")]
+pub const ETM_ITERATIONS: usize = 20;
+
+/// Benchmark the encrypt-then-MAC pattern: each record is encrypted with
+/// AES-256-CTR over DMA and an HMAC-SHA256 is then computed over the
+/// ciphertext. Reports the combined throughput and the average per-record
+/// latency.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_encrypt_then_mac<'d>(
+    mut bench: AesBench<'d>,
+    sha: &mut Sha,
+    data_sizes: &[usize],
+) -> AesResult<'d> {
+    let mac_key = [0x5A_u8; 32];
+    let mut mac = [0_u8; 32];
+
+    for &size in data_sizes {
+        let size = size.min(bench.capacity());
+
+        let start_time = Instant::now();
+        for _ in 0..ETM_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+            if let Err(error) =
+                hmac_sha256(sha, &mac_key, &bench.output.as_slice()[..size], &mut mac)
+            {
+                return Err((error, Some(bench)));
+            }
+        }
+        let elapsed = start_time.elapsed();
+
+        #[cfg(feature = "raw-timings")]
+        raw_timings::emit_frame("AES-CTR+HMAC", size, &[elapsed.as_micros() as u32]);
+
+        let throughput = (ETM_ITERATIONS * size) as f64 / elapsed.as_micros() as f64;
+        info!(
+            "Encrypt-then-MAC (AES-CTR + HMAC-SHA256), DataSize: {size}, Throughput: {}, Latency: {}/record",
+            Throughput(throughput),
+            Elapsed(elapsed.as_micros() as f64 / ETM_ITERATIONS as f64)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the store-with-integrity benchmark.
+#[cfg(feature = "bench-sha")]
+pub const INTEGRITY_SIZES: [usize; 4] = [256, 1024, 4096, 16 * 1024];
+
+/// Operations per data size and primitive in the store-with-integrity
+/// benchmark.
+#[cfg(feature = "bench-sha")]
+pub const INTEGRITY_ITERATIONS: usize = 100;
+
+/// Benchmark storing data with an integrity check: each record is encrypted
+/// with AES-CTR and the ciphertext is then hashed with SHA-256, one after the
+/// other. AES-CTR and SHA-256 are also timed on their own, so the combined
+/// time can be compared with the sum of the two; a combined time below the
+/// sum means doing them back to back costs less than the primitives suggest.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_store_with_integrity<'d>(mut bench: AesBench<'d>, sha: &mut Sha) -> AesResult<'d> {
+    let mut digest = [0_u8; 32];
+
+    // Pre-warm both peripherals
+    bench = bench.encrypt(64, || {})?;
+    if let Err(error) = benchmark_single_sha256(sha, &bench.output.as_slice()[..64], &mut digest) {
+        return Err((error, Some(bench)));
+    }
+
+    let capacity = bench.capacity();
+    for size in INTEGRITY_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let start_time = Instant::now();
+        for _ in 0..INTEGRITY_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+        }
+        let aes_us = start_time.elapsed().as_micros() as f64 / INTEGRITY_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..INTEGRITY_ITERATIONS {
+            if let Err(error) =
+                benchmark_single_sha256(sha, &bench.output.as_slice()[..size], &mut digest)
+            {
+                return Err((error, Some(bench)));
+            }
+        }
+        let sha_us = start_time.elapsed().as_micros() as f64 / INTEGRITY_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..INTEGRITY_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+            if let Err(error) =
+                benchmark_single_sha256(sha, &bench.output.as_slice()[..size], &mut digest)
+            {
+                return Err((error, Some(bench)));
+            }
+        }
+        let combined_us = start_time.elapsed().as_micros() as f64 / INTEGRITY_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR then SHA-256 (store with integrity), DataSize: {size}, AES: {}, SHA: {}, Sum: {}, Combined: {}, vs sum: {}, Throughput: {}",
+            Elapsed(aes_us),
+            Elapsed(sha_us),
+            Elapsed(aes_us + sha_us),
+            Elapsed(combined_us),
+            Delta::between(Some(aes_us + sha_us), combined_us),
+            Throughput::of(size, combined_us)
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data sizes of the AES and SHA parallelism benchmark.
+#[cfg(feature = "bench-sha")]
+pub const PARALLEL_SIZES: [usize; 3] = [1024, 4096, 16 * 1024];
+
+/// Encryptions and digests per data size and schedule in the AES and SHA
+/// parallelism benchmark.
+#[cfg(feature = "bench-sha")]
+pub const PARALLEL_ITERATIONS: usize = 100;
+
+/// Measure whether the AES and SHA engines run concurrently.
+///
+/// Each data size is encrypted with AES-CTR and hashed with SHA-256
+/// [`PARALLEL_ITERATIONS`] times, first one after the other and then with the
+/// SHA engine fed from the polling loop of the AES DMA transfer; what is left
+/// of the message when the transfer completes is hashed afterwards. A speedup
+/// above 1 means the engines overlapped. The share of the message hashed
+/// during the transfer shows how far the SHA engine keeps up with the DMA.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_aes_sha_parallel<'d>(mut bench: AesBench<'d>, sha: &mut Sha) -> AesResult<'d> {
+    let input = [0xAB_u8; 16 * 1024];
+    let mut sequential_digest = [0_u8; 32];
+    let mut parallel_digest = [0_u8; 32];
+
+    // Pre-warm both peripherals
+    bench = bench.encrypt(64, || {})?;
+    if let Err(error) = benchmark_single_sha256(sha, &input[..64], &mut sequential_digest) {
+        return Err((error, Some(bench)));
+    }
+
+    let capacity = bench.capacity();
+    for size in PARALLEL_SIZES.into_iter().filter(|&size| size <= capacity) {
+        let message = &input[..size];
+
+        let start_time = Instant::now();
+        for _ in 0..PARALLEL_ITERATIONS {
+            bench = bench.encrypt(size, || {})?;
+            if let Err(error) = benchmark_single_sha256(sha, message, &mut sequential_digest) {
+                return Err((error, Some(bench)));
+            }
+        }
+        let sequential_us = start_time.elapsed().as_micros() as f64 / PARALLEL_ITERATIONS as f64;
+
+        let mut overlapped = 0;
+        let start_time = Instant::now();
+        for _ in 0..PARALLEL_ITERATIONS {
+            let mut digest = sha.start::<Sha256>();
+            let mut remaining = message;
+            bench = bench.encrypt(size, || {
+                if let Ok(rest) = digest.update(remaining) {
+                    remaining = rest;
+                }
+            })?;
+            overlapped += size - remaining.len();
+            if let Err(error) = sha256_update(&mut digest, remaining)
+                .and_then(|()| sha256_finish(&mut digest, &mut parallel_digest))
+            {
+                return Err((error, Some(bench)));
+            }
+        }
+        let parallel_us = start_time.elapsed().as_micros() as f64 / PARALLEL_ITERATIONS as f64;
+
+        info!(
+            "AES-CTR + SHA-256 in parallel, DataSize: {size}, Sequential: {}, Parallel: {}, Speedup: {:.2}x, Hashed during AES: {:.0}%, Digest: {}",
+            Elapsed(sequential_us),
+            Elapsed(parallel_us),
+            sequential_us / parallel_us,
+            overlapped as f64 / (PARALLEL_ITERATIONS * size) as f64 * 100.0,
+            if parallel_digest == sequential_digest {
+                "ok"
+            } else {
+                "MISMATCH"
+            }
+        );
+    }
+
+    Ok(bench)
+}
+
+/// Data size of each operation in the peripheral switching benchmark.
+#[cfg(feature = "bench-sha")]
+pub const SWITCH_DATA_SIZE: usize = 1024;
+
+/// Number of AES and SHA operations in the peripheral switching benchmark.
+#[cfg(feature = "bench-sha")]
+pub const SWITCH_ITERATIONS: usize = 100;
+
+/// Measure the cost of alternating between the AES and SHA peripherals.
+///
+/// [`SWITCH_ITERATIONS`] AES-CTR and SHA-256 operations of
+/// [`SWITCH_DATA_SIZE`] bytes are timed first back to back on each peripheral
+/// and then interleaved one AES, one SHA. Every interleaved operation follows
+/// one on the other peripheral, so the switching penalty is the extra time of
+/// the interleaved run divided by its `2 * SWITCH_ITERATIONS` switches.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_peripheral_switching<'d>(mut bench: AesBench<'d>, sha: &mut Sha) -> AesResult<'d> {
+    let size = SWITCH_DATA_SIZE.min(bench.capacity());
+    let input = [0xAB_u8; SWITCH_DATA_SIZE];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm both peripherals
+    bench = bench.encrypt(size, || {})?;
+    if let Err(error) = benchmark_single_sha256(sha, &input[..size], &mut output) {
+        return Err((error, Some(bench)));
+    }
+
+    let start_time = Instant::now();
+    for _ in 0..SWITCH_ITERATIONS {
+        bench = bench.encrypt(size, || {})?;
+    }
+    let aes_only = start_time.elapsed();
+
+    let start_time = Instant::now();
+    for _ in 0..SWITCH_ITERATIONS {
+        if let Err(error) = benchmark_single_sha256(sha, &input[..size], &mut output) {
+            return Err((error, Some(bench)));
+        }
+    }
+    let sha_only = start_time.elapsed();
+
+    let start_time = Instant::now();
+    for _ in 0..SWITCH_ITERATIONS {
+        bench = bench.encrypt(size, || {})?;
+        if let Err(error) = benchmark_single_sha256(sha, &input[..size], &mut output) {
+            return Err((error, Some(bench)));
+        }
+    }
+    let interleaved = start_time.elapsed();
+
+    let separate = aes_only.as_micros() + sha_only.as_micros();
+    let penalty =
+        (interleaved.as_micros() as f64 - separate as f64) / (2 * SWITCH_ITERATIONS) as f64;
+    info!(
+        "Peripheral switching (AES-CTR <-> SHA-256), DataSize: {size}, Separate: {}, Interleaved: {}, Penalty: {}/switch",
+        Elapsed::from_micros(separate),
+        Elapsed::from_micros(interleaved.as_micros()),
+        Elapsed(penalty)
+    );
+
+    Ok(bench)
+}
+
+/// Same as [`benchmark_single_aes_dma`], but calls `while_busy` repeatedly
+/// while each DMA transfer is in flight instead of idle-polling for completion.
+fn benchmark_single_aes_dma_with(
+    mut bench: AesBench<'_>,
+    buffer_size: usize,
+    mut while_busy: impl FnMut(),
+) -> AesResult<'_, (AesBench<'_>, f64, Summary, Option<BenchStats>)> {
+    // Ensure buffer_size doesn't exceed the allocated buffers
+    let actual_buffer_size = buffer_size.min(bench.capacity());
+
+    debug!(
+        "AES DMA benchmark started with buffer size: {} bytes",
+        actual_buffer_size
+    );
+
+    // Benchmark the AES process call. The timed region covers the whole
+    // transfer, so any cache maintenance esp-hal does when preparing and
+    // finishing it is included. On the C6 there is none: the buffers are in
+    // internal SRAM, which the GDMA accesses coherently, and the HAL only
+    // writes back or invalidates caches for PSRAM buffers on chips with
+    // PSRAM DMA.
+    let overhead_us = timestamp_overhead().as_micros();
+    let mut samples = Samples::<AES_ITERATIONS>::new();
+    let start_time = Instant::now();
+    for _ in 0..AES_ITERATIONS {
+        let iteration_start = Instant::now();
+        bench = bench.encrypt(actual_buffer_size, &mut while_busy)?;
+        samples.push(
+            iteration_start
+                .elapsed()
+                .as_micros()
+                .saturating_sub(overhead_us),
+        );
+    }
+    let elapsed = start_time.elapsed();
+
+    #[cfg(feature = "raw-timings")]
+    raw_timings::emit_frame("AES-CTR", actual_buffer_size, samples.as_slice());
+    #[cfg(feature = "histogram")]
+    log_histogram("AES-CTR", actual_buffer_size, samples.as_slice());
+
+    debug!(
+        "AES DMA process completed in {} for {} iterations",
+        Elapsed::from_micros(elapsed.as_micros()),
+        AES_ITERATIONS
+    );
+    debug!(
+        "Average time per iteration: {}",
+        Elapsed(elapsed.as_micros() as f64 / AES_ITERATIONS as f64)
+    );
+    let data_processed = AES_ITERATIONS * actual_buffer_size;
+    let throughput: f64 = data_processed as f64 / elapsed.as_micros() as f64 * 1_000_000.0; // bytes per second
+    debug!("Throughput: {}", Throughput(throughput / 1_000_000.0));
+    debug!("Throughput: {:.2} Mbps", throughput * 8.0 / 1_000_000.0);
+
+    // Bytes per microsecond is MB/s.
+    let summary = Summary::of(
+        samples
+            .as_slice()
+            .iter()
+            .map(|&us| actual_buffer_size as f64 / us.max(1) as f64),
+    );
+    let times = BenchStats::of(samples.as_mut_slice());
+
+    Ok((bench, throughput, summary, times))
+}
+
+/// Time the first AES-CTR encryption of the run against the warm ones after
+/// it, and return the time of the first in microseconds.
+pub fn cold_start_aes(mut bench: AesBench<'_>) -> AesResult<'_, (AesBench<'_>, u64)> {
+    let size = COLD_START_SIZE.min(bench.capacity());
+    let start_time = Instant::now();
+    bench = bench.encrypt(size, || {})?;
+    let cold_us = start_time.elapsed().as_micros();
+
+    let start_time = Instant::now();
+    for _ in 0..COLD_START_WARM_ITERATIONS {
+        bench = bench.encrypt(size, || {})?;
+    }
+    let warm_us = start_time.elapsed().as_micros() as f64 / COLD_START_WARM_ITERATIONS as f64;
+
+    log_cold_start("AES-CTR", size, cold_us, warm_us);
+    Ok((bench, cold_us))
+}
+
+/// Measure the wake cost of the AES peripheral clock: drop the driver, which
+/// gates the clock of the AES peripheral (and of the DMA, unless another
+/// driver holds it) and resets it, create it again and time the first
+/// encryption, against encryptions on the driver kept alive.
+///
+/// esp-hal has no public call to gate a peripheral clock, so dropping the
+/// last driver is how a power-managed design turns it off.
+pub fn benchmark_aes_wake(mut bench: AesBench<'_>) -> AesResult<'_> {
+    // Pre-warm the AES DMA
+    bench = bench.encrypt(WAKE_SIZE, || {})?;
+
+    let mut steady = Samples::<WAKE_ITERATIONS>::new();
+    for _ in 0..WAKE_ITERATIONS {
+        let (result, elapsed) = timed(|| bench.encrypt(WAKE_SIZE, || {}));
+        bench = result?;
+        steady.push(elapsed.as_micros());
+    }
+
+    let mut ungate = Samples::<WAKE_ITERATIONS>::new();
+    let mut first = Samples::<WAKE_ITERATIONS>::new();
+    for _ in 0..WAKE_ITERATIONS {
+        let AesBench { aes, output, input } = bench;
+        drop(aes);
+        // SAFETY: the previous driver has been dropped.
+        let (aes, elapsed) = timed(|| unsafe { attach_aes_dma() });
+        ungate.push(elapsed.as_micros());
+        bench = AesBench { aes, output, input };
+
+        let (result, elapsed) = timed(|| bench.encrypt(WAKE_SIZE, || {}));
+        bench = result?;
+        first.push(elapsed.as_micros());
+    }
+
+    log_wake(
+        "AES-CTR",
+        ungate.as_slice(),
+        first.as_slice(),
+        steady.as_slice(),
+    );
+    Ok(bench)
+}
+
+/// Run the AES known-answer tests and log whether each passed.
+pub fn self_test_aes(bench: AesBench<'_>) -> AesResult<'_> {
+    let (bench, passed) = self_test_aes_ctr_iv(bench)?;
+    if passed {
+        info!("AES-CTR IV known-answer test (NIST SP 800-38A F.5.5): PASS");
+    } else {
+        error!("AES-CTR IV known-answer test (NIST SP 800-38A F.5.5): FAIL");
+    }
+    let (bench, passed) = self_test_aes_gcm(bench)?;
+    if passed {
+        info!("AES-GCM known-answer test (GCM spec test case 16): PASS");
+    } else {
+        error!("AES-GCM known-answer test (GCM spec test case 16): FAIL");
+    }
+    let (bench, passed) = self_test_aes_ctr32_wrap(bench)?;
+    if passed {
+        info!("AES-CTR 96/32 counter wrap test: PASS");
+    } else {
+        error!("AES-CTR 96/32 counter wrap test: FAIL");
+    }
+    let (bench, passed) = self_test_aes_cmac(bench)?;
+    if passed {
+        info!("AES-CMAC known-answer test (RFC 4493 examples 1-4): PASS");
+    } else {
+        error!("AES-CMAC known-answer test (RFC 4493 examples 1-4): FAIL");
+    }
+    #[cfg(feature = "soft-aes-check")]
+    let (bench, _) = cross_check_aes_ctr(bench)?;
+    Ok(bench)
+}
@@ -0,0 +1,139 @@
+//! The hardware engines against software implementations of the same
+//! operations on the CPU (`soft-compare`).
+
+use crypto_bigint::{NonZero, Odd, U2048};
+use esp_hal::time::Duration;
+use log::{error, info};
+
+use super::aes::AES_KEY;
+use super::rsa::{RSA_BASE, RSA_EXPONENT, RSA_MODULUS};
+use super::{aborted, set_current_benchmark, timed};
+use crate::report::{Backend, Comparison, ComparisonCsv};
+use crate::units::{Elapsed, Throughput};
+
+/// Encryptions and hashes timed per data size by the software AES-CTR and
+/// SHA-256 baselines of `soft-compare`.
+pub const SOFT_COMPARE_ITERATIONS: usize = 10;
+
+/// Log the software time of `comparison` next to the hardware one with the
+/// speedup, then its two [`ComparisonCsv`] records prefixed with `CMP: `, so a
+/// host script can pick them out of the log under the header logged at the
+/// start of the run.
+fn log_comparison(comparison: &Comparison<'_>) {
+    info!(
+        "{} (software), DataSize: {}, Time: {}, Throughput: {}, Hardware: {} ({}), Speedup: {:.1}x",
+        comparison.algorithm,
+        comparison.data_size,
+        Elapsed(comparison.software_us),
+        Throughput(comparison.throughput_mb_per_s(Backend::Software)),
+        Elapsed(comparison.hardware_us),
+        Throughput(comparison.throughput_mb_per_s(Backend::Hardware)),
+        comparison.speedup()
+    );
+    for backend in [Backend::Hardware, Backend::Software] {
+        info!("CMP: {}", ComparisonCsv(comparison, backend));
+    }
+}
+
+/// Encrypt each of `data_sizes` [`SOFT_COMPARE_ITERATIONS`] times in CTR mode
+/// with the software AES of the `aes` crate, from the all-zero counter block
+/// of the engine, and log the speedup of `aes_throughputs`, the hardware
+/// AES-CTR sweep at the same sizes in bytes per microsecond.
+pub fn compare_aes_ctr(data_sizes: &[usize], aes_throughputs: &[f64]) {
+    use aes::cipher::{BlockEncrypt, KeyInit};
+    use aes::Aes256;
+
+    fn encrypt(cipher: &Aes256, data: &mut [u8]) {
+        let mut counter = 0_u128;
+        for chunk in data.chunks_mut(16) {
+            let mut block = counter.to_be_bytes().into();
+            cipher.encrypt_block(&mut block);
+            counter = counter.wrapping_add(1);
+            for (byte, keystream) in chunk.iter_mut().zip(&block) {
+                *byte ^= keystream;
+            }
+        }
+    }
+
+    let cipher = Aes256::new((&AES_KEY).into());
+    let mut data = [0xAB_u8; 32 * 1024];
+
+    // Pre-warm the code and data caches
+    encrypt(&cipher, &mut data[..64]);
+
+    for (&size, &hardware) in data_sizes.iter().zip(aes_throughputs) {
+        if aborted() {
+            break;
+        }
+        set_current_benchmark("AES-CTR (software)", Some(size));
+        let ((), elapsed) = timed(|| {
+            for _ in 0..SOFT_COMPARE_ITERATIONS {
+                encrypt(&cipher, core::hint::black_box(&mut data[..size]));
+            }
+        });
+        log_comparison(&Comparison {
+            algorithm: "AES-CTR",
+            data_size: size,
+            hardware_us: size as f64 / hardware.max(f64::MIN_POSITIVE),
+            software_us: elapsed.as_micros() as f64 / SOFT_COMPARE_ITERATIONS as f64,
+        });
+    }
+}
+
+/// Hash each of `data_sizes` [`SOFT_COMPARE_ITERATIONS`] times with the
+/// software SHA-256 of the `sha2` crate and log the speedup of
+/// `sha_throughputs`, the hardware SHA-256 sweep at the same sizes in bytes
+/// per microsecond.
+pub fn compare_sha256(data_sizes: &[usize], sha_throughputs: &[f64]) {
+    use sha2::Digest;
+
+    let input = [0xAB_u8; 32 * 1024];
+
+    // Pre-warm the code and data caches
+    core::hint::black_box(sha2::Sha256::digest(&input[..64]));
+
+    for (&size, &hardware) in data_sizes.iter().zip(sha_throughputs) {
+        if aborted() {
+            break;
+        }
+        set_current_benchmark("SHA-256 (software)", Some(size));
+        let ((), elapsed) = timed(|| {
+            for _ in 0..SOFT_COMPARE_ITERATIONS {
+                core::hint::black_box(sha2::Sha256::digest(core::hint::black_box(&input[..size])));
+            }
+        });
+        log_comparison(&Comparison {
+            algorithm: "SHA-256",
+            data_size: size,
+            hardware_us: size as f64 / hardware.max(f64::MIN_POSITIVE),
+            software_us: elapsed.as_micros() as f64 / SOFT_COMPARE_ITERATIONS as f64,
+        });
+    }
+}
+
+/// Compute the RSA-2048 exponentiation of [`benchmark_rsa`](super::rsa::benchmark_rsa) once with the
+/// Montgomery exponentiation of `crypto-bigint` on the CPU, check that it
+/// matches `hardware_result`, and log the speedup of `exponentiation`, the
+/// engine's time for it.
+pub fn compare_rsa_modexp(exponentiation: Duration, hardware_result: &[u32; U2048::LIMBS]) {
+    use crypto_bigint::modular::{MontyForm, MontyParams};
+
+    let modulus: U2048 = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let exponent: U2048 = RSA_EXPONENT.resize();
+    let params = MontyParams::new_vartime(Odd::new(modulus).unwrap());
+
+    set_current_benchmark("RSA-2048 (software)", None);
+    let (result, elapsed) = timed(|| MontyForm::new(&base, params).pow(&exponent).retrieve());
+    if result != U2048::from_words(*hardware_result) {
+        error!("RSA-2048 (software): result differs from the hardware exponentiation");
+    }
+    log_comparison(&Comparison {
+        algorithm: "RSA-2048",
+        data_size: U2048::BYTES,
+        hardware_us: exponentiation.as_micros() as f64,
+        software_us: elapsed.as_micros() as f64,
+    });
+}
@@ -0,0 +1,128 @@
+//! The AES, SHA and RSA engines busy at the same time (`concurrent`), with
+//! [`harness::measure_together`].
+
+extern crate alloc;
+
+use alloc::vec;
+
+use crypto_bigint::{NonZero, U2048};
+use esp_hal::aes::dma::CipherMode;
+use esp_hal::rsa::operand_sizes::Op2048;
+use esp_hal::rsa::Rsa;
+use esp_hal::sha::Sha;
+use esp_hal::Blocking;
+use log::info;
+
+use super::aes::{AesBench, AesDmaBenchmark, AesTarget};
+use super::rsa::{RsaBenchmark, RSA_BASE, RSA_EXPONENT, RSA_MODULUS};
+use super::sha::Sha256Benchmark;
+use super::{failed, set_current_benchmark, timed};
+use crate::error::BenchError;
+use crate::harness;
+use crate::units::{Elapsed, Throughput};
+
+/// Data size of the AES-CTR and SHA-256 tasks of the concurrent engines
+/// benchmark.
+pub const CONCURRENT_SIZE: usize = 16 * 1024;
+
+/// Iterations of the AES-CTR, SHA-256 and RSA-2048 tasks of the concurrent
+/// engines benchmark. AES and SHA get many short iterations so that they
+/// keep running through several exponentiations.
+pub const CONCURRENT_ITERATIONS: (usize, usize, usize) = (200, 50, 4);
+
+/// Measure contention between the engines: [`CONCURRENT_SIZE`] byte AES-CTR
+/// encryptions over DMA, SHA-256 digests of the same size and RSA-2048
+/// exponentiations with the dummy exponent, [`CONCURRENT_ITERATIONS`] each,
+/// first every task alone and then all three joined with
+/// [`harness::measure_together`], which polls them in turn. Logs each task's
+/// slowdown and the aggregate time and throughput of the three, and whether
+/// the SHA and RSA results stayed the same.
+///
+/// The RSA driver runs in async mode for this and is handed back blocking.
+/// If an AES transfer hangs, the engine is attached to its DMA channel
+/// again; the bench is `None` if there is no heap left for its buffers.
+pub fn benchmark_concurrent_engines<'d, 'r>(
+    bench: AesBench<'d>,
+    sha: &mut Sha,
+    rsa: Rsa<'r, Blocking>,
+) -> (Option<AesBench<'d>>, Rsa<'r, Blocking>) {
+    let (aes_iterations, sha_iterations, rsa_iterations) = CONCURRENT_ITERATIONS;
+    let data = vec![0xAB_u8; CONCURRENT_SIZE];
+    let modulus: U2048 = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let mut rsa = rsa.into_async();
+
+    let capacity = bench.capacity();
+    let AesBench { aes, output, input } = bench;
+    let mut aes = AesDmaBenchmark::new(
+        AesTarget { aes, output, input },
+        CONCURRENT_SIZE,
+        CipherMode::Ctr,
+    );
+    let mut hash = Sha256Benchmark::new(sha, &data);
+    let mut exponentiation = RsaBenchmark::<Op2048, _, { U2048::LIMBS }>::new(
+        &mut rsa,
+        "RSA-2048",
+        base,
+        RSA_EXPONENT.resize(),
+        modulus,
+    );
+
+    set_current_benchmark("Concurrent engines", None);
+    let outcome = (|| {
+        let alone = [
+            harness::measure_blocking(&mut aes, aes_iterations)?,
+            harness::measure_blocking(&mut hash, sha_iterations)?,
+            harness::measure_blocking(&mut exponentiation, rsa_iterations)?,
+        ];
+        let expected = (*hash.digest(), *exponentiation.result());
+        let ((aes_result, sha_result, rsa_result), wall) = timed(|| {
+            harness::measure_together(
+                (&mut aes, aes_iterations),
+                (&mut hash, sha_iterations),
+                (&mut exponentiation, rsa_iterations),
+            )
+        });
+        let concurrent = [aes_result?, sha_result?, rsa_result?];
+        let matches = expected == (*hash.digest(), *exponentiation.result());
+        Ok::<_, BenchError>((alone, concurrent, wall, matches))
+    })();
+
+    match outcome {
+        Ok((alone, concurrent, wall, matches)) => {
+            let (mut sequential_us, mut bytes) = (0.0, 0);
+            for (alone, concurrent) in alone.iter().zip(&concurrent) {
+                info!(
+                    "Concurrent engines, {}, DataSize: {}, Iterations: {}, Alone: {}, Concurrent: {}, Slowdown: {:.2}x",
+                    alone.algorithm,
+                    alone.data_size,
+                    alone.iterations,
+                    Elapsed(alone.time_us),
+                    Elapsed(concurrent.time_us),
+                    concurrent.time_us / alone.time_us.max(f64::MIN_POSITIVE)
+                );
+                sequential_us += alone.time_us * alone.iterations as f64;
+                bytes += alone.data_size * alone.iterations;
+            }
+            let wall_us = wall.as_micros() as f64;
+            info!(
+                "Concurrent engines, Total: Sequential: {}, Concurrent: {}, Saved: {:.0}%, Throughput: {} sequential, {} concurrent, Results match: {}",
+                Elapsed(sequential_us),
+                Elapsed(wall_us),
+                (sequential_us - wall_us) / sequential_us.max(f64::MIN_POSITIVE) * 100.0,
+                Throughput(bytes as f64 / sequential_us),
+                Throughput(bytes as f64 / wall_us.max(f64::MIN_POSITIVE)),
+                if matches { "yes" } else { "no" }
+            );
+        }
+        Err(error) => failed(error),
+    }
+
+    let bench = match aes.into_target() {
+        Some(AesTarget { aes, output, input }) => Some(AesBench { aes, output, input }),
+        None => AesBench::reattach(capacity).map_err(failed).ok(),
+    };
+    (bench, rsa.into_blocking())
+}
@@ -0,0 +1,267 @@
+//! ECC engine benchmarks (`bench-ecc`): point multiplication on P-192 and
+//! P-256, and ECDSA P-256 built on it.
+
+use crypto_bigint::{NonZero, Odd, U256};
+use esp_hal::ecc::{self, Ecc, EllipticCurve};
+use esp_hal::Blocking;
+use log::{info, warn};
+
+use super::{hex_bytes, log_result, set_current_benchmark, timed};
+use crate::report::BenchmarkResult;
+use crate::units::Elapsed;
+
+/// Point multiplications and ECDSA operations timed by the ECC benchmark,
+/// per curve and operation.
+pub const ECC_ITERATIONS: usize = 20;
+
+/// The prime p of the P-256 field.
+const P256_P: U256 =
+    U256::from_be_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+
+/// The order n of the P-256 base point.
+const P256_N: U256 =
+    U256::from_be_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+
+/// The P-256 base point G (FIPS 186-4, D.1.2.3), big-endian x then y.
+const P256_G: ([u8; 32], [u8; 32]) = (
+    hex_bytes("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+    hex_bytes("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+);
+
+/// The P-192 base point G (FIPS 186-4, D.1.2.1), big-endian x then y.
+const P192_G: ([u8; 24], [u8; 24]) = (
+    hex_bytes("188da80eb03090f67cbf20eb43a18800f4ff0afd82ff1012"),
+    hex_bytes("07192b95ffc8da78631011ed6b24cdd573f977a11e794811"),
+);
+
+/// P-192 private key of RFC 6979, A.2.3, and its public key, the result
+/// the P-192 point multiplication has to produce.
+const P192_KEY: ([u8; 24], [u8; 24], [u8; 24]) = (
+    hex_bytes("6fab034934e4c0fc9ae67f5b5659a9d7d1fefd187ee09fd4"),
+    hex_bytes("ac2c77f529f91689fea0ea5efec7f210d8eea0b9e047ed56"),
+    hex_bytes("3bc723e57670bd4887ebc732c523063d0a7c957bc97c1c43"),
+);
+
+/// P-256 private key of RFC 6979, A.2.5, and its public key.
+const P256_KEY: ([u8; 32], [u8; 32], [u8; 32]) = (
+    hex_bytes("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"),
+    hex_bytes("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"),
+    hex_bytes("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"),
+);
+
+/// SHA-256 of "sample", the deterministic nonce RFC 6979 derives for it
+/// with [`P256_KEY`], and the resulting signature (r, s) of A.2.5.
+const ECDSA_SAMPLE: ([u8; 32], [u8; 32], [u8; 32], [u8; 32]) = (
+    hex_bytes("af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf"),
+    hex_bytes("a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60"),
+    hex_bytes("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716"),
+    hex_bytes("f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"),
+);
+
+/// Time [`ECC_ITERATIONS`] affine point multiplications `k * (x, y)` on
+/// `curve` and log the mean, whether the result is `expected` and the
+/// operations per second. Returns the mean time in microseconds, or `None`
+/// if the engine rejected the operands.
+fn benchmark_ecc_point_multiplication<const L: usize>(
+    ecc: &mut Ecc<'_, Blocking>,
+    label: &'static str,
+    curve: &EllipticCurve,
+    k: &[u8; L],
+    point: &([u8; L], [u8; L]),
+    expected: &([u8; L], [u8; L]),
+) -> Option<f64> {
+    let mut result = *point;
+    let (outcome, elapsed) = timed(|| {
+        for _ in 0..ECC_ITERATIONS {
+            result = *point;
+            ecc.affine_point_multiplication(curve, k, &mut result.0, &mut result.1)?;
+        }
+        Ok::<(), ecc::Error>(())
+    });
+    if let Err(error) = outcome {
+        warn!("{label}: {error:?}");
+        return None;
+    }
+    let time_us = elapsed.as_micros() as f64 / ECC_ITERATIONS as f64;
+    info!(
+        "{label}, Time: {}, Operations/s: {:.1}, Result matches: {}",
+        Elapsed(time_us),
+        1_000_000.0 / time_us,
+        if result == *expected { "yes" } else { "no" }
+    );
+    log_result(&BenchmarkResult {
+        algorithm: label,
+        data_size: L,
+        iterations: ECC_ITERATIONS,
+        time_us,
+    });
+    Some(time_us)
+}
+
+/// The x coordinate of the sum of the affine P-256 points `a` and `b`,
+/// computed on the CPU, since the ESP32-C6 engine has no point addition; or
+/// `None` if they share their x coordinate (`a = ±b`), which the affine
+/// formula does not cover.
+fn p256_add_x(a: (U256, U256), b: (U256, U256)) -> Option<U256> {
+    let p = NonZero::new(P256_P).unwrap();
+    let dx_inv = Option::<U256>::from(
+        b.0.sub_mod(&a.0, &P256_P)
+            .inv_odd_mod(&Odd::new(P256_P).unwrap()),
+    )?;
+    let lambda = b.1.sub_mod(&a.1, &P256_P).mul_mod_vartime(&dx_inv, &p);
+    Some(
+        lambda
+            .mul_mod_vartime(&lambda, &p)
+            .sub_mod(&a.0, &P256_P)
+            .sub_mod(&b.0, &P256_P),
+    )
+}
+
+/// ECDSA P-256 signature (r, s) of `digest` under the private key `d` with
+/// the nonce `k`: `k * G` on the engine, `r = x mod n` and
+/// `s = k^-1 (z + r d) mod n` on the CPU.
+fn ecdsa_p256_sign(
+    ecc: &mut Ecc<'_, Blocking>,
+    d: &[u8; 32],
+    k: &[u8; 32],
+    digest: &[u8; 32],
+) -> Result<(U256, U256), ecc::Error> {
+    let n = NonZero::new(P256_N).unwrap();
+    let (mut x, mut y) = P256_G;
+    ecc.affine_point_multiplication(&EllipticCurve::P256, k, &mut x, &mut y)?;
+    let r = U256::from_be_slice(&x).rem_vartime(&n);
+    let z = U256::from_be_slice(digest).rem_vartime(&n);
+    let k_inv = U256::from_be_slice(k)
+        .inv_odd_mod(&Odd::new(P256_N).unwrap())
+        .unwrap();
+    let rd = r.mul_mod_vartime(&U256::from_be_slice(d), &n);
+    Ok((r, k_inv.mul_mod_vartime(&z.add_mod(&rd, &P256_N), &n)))
+}
+
+/// Whether (r, s) is a valid ECDSA P-256 signature of `digest` for the
+/// public key `q`: `u1 * G` and `u2 * Q` on the engine, their sum and the
+/// scalars on the CPU.
+///
+/// The case `u1 * G = ±u2 * Q`, which a valid signature hits with negligible
+/// probability, is reported as invalid.
+fn ecdsa_p256_verify(
+    ecc: &mut Ecc<'_, Blocking>,
+    q: &([u8; 32], [u8; 32]),
+    digest: &[u8; 32],
+    r: &U256,
+    s: &U256,
+) -> Result<bool, ecc::Error> {
+    let n = NonZero::new(P256_N).unwrap();
+    if *r == U256::ZERO || *r >= P256_N || *s == U256::ZERO || *s >= P256_N {
+        return Ok(false);
+    }
+    let z = U256::from_be_slice(digest).rem_vartime(&n);
+    let w = s.inv_odd_mod(&Odd::new(P256_N).unwrap()).unwrap();
+    let u1 = z.mul_mod_vartime(&w, &n).to_be_bytes();
+    let u2 = r.mul_mod_vartime(&w, &n).to_be_bytes();
+
+    let (mut x1, mut y1) = P256_G;
+    ecc.affine_point_multiplication(&EllipticCurve::P256, &u1, &mut x1, &mut y1)?;
+    let (mut x2, mut y2) = *q;
+    ecc.affine_point_multiplication(&EllipticCurve::P256, &u2, &mut x2, &mut y2)?;
+
+    let x = p256_add_x(
+        (U256::from_be_slice(&x1), U256::from_be_slice(&y1)),
+        (U256::from_be_slice(&x2), U256::from_be_slice(&y2)),
+    );
+    Ok(x.is_some_and(|x| x.rem_vartime(&n) == *r))
+}
+
+/// Time the ECC engine: P-192 and P-256 point multiplications with the
+/// RFC 6979 test keys, checked against their public keys, then
+/// [`ECC_ITERATIONS`] ECDSA P-256 signatures and verifications of the
+/// RFC 6979 A.2.5 "sample" message, checked against its published
+/// signature.
+///
+/// The engine only multiplies points; the ECDSA scalar arithmetic mod n and
+/// the point addition of the verification run on the CPU with
+/// `crypto-bigint`. Each ECDSA line logs how much of it is the engine.
+pub fn benchmark_ecc(ecc: &mut Ecc<'_, Blocking>) {
+    let (d, qx, qy) = P192_KEY;
+    set_current_benchmark("ECC P-192 point multiplication", None);
+    benchmark_ecc_point_multiplication(
+        ecc,
+        "ECC P-192 point multiplication",
+        &EllipticCurve::P192,
+        &d,
+        &P192_G,
+        &(qx, qy),
+    );
+
+    let (d, qx, qy) = P256_KEY;
+    set_current_benchmark("ECC P-256 point multiplication", None);
+    let Some(multiplication_us) = benchmark_ecc_point_multiplication(
+        ecc,
+        "ECC P-256 point multiplication",
+        &EllipticCurve::P256,
+        &d,
+        &P256_G,
+        &(qx, qy),
+    ) else {
+        return;
+    };
+
+    let (digest, k, r, s) = ECDSA_SAMPLE;
+    let expected = (U256::from_be_slice(&r), U256::from_be_slice(&s));
+    set_current_benchmark("ECDSA P-256 sign", None);
+    let mut signature = (U256::ZERO, U256::ZERO);
+    let (outcome, elapsed) = timed(|| {
+        for _ in 0..ECC_ITERATIONS {
+            signature = ecdsa_p256_sign(ecc, &d, &k, &digest)?;
+        }
+        Ok::<(), ecc::Error>(())
+    });
+    if let Err(error) = outcome {
+        warn!("ECDSA P-256 sign: {error:?}");
+        return;
+    }
+    let sign_us = elapsed.as_micros() as f64 / ECC_ITERATIONS as f64;
+    info!(
+        "ECDSA P-256 sign (RFC 6979 A.2.5), Time: {}, Point multiplication: {} ({:.0}%), Signatures/s: {:.1}, Signature matches: {}",
+        Elapsed(sign_us),
+        Elapsed(multiplication_us),
+        multiplication_us / sign_us * 100.0,
+        1_000_000.0 / sign_us,
+        if signature == expected { "yes" } else { "no" }
+    );
+
+    set_current_benchmark("ECDSA P-256 verify", None);
+    let mut valid = false;
+    let (outcome, elapsed) = timed(|| {
+        for _ in 0..ECC_ITERATIONS {
+            valid = ecdsa_p256_verify(ecc, &(qx, qy), &digest, &expected.0, &expected.1)?;
+        }
+        Ok::<(), ecc::Error>(())
+    });
+    if let Err(error) = outcome {
+        warn!("ECDSA P-256 verify: {error:?}");
+        return;
+    }
+    let verify_us = elapsed.as_micros() as f64 / ECC_ITERATIONS as f64;
+    info!(
+        "ECDSA P-256 verify (RFC 6979 A.2.5), Time: {}, Point multiplications: {} ({:.0}%), Verifications/s: {:.1}, Signature: {}",
+        Elapsed(verify_us),
+        Elapsed(2.0 * multiplication_us),
+        2.0 * multiplication_us / verify_us * 100.0,
+        1_000_000.0 / verify_us,
+        if valid { "valid" } else { "invalid" }
+    );
+
+    log_result(&BenchmarkResult {
+        algorithm: "ECDSA P-256 sign",
+        data_size: digest.len(),
+        iterations: ECC_ITERATIONS,
+        time_us: sign_us,
+    });
+    log_result(&BenchmarkResult {
+        algorithm: "ECDSA P-256 verify",
+        data_size: digest.len(),
+        iterations: ECC_ITERATIONS,
+        time_us: verify_us,
+    });
+}
@@ -0,0 +1,169 @@
+//! HMAC engine benchmarks with eFuse keys (`hmac-efuse`).
+
+use esp_hal::hmac::{self, Hmac, HmacPurpose, KeyId};
+use log::{info, warn};
+
+use super::{aborted, log_result, set_current_benchmark, timed};
+use crate::error::BusyWait;
+use crate::report::BenchmarkResult;
+use crate::units::{Elapsed, Throughput};
+
+/// eFuse key block of the HMAC benchmarks. It has to be burned with an HMAC
+/// key and purpose beforehand, e.g. with `espefuse.py burn_key BLOCK_KEY0
+/// key.bin HMAC_UP`; change it here to use another block.
+pub const HMAC_KEY_ID: KeyId = KeyId::Key0;
+
+/// HMACs timed in the upstream mode.
+pub const HMAC_ITERATIONS: usize = 100;
+
+/// HMACs timed per data size by the HMAC peripheral sweep.
+pub const HMAC_SWEEP_ITERATIONS: usize = 10;
+
+/// Why an upstream HMAC did not produce a result.
+#[derive(Clone, Copy, Debug)]
+enum HmacError {
+    /// The key block was not burned for upstream use.
+    Configure(hmac::Error),
+    /// The peripheral stayed busy for [`ENGINE_TIMEOUT`] during a step of
+    /// the HMAC.
+    ///
+    /// [`ENGINE_TIMEOUT`]: crate::error::ENGINE_TIMEOUT
+    Timeout,
+}
+
+/// One upstream (`ToUser`) HMAC-SHA256 of `message` on the peripheral with
+/// the eFuse key [`HMAC_KEY_ID`], read back into `output`.
+///
+/// The configure, update and finalize loops poll while the driver reports
+/// `WouldBlock` and give up with [`HmacError::Timeout`] once a [`BusyWait`]
+/// expires, so a hung peripheral does not stall the run.
+fn hmac_upstream(
+    hmac: &mut Hmac<'_>,
+    message: &[u8],
+    output: &mut [u8; 32],
+) -> Result<(), HmacError> {
+    hmac.init();
+    let mut busy = BusyWait::default();
+    loop {
+        match hmac.configure(HmacPurpose::ToUser, HMAC_KEY_ID) {
+            Ok(()) => break,
+            Err(nb::Error::WouldBlock) => {
+                if busy.expired() {
+                    return Err(HmacError::Timeout);
+                }
+            }
+            Err(nb::Error::Other(error)) => return Err(HmacError::Configure(error)),
+        }
+    }
+    let mut remaining = message;
+    while !remaining.is_empty() {
+        match hmac.update(remaining) {
+            Ok(rest) => {
+                remaining = rest;
+                busy = BusyWait::default();
+            }
+            Err(nb::Error::WouldBlock) => {
+                if busy.expired() {
+                    return Err(HmacError::Timeout);
+                }
+            }
+            Err(nb::Error::Other(never)) => match never {},
+        }
+    }
+    loop {
+        match hmac.finalize(output) {
+            Ok(()) => return Ok(()),
+            Err(nb::Error::WouldBlock) => {
+                if busy.expired() {
+                    return Err(HmacError::Timeout);
+                }
+            }
+            Err(nb::Error::Other(never)) => match never {},
+        }
+    }
+}
+
+/// Time the HMAC peripheral in each of its modes with the eFuse key
+/// [`HMAC_KEY_ID`] and log the latency per mode.
+///
+/// Upstream (`ToUser`) is a complete HMAC-SHA256 of a 32-byte message read
+/// back by the CPU, the mean of [`HMAC_ITERATIONS`]. The downstream modes
+/// derive a key for the JTAG (`ToJtag`) or digital signature (`ToDs`)
+/// peripheral from a fixed message without handing the result to the CPU;
+/// esp-hal only exposes selecting them, so what is timed is `init` and
+/// `configure` once each. A mode the key block was not burned for logs the
+/// purpose mismatch instead.
+pub fn benchmark_hmac_modes(hmac: &mut Hmac<'_>) {
+    let message = [0xAB_u8; 32];
+    let mut output = [0_u8; 32];
+
+    let (result, elapsed) = timed(|| {
+        for _ in 0..HMAC_ITERATIONS {
+            hmac_upstream(hmac, &message, &mut output)?;
+        }
+        Ok::<(), HmacError>(())
+    });
+    match result {
+        Ok(()) => info!(
+            "HMAC upstream (ToUser), Key: {HMAC_KEY_ID:?}, DataSize: {}, Time: {}, Iterations: {HMAC_ITERATIONS}",
+            message.len(),
+            Elapsed(elapsed.as_micros() as f64 / HMAC_ITERATIONS as f64)
+        ),
+        Err(error) => warn!("HMAC upstream (ToUser), Key: {HMAC_KEY_ID:?}: {error:?}"),
+    }
+
+    for purpose in [HmacPurpose::ToJtag, HmacPurpose::ToDs] {
+        let (result, elapsed) = timed(|| {
+            hmac.init();
+            hmac.configure(purpose, HMAC_KEY_ID)
+        });
+        match result {
+            Ok(()) => info!(
+                "HMAC downstream ({purpose:?}), Key: {HMAC_KEY_ID:?}, Configure: {}",
+                Elapsed::from_micros(elapsed.as_micros())
+            ),
+            Err(error) => warn!("HMAC downstream ({purpose:?}), Key: {HMAC_KEY_ID:?}: {error:?}"),
+        }
+    }
+}
+
+/// Run the upstream HMAC-SHA256 of the peripheral over the same
+/// `data_sizes` as the SHA-256 sweep, [`HMAC_SWEEP_ITERATIONS`] times each,
+/// and log the time and throughput per size.
+///
+/// Stops at the first error, which with a key block burned for another
+/// purpose is the mismatch for the first size, and with a hung peripheral
+/// the timeout.
+pub fn benchmark_hmac(hmac: &mut Hmac<'_>, data_sizes: &[usize]) {
+    let message = [0xAB_u8; 32 * 1024];
+    let mut output = [0_u8; 32];
+
+    for &size in data_sizes {
+        if aborted() {
+            break;
+        }
+        set_current_benchmark("HMAC-SHA256 (peripheral)", Some(size));
+        let (result, elapsed) = timed(|| {
+            for _ in 0..HMAC_SWEEP_ITERATIONS {
+                hmac_upstream(hmac, &message[..size], &mut output)?;
+            }
+            Ok::<(), HmacError>(())
+        });
+        if let Err(error) = result {
+            warn!("HMAC-SHA256 (peripheral), Key: {HMAC_KEY_ID:?}, DataSize: {size}: {error:?}");
+            return;
+        }
+        let time_us = elapsed.as_micros() as f64 / HMAC_SWEEP_ITERATIONS as f64;
+        info!(
+            "HMAC-SHA256 (peripheral), Key: {HMAC_KEY_ID:?}, DataSize: {size}, Time: {}, Throughput: {}",
+            Elapsed(time_us),
+            Throughput(size as f64 / time_us.max(f64::MIN_POSITIVE))
+        );
+        log_result(&BenchmarkResult {
+            algorithm: "HMAC-SHA256 (peripheral)",
+            data_size: size,
+            iterations: HMAC_SWEEP_ITERATIONS,
+            time_us,
+        });
+    }
+}
@@ -0,0 +1,101 @@
+//! Hardware random number generator benchmarks (`bench-rng`).
+
+use esp_hal::rng::Trng;
+use esp_hal::time::Instant;
+use log::info;
+
+use super::{log_result, timed};
+use crate::report::BenchmarkResult;
+use crate::stats::{Samples, Summary};
+use crate::units::{Elapsed, Throughput};
+
+/// Bytes read per iteration of the bulk RNG benchmark.
+pub const RNG_BULK_SIZE: usize = 4096;
+
+/// Random bytes needed for one RSA-2048 key generation attempt: the 2048 bits
+/// of its two 1024-bit prime candidates.
+pub const RNG_KEYGEN_SIZE: usize = 256;
+
+/// Number of timed reads of each RNG benchmark.
+pub const RNG_ITERATIONS: usize = 20;
+
+/// Number of timed [`RNG_KEYGEN_SIZE`] reads of the RNG steady-state check.
+pub const RNG_STEADY_ITERATIONS: usize = 1000;
+
+/// Benchmark the hardware RNG, with the ADC noise source enabled by
+/// [`Trng`]: the bulk throughput over [`RNG_BULK_SIZE`] byte reads, and
+/// separately the time to fill the [`RNG_KEYGEN_SIZE`] byte buffer an
+/// RSA-2048 key generation draws its randomness from.
+///
+/// The HAL reads the RNG data register without waiting for new entropy to be
+/// mixed in, so these are read rates, not the rate at which the hardware
+/// gathers entropy.
+pub fn benchmark_rng(rng: &mut Trng<'_>) {
+    let mut bulk = [0_u8; RNG_BULK_SIZE];
+    let start_time = Instant::now();
+    for _ in 0..RNG_ITERATIONS {
+        rng.read(&mut bulk);
+    }
+    let elapsed = start_time.elapsed();
+    info!(
+        "RNG, DataSize: {RNG_BULK_SIZE}, Throughput: {}",
+        Throughput::of(RNG_ITERATIONS * RNG_BULK_SIZE, elapsed.as_micros() as f64)
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "RNG",
+        data_size: RNG_BULK_SIZE,
+        iterations: RNG_ITERATIONS,
+        time_us: elapsed.as_micros() as f64 / RNG_ITERATIONS as f64,
+    });
+
+    let mut keygen = [0_u8; RNG_KEYGEN_SIZE];
+    let mut samples = Samples::<RNG_ITERATIONS>::new();
+    for _ in 0..RNG_ITERATIONS {
+        let ((), elapsed) = timed(|| rng.read(&mut keygen));
+        samples.push(elapsed.as_micros());
+    }
+    let times = samples.as_slice();
+    let summary = Summary::of(times.iter().map(|&us| us as f64));
+    info!(
+        "RNG (RSA-2048 key generation), DataSize: {RNG_KEYGEN_SIZE}, Time: {}, Min: {}, Max: {}",
+        Elapsed(summary.mean),
+        Elapsed::from_micros(times.iter().copied().min().unwrap_or(0).into()),
+        Elapsed::from_micros(times.iter().copied().max().unwrap_or(0).into())
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "RNG (RSA-2048 key generation)",
+        data_size: RNG_KEYGEN_SIZE,
+        iterations: RNG_ITERATIONS,
+        time_us: summary.mean,
+    });
+
+    benchmark_rng_steady_state(rng);
+}
+
+/// Read [`RNG_KEYGEN_SIZE`] bytes [`RNG_STEADY_ITERATIONS`] times and log the
+/// mean and maximum read time and the number of reads taking more than twice
+/// the mean, which would reveal reseeding or health tests stalling reads.
+///
+/// The HAL has no way to force a reseed or run a health test, so only the
+/// steady state is measured.
+fn benchmark_rng_steady_state(rng: &mut Trng<'_>) {
+    let mut buffer = [0_u8; RNG_KEYGEN_SIZE];
+    let mut samples = Samples::<RNG_STEADY_ITERATIONS>::new();
+    for _ in 0..RNG_STEADY_ITERATIONS {
+        let ((), elapsed) = timed(|| rng.read(&mut buffer));
+        samples.push(elapsed.as_micros());
+    }
+
+    let times = samples.as_slice();
+    let summary = Summary::of(times.iter().map(|&us| us as f64));
+    let spikes = times
+        .iter()
+        .filter(|&&us| f64::from(us) > 2.0 * summary.mean)
+        .count();
+    info!(
+        "RNG steady state, DataSize: {RNG_KEYGEN_SIZE}, Reads: {}, Time: {}, Max: {}, Spikes (> 2x mean): {spikes}",
+        summary.count,
+        Elapsed(summary.mean),
+        Elapsed::from_micros(times.iter().copied().max().unwrap_or(0).into())
+    );
+}
@@ -0,0 +1,1492 @@
+//! RSA modular arithmetic benchmarks (`bench-rsa`).
+//!
+//! [`run_rsa_benchmark`] measures one exponentiation through the
+//! [`RsaBenchmark`] adapter, which also runs on the async driver, and logs
+//! nothing. The other benchmarks borrow the blocking driver, cover the
+//! operand sizes from 512 to 3072 bits and what surrounds an RSA operation
+//! in practice (Montgomery parameters, CRT, blinding, hash-then-sign), and
+//! log their figures as they go.
+
+extern crate alloc;
+
+use alloc::format;
+#[cfg(feature = "verify")]
+use alloc::vec::Vec;
+
+use crypto_bigint::modular::{MontyForm, MontyParams};
+use crypto_bigint::{NonZero, Odd, Uint, U1024, U2048, U3072, U512};
+use esp_hal::rsa::operand_sizes::{Op1024, Op2048, Op32, Op512};
+use esp_hal::rsa::{Rsa, RsaMode, RsaModularExponentiation, RsaModularMultiplication};
+#[cfg(feature = "bench-sha")]
+use esp_hal::sha::{Sha, Sha256};
+use esp_hal::time::{Duration, Instant};
+use esp_hal::{Async, Blocking, DriverMode};
+#[cfg(feature = "bench-sha")]
+use log::error;
+use log::info;
+
+#[cfg(feature = "soft-compare")]
+use super::compare::compare_rsa_modexp;
+#[cfg(feature = "hex-dump")]
+use super::log_hex;
+#[cfg(feature = "histogram")]
+use super::log_histogram;
+#[cfg(feature = "bench-sha")]
+use super::set_current_benchmark;
+#[cfg(feature = "bench-sha")]
+use super::sha::{benchmark_single_sha256, sha256_finish, sha256_update};
+use super::{
+    aborted, failed, known_answer, log_cold_start, log_result, timed, COLD_START_WARM_ITERATIONS,
+};
+#[cfg(feature = "datasheet-power")]
+use crate::datasheet_power;
+use crate::error::BenchError;
+use crate::harness::{self, Benchmark};
+#[cfg(feature = "verify")]
+use crate::hex::Hex;
+#[cfg(feature = "raw-timings")]
+use crate::raw_timings;
+use crate::report::BenchmarkResult;
+use crate::stats::{trimmed_mean, Samples, Summary};
+#[cfg(feature = "thresholds")]
+use crate::thresholds;
+use crate::units::Elapsed;
+
+/// Time one modular exponentiation `base^exponent mod modulus` with the
+/// operand size `T` of `N` words, writing the result as little-endian words
+/// to `result`, e.g. `run_rsa_benchmark::<Op2048, { U2048::LIMBS }>(...)`.
+///
+/// `modulus` has to be odd and `base` smaller than it. The time covers the
+/// exponentiation only, not computing and loading the Montgomery parameters.
+pub fn run_rsa_benchmark<'a, T, const N: usize>(
+    rsa: &mut Rsa<'_, Blocking>,
+    label: &'a str,
+    base: &Uint<N>,
+    exponent: &Uint<N>,
+    modulus: &Uint<N>,
+    result: &mut [u32; N],
+) -> Result<BenchmarkResult<'a>, BenchError>
+where
+    T: RsaMode<InputType = [u32; N]>,
+{
+    let mut benchmark = RsaBenchmark::<T, _, N>::new(rsa, label, *base, *exponent, *modulus);
+    let measured = harness::measure_blocking(&mut benchmark, 1)?;
+    *result = *benchmark.result();
+    // The benchmark ties the label to its borrow of the driver; the result
+    // keeps it for as long as the caller lent it.
+    Ok(BenchmarkResult {
+        algorithm: label,
+        ..measured
+    })
+}
+
+/// Montgomery parameters the RSA accelerator needs for an odd `modulus` M
+/// of `N` words: `r = R^2 mod M` with `R = 2^(32 * N)`, and
+/// `m_prime = -M^-1 mod 2^32`.
+pub fn rsa_montgomery_params<const N: usize>(modulus: &Uint<N>) -> (Uint<N>, u32) {
+    let params = MontyParams::new_vartime(Odd::new(*modulus).unwrap());
+    // The Montgomery form of x is x * R mod M, so converting 1 yields R mod M
+    // and converting that again yields R^2 mod M.
+    let r_mod_m = *MontyForm::new(&Uint::ONE, params).as_montgomery();
+    let r = *MontyForm::new(&r_mod_m, params).as_montgomery();
+
+    // Newton's iteration doubles the number of correct low bits of the
+    // inverse each step; M is odd, so M is its own inverse mod 8.
+    let m0 = modulus.as_words()[0];
+    let mut inverse = m0;
+    for _ in 0..4 {
+        inverse = inverse.wrapping_mul(2_u32.wrapping_sub(m0.wrapping_mul(inverse)));
+    }
+    (r, inverse.wrapping_neg())
+}
+
+/// Modular exponentiations `base^exponent mod modulus` with the operand
+/// size `T` of `N` words, on the blocking or the async RSA driver.
+///
+/// [`Benchmark::setup`] loads the exponent, the modulus and their Montgomery
+/// parameters into the engine, so a run times the exponentiation only.
+pub struct RsaBenchmark<'a, 'd, T: RsaMode, Dm: DriverMode, const N: usize> {
+    rsa: Option<&'a mut Rsa<'d, Dm>>,
+    mod_exp: Option<RsaModularExponentiation<'a, 'd, T, Dm>>,
+    label: &'a str,
+    base: Uint<N>,
+    exponent: Uint<N>,
+    modulus: Uint<N>,
+    r: Uint<N>,
+    result: [u32; N],
+}
+
+impl<'a, 'd, T, Dm, const N: usize> RsaBenchmark<'a, 'd, T, Dm, N>
+where
+    T: RsaMode<InputType = [u32; N]>,
+    Dm: DriverMode,
+{
+    /// A benchmark labelled `label`. `modulus` has to be odd and `base`
+    /// smaller than it.
+    pub fn new(
+        rsa: &'a mut Rsa<'d, Dm>,
+        label: &'a str,
+        base: Uint<N>,
+        exponent: Uint<N>,
+        modulus: Uint<N>,
+    ) -> Self {
+        Self {
+            rsa: Some(rsa),
+            mod_exp: None,
+            label,
+            base,
+            exponent,
+            modulus,
+            r: Uint::ZERO,
+            result: [0; N],
+        }
+    }
+
+    /// The result of the last run as little-endian words.
+    pub fn result(&self) -> &[u32; N] {
+        &self.result
+    }
+
+    fn load(&mut self) {
+        if let Some(rsa) = self.rsa.take() {
+            let m_prime;
+            (self.r, m_prime) = rsa_montgomery_params(&self.modulus);
+            self.mod_exp = Some(RsaModularExponentiation::new(
+                rsa,
+                self.exponent.as_words(),
+                self.modulus.as_words(),
+                m_prime,
+            ));
+        }
+    }
+
+    fn report_as(&self, iterations: usize, time_us: f64) -> BenchmarkResult<'a> {
+        BenchmarkResult {
+            algorithm: self.label,
+            data_size: Uint::<N>::BYTES,
+            iterations,
+            time_us,
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Benchmark<'a> for RsaBenchmark<'a, '_, T, Blocking, N>
+where
+    T: RsaMode<InputType = [u32; N]>,
+{
+    fn setup(&mut self) {
+        self.load();
+    }
+
+    async fn run(&mut self) -> Result<(), BenchError> {
+        let mod_exp = self.mod_exp.as_mut().ok_or(BenchError::NotSetUp)?;
+        mod_exp.start_exponentiation(self.base.as_words(), self.r.as_words());
+        mod_exp.read_results(&mut self.result);
+        Ok(())
+    }
+
+    fn report(&self, iterations: usize, time_us: f64) -> BenchmarkResult<'a> {
+        self.report_as(iterations, time_us)
+    }
+}
+
+impl<'a, T, const N: usize> Benchmark<'a> for RsaBenchmark<'a, '_, T, Async, N>
+where
+    T: RsaMode<InputType = [u32; N]>,
+{
+    fn setup(&mut self) {
+        self.load();
+    }
+
+    async fn run(&mut self) -> Result<(), BenchError> {
+        let mod_exp = self.mod_exp.as_mut().ok_or(BenchError::NotSetUp)?;
+        mod_exp
+            .exponentiation(self.base.as_words(), self.r.as_words(), &mut self.result)
+            .await;
+        Ok(())
+    }
+
+    fn report(&self, iterations: usize, time_us: f64) -> BenchmarkResult<'a> {
+        self.report_as(iterations, time_us)
+    }
+}
+
+/// Check the RSA-2048 exponentiation of the benchmarks,
+/// [`run_rsa_benchmark`] with 2048-bit operands and the Montgomery
+/// parameters of the modulus, on 4^13 mod 497 = 445.
+pub fn verify_rsa_modexp(rsa: &mut Rsa<'_, Blocking>) -> bool {
+    let mut result = [0_u32; U2048::LIMBS];
+    if let Err(error) = run_rsa_benchmark::<Op2048, { U2048::LIMBS }>(
+        rsa,
+        "RSA-2048 known answer",
+        &U2048::from_u32(4),
+        &U2048::from_u32(13),
+        &U2048::from_u32(497),
+        &mut result,
+    ) {
+        failed(error);
+        return false;
+    }
+    known_answer(
+        "RSA-2048 modexp known-answer test (4^13 mod 497 = 445)",
+        &U2048::from_u32(445).to_be_bytes(),
+        &U2048::from_words(result).to_be_bytes(),
+    )
+}
+
+// Dummy values for RSA modular exponentiation
+// These values are not secure and should not be used in production.
+// They are only for benchmarking purposes.
+// The values are sized for the largest operand the ESP32-C6 RSA accelerator
+// supports (3072 bits); smaller operand sizes use the low-order words, so the
+// 2048-bit benchmark uses the low 2048 bits of each value.
+pub const RSA_BASE: U3072 = Uint::from_be_hex(
+    "c7f61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b71\
+    9d8ca980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878\
+    c7f61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b71\
+    9d8ca980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878\
+    c7f61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b71\
+    9d8ca980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878c\
+    7f61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b719d\
+    8ca980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878c7f\
+    61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b719d8c\
+    a980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878c7f61\
+    058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b719d8ca9\
+    80b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878",
+);
+pub const RSA_EXPONENT: U3072 = Uint::from_be_hex(
+    "1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
+    fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
+    1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
+    fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
+    1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
+    fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
+    1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
+    fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
+    1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
+    fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
+    1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
+    fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51",
+);
+pub const RSA_MODULUS: U3072 = Uint::from_be_hex(
+    "6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
+    df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
+    6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
+    df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
+    6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
+    df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
+    6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
+    df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
+    6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
+    df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
+    6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
+    df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361",
+);
+
+/// Public exponent used for the RSA verify comparison (F4).
+pub const RSA_PUBLIC_EXPONENT: u32 = 65537;
+
+/// Run the RSA-2048 exponentiations and return the time of the one with the
+/// dummy exponent. With `soft-compare`, that one is repeated in software.
+pub fn benchmark_rsa(
+    rsa: &mut Rsa<'_, Blocking>,
+    #[cfg(feature = "verify")] sha: &mut Sha,
+) -> Result<Duration, BenchError> {
+    let (elapsed, _result) =
+        benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(rsa, "RSA-2048", &RSA_EXPONENT.resize())?;
+    #[cfg(feature = "verify")]
+    log_result_digest(sha, "RSA-2048", &_result);
+    #[cfg(feature = "soft-compare")]
+    compare_rsa_modexp(elapsed, &_result);
+
+    // Private-key operation: a full-size exponent with every bit set is the
+    // worst case for square-and-multiply, so this is an upper bound on the
+    // signing latency.
+    let (private, _result) = benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(
+        rsa,
+        "RSA-2048 (private exponent)",
+        &U2048::MAX,
+    )?;
+    #[cfg(feature = "verify")]
+    log_result_digest(sha, "RSA-2048 (private exponent)", &_result);
+
+    // Public-key operation: with search acceleration the engine skips the
+    // leading zero bits of the exponent, as a verify implementation would.
+    rsa.enable_disable_search_acceleration(true);
+    let public = benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(
+        rsa,
+        "RSA-2048 (e = 65537)",
+        &U2048::from_u32(RSA_PUBLIC_EXPONENT),
+    );
+    rsa.enable_disable_search_acceleration(false);
+    let (public, _result) = public?;
+    #[cfg(feature = "verify")]
+    log_result_digest(sha, "RSA-2048 (e = 65537)", &_result);
+
+    info!(
+        "RSA-2048 private exponent / public exponent: {:.1}x",
+        private.as_micros() as f64 / public.as_micros().max(1) as f64
+    );
+
+    Ok(elapsed)
+}
+
+/// Number of timed repetitions of each `Uint` word conversion.
+pub const RSA_CONVERSION_ITERATIONS: usize = 1000;
+
+/// Benchmark the conversions between `Uint` operands and the `[u32; N]` words
+/// the RSA driver takes and returns, for each operand size the engine
+/// supports, and compare them with the `exponentiation` time of RSA-2048.
+///
+/// One exponentiation of [`benchmark_rsa`] resizes the base, exponent and
+/// modulus from the 3072-bit constants, copies out four operands (base,
+/// exponent, modulus and `r`) and converts the result back.
+pub fn benchmark_rsa_word_conversions(exponentiation: Duration) {
+    benchmark_rsa_conversions::<{ U1024::LIMBS }>("RSA-1024");
+    let per_operation = benchmark_rsa_conversions::<{ U2048::LIMBS }>("RSA-2048");
+    benchmark_rsa_conversions::<{ U3072::LIMBS }>("RSA-3072");
+
+    info!(
+        "RSA-2048 word conversions per exponentiation: {} (4 to words, 1 from words, 3 resizes), Share: {:.4}% of {}",
+        Elapsed(per_operation),
+        per_operation / exponentiation.as_micros() as f64 * 100.0,
+        Elapsed::from_micros(exponentiation.as_micros())
+    );
+}
+
+/// Time each conversion of an `N`-word operand over
+/// [`RSA_CONVERSION_ITERATIONS`] repetitions and return the conversion time
+/// of one exponentiation in microseconds.
+///
+/// It also times preparing the operand from a byte string, big-endian as in
+/// key files and protocols and little-endian as in the driver's word order,
+/// to show the cost of the big-endian marshaling.
+fn benchmark_rsa_conversions<const N: usize>(label: &str) -> f64 {
+    use core::hint::black_box;
+
+    let per_iteration = |convert: &mut dyn FnMut()| {
+        let start_time = Instant::now();
+        for _ in 0..RSA_CONVERSION_ITERATIONS {
+            convert();
+        }
+        start_time.elapsed().as_micros() as f64 / RSA_CONVERSION_ITERATIONS as f64
+    };
+
+    let value: Uint<N> = RSA_MODULUS.resize();
+    let words = *value.as_words();
+    let to_words = per_iteration(&mut || {
+        black_box(*black_box(&value).as_words());
+    });
+    let from_words = per_iteration(&mut || {
+        black_box(Uint::<N>::from_words(black_box(words)));
+    });
+    let resize = per_iteration(&mut || {
+        black_box(black_box(&RSA_MODULUS).resize::<N>());
+    });
+
+    info!(
+        "{label} word conversions, To words: {}, From words: {}, Resize: {}",
+        Elapsed(to_words),
+        Elapsed(from_words),
+        Elapsed(resize)
+    );
+
+    // The same operand as it arrives over the wire, most significant byte
+    // first, and as little-endian bytes, which match the driver's word order.
+    let mut be_bytes = [0_u8; U3072::BYTES];
+    let mut le_bytes = [0_u8; U3072::BYTES];
+    let be_bytes = &mut be_bytes[..Uint::<N>::BYTES];
+    let le_bytes = &mut le_bytes[..Uint::<N>::BYTES];
+    for (i, word) in words.iter().enumerate() {
+        le_bytes[i * 4..][..4].copy_from_slice(&word.to_le_bytes());
+    }
+    for (be, le) in be_bytes.iter_mut().zip(le_bytes.iter().rev()) {
+        *be = *le;
+    }
+    let from_be = per_iteration(&mut || {
+        black_box(Uint::<N>::from_be_slice(black_box(&*be_bytes)));
+    });
+    let from_le = per_iteration(&mut || {
+        black_box(Uint::<N>::from_le_slice(black_box(&*le_bytes)));
+    });
+    info!(
+        "{label} operand loading, From big-endian bytes: {}, From little-endian bytes: {}, Big-endian cost: {:+}",
+        Elapsed(from_be),
+        Elapsed(from_le),
+        Elapsed(from_be - from_le)
+    );
+    4.0 * to_words + from_words + 3.0 * resize
+}
+
+/// Exponent lengths in bits of the RSA-2048 exponent size sweep.
+pub const RSA_EXPONENT_BITS: [u32; 4] = [16, 256, 1024, 2048];
+
+/// Run the RSA-2048 exponentiation with exponents of each of
+/// [`RSA_EXPONENT_BITS`] bits and log the time per exponent bit.
+///
+/// Each exponent is the low bits of [`RSA_EXPONENT`] with its top bit set, so
+/// it has exactly the requested length and a Hamming weight of about half its
+/// bits. Search acceleration is enabled so the engine skips the leading zero
+/// bits, as it does for the public exponent.
+pub fn benchmark_rsa_exponent_sizes(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    rsa.enable_disable_search_acceleration(true);
+    let sweep = rsa_exponent_sweep(rsa);
+    rsa.enable_disable_search_acceleration(false);
+    sweep
+}
+
+/// The exponentiations of [`benchmark_rsa_exponent_sizes`], run with search
+/// acceleration enabled.
+fn rsa_exponent_sweep(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    for bits in RSA_EXPONENT_BITS {
+        let exponent = RSA_EXPONENT
+            .resize::<{ U2048::LIMBS }>()
+            .bitand(&U2048::MAX.shr_vartime(U2048::BITS - bits))
+            .bitor(&U2048::ONE.shl_vartime(bits - 1));
+        let weight: u32 = exponent.as_words().iter().map(|w| w.count_ones()).sum();
+
+        let label = format!("RSA-2048 ({bits}-bit exponent)");
+        let (elapsed, _) =
+            benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(rsa, &label, &exponent)?;
+        info!(
+            "RSA-2048 exponent sweep, Bits: {bits}, Weight: {weight}, Time: {}, Per bit: {}",
+            Elapsed::from_micros(elapsed.as_micros()),
+            Elapsed(elapsed.as_micros() as f64 / bits as f64)
+        );
+    }
+    Ok(())
+}
+
+/// Run the dummy-exponent RSA-2048 benchmark with interrupts enabled and again
+/// with them masked inside a critical section, reporting the difference caused
+/// by interrupt handling.
+#[cfg(feature = "interrupt-compare")]
+pub fn benchmark_rsa_interrupts_masked(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    let exponent: U2048 = RSA_EXPONENT.resize();
+    let (enabled, _) =
+        benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(rsa, "RSA-2048", &exponent)?;
+    let (masked, _) = critical_section::with(|_| {
+        benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(
+            rsa,
+            "RSA-2048 (interrupts masked)",
+            &exponent,
+        )
+    })?;
+    info!(
+        "RSA-2048 (interrupts masked), Time: {}, Enabled: {}, Change: {:+.1}%",
+        Elapsed::from_micros(masked.as_micros()),
+        Elapsed::from_micros(enabled.as_micros()),
+        (masked.as_micros() as f64 - enabled.as_micros() as f64) / enabled.as_micros() as f64
+            * 100.0
+    );
+    Ok(())
+}
+
+/// Benchmark one modular exponentiation with the operand size `T`.
+///
+/// `N` is the number of 32-bit words per operand and must match `T`, e.g.
+/// `benchmark_rsa_modexp::<Op1024, { U1024::LIMBS }>(rsa, "RSA-1024", &exponent)`.
+/// The modulus is the low `N` words of [`RSA_MODULUS`] and the base the low
+/// `N` words of [`RSA_BASE`] reduced modulo it. Returns the time taken by the
+/// exponentiation and the result `base^exponent mod modulus` as little-endian
+/// words.
+pub fn benchmark_rsa_modexp<T, const N: usize>(
+    rsa: &mut Rsa<'_, Blocking>,
+    label: &str,
+    exponent: &Uint<N>,
+) -> Result<(Duration, [u32; N]), BenchError>
+where
+    T: RsaMode<InputType = [u32; N]>,
+{
+    let modulus: Uint<N> = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<N>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+
+    let mut outbuf = [0_u32; N];
+    let result = run_rsa_benchmark::<T, N>(rsa, label, &base, exponent, &modulus, &mut outbuf)?;
+    let elapsed = Duration::from_micros(result.time_us as u64);
+
+    #[cfg(feature = "raw-timings")]
+    raw_timings::emit_frame(label, Uint::<N>::BYTES, &[elapsed.as_micros() as u32]);
+
+    info!(
+        "{label} Modular Exponentiation completed in {}",
+        Elapsed::from_micros(elapsed.as_micros())
+    );
+    #[cfg(feature = "thresholds")]
+    thresholds::check_duration(label, Uint::<N>::BYTES, elapsed.as_millis());
+    #[cfg(feature = "datasheet-power")]
+    datasheet_power::log_operation(
+        label,
+        Uint::<N>::BYTES,
+        elapsed.as_micros() as f64,
+        datasheet_power::RSA_POWER_MW,
+    );
+    log_result(&result);
+    #[cfg(feature = "hex-dump")]
+    {
+        // The most significant bytes, as in the big-endian encoding.
+        let mut head = [0_u8; 16];
+        for (bytes, word) in head
+            .as_chunks_mut::<4>()
+            .0
+            .iter_mut()
+            .zip(outbuf.iter().rev())
+        {
+            *bytes = word.to_be_bytes();
+        }
+        log_hex(label, Uint::<N>::BYTES, &head[..Uint::<N>::BYTES.min(16)]);
+    }
+
+    Ok((elapsed, outbuf))
+}
+
+/// Exponentiations timed per operand size in the RSA floor benchmark.
+pub const RSA_FLOOR_ITERATIONS: usize = 100;
+
+/// Mean time in microseconds of [`RSA_FLOOR_ITERATIONS`] exponentiations
+/// with the small public `exponent` at the operand size `T` of `N` words,
+/// with the modulus and base taken from the low words of [`RSA_MODULUS`] and
+/// [`RSA_BASE`]. The operands are loaded once, outside the timing.
+fn rsa_public_exponentiation_us<T, const N: usize>(
+    rsa: &mut Rsa<'_, Blocking>,
+    exponent: u32,
+) -> f64
+where
+    T: RsaMode<InputType = [u32; N]>,
+{
+    let modulus: Uint<N> = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<N>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let exponent = Uint::<N>::from_u32(exponent);
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+    let mut outbuf = [0_u32; N];
+
+    let mut mod_exp = RsaModularExponentiation::<T, _>::new(
+        rsa,
+        exponent.as_words(),
+        modulus.as_words(),
+        m_prime,
+    );
+    let start_time = Instant::now();
+    for _ in 0..RSA_FLOOR_ITERATIONS {
+        mod_exp.start_exponentiation(base.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+    }
+    start_time.elapsed().as_micros() as f64 / RSA_FLOOR_ITERATIONS as f64
+}
+
+/// Find the floor of the RSA latency: an exponentiation with the public
+/// exponent at the smallest operand size the HAL supports, `Op32`, against
+/// 512, 1024 and 2048 bits with the same exponent.
+///
+/// With search acceleration every size runs the same 17 squarings and one
+/// multiplication, so the `Op32` time is close to the fixed cost of starting
+/// the engine and reading the result, and the growth above it comes from
+/// the modulus size.
+pub fn benchmark_rsa_floor(rsa: &mut Rsa<'_, Blocking>) {
+    rsa.enable_disable_search_acceleration(true);
+    let sizes = [
+        (
+            32,
+            rsa_public_exponentiation_us::<Op32, 1>(rsa, RSA_PUBLIC_EXPONENT),
+        ),
+        (
+            512,
+            rsa_public_exponentiation_us::<Op512, { U512::LIMBS }>(rsa, RSA_PUBLIC_EXPONENT),
+        ),
+        (
+            1024,
+            rsa_public_exponentiation_us::<Op1024, { U1024::LIMBS }>(rsa, RSA_PUBLIC_EXPONENT),
+        ),
+        (
+            2048,
+            rsa_public_exponentiation_us::<Op2048, { U2048::LIMBS }>(rsa, RSA_PUBLIC_EXPONENT),
+        ),
+    ];
+    rsa.enable_disable_search_acceleration(false);
+
+    let floor_us = sizes[0].1;
+    for (bits, time_us) in sizes {
+        info!(
+            "RSA-{bits} floor (e = {RSA_PUBLIC_EXPONENT}), Iterations: {RSA_FLOOR_ITERATIONS}, Time: {}, Fixed share: {:.1}%",
+            Elapsed(time_us),
+            floor_us / time_us * 100.0
+        );
+    }
+}
+
+/// Public exponents compared by the RSA public exponent benchmark, the
+/// largest last.
+pub const RSA_VERIFY_EXPONENTS: [u32; 3] = [3, 17, RSA_PUBLIC_EXPONENT];
+
+/// Compare RSA-2048 verification with each of [`RSA_VERIFY_EXPONENTS`] over
+/// the fixed modulus, [`RSA_FLOOR_ITERATIONS`] exponentiations each with
+/// search acceleration, and log each time with its speedup over 65537.
+///
+/// Square-and-multiply runs one squaring per exponent bit after the first and
+/// one multiplication per further set bit, so e = 3 needs 2 operations,
+/// e = 17 needs 5 and e = 65537 needs 17.
+pub fn benchmark_rsa_public_exponents(rsa: &mut Rsa<'_, Blocking>) {
+    rsa.enable_disable_search_acceleration(true);
+    let times = RSA_VERIFY_EXPONENTS
+        .map(|e| rsa_public_exponentiation_us::<Op2048, { U2048::LIMBS }>(rsa, e));
+    rsa.enable_disable_search_acceleration(false);
+
+    let largest_us = times[times.len() - 1];
+    for (e, time_us) in RSA_VERIFY_EXPONENTS.into_iter().zip(times) {
+        info!(
+            "RSA-2048 verify, e = {e}, Iterations: {RSA_FLOOR_ITERATIONS}, Time: {}, Speedup vs e = {RSA_PUBLIC_EXPONENT}: {:.2}x",
+            Elapsed(time_us),
+            largest_us / time_us
+        );
+    }
+}
+
+/// Verifications timed by the RSA byte marshaling benchmark, with and
+/// without the conversions.
+pub const RSA_MARSHAL_ITERATIONS: u32 = 100;
+
+/// Time an RSA-2048 verification from big-endian signature bytes to
+/// big-endian message bytes, the way a protocol receives and sends the
+/// operands: `Uint::from_be_slice`, `as_words`, the exponentiation with
+/// [`RSA_PUBLIC_EXPONENT`], `read_results` and `to_be_bytes`. The same loop
+/// on operands already held as words gives the hardware share, and the
+/// difference is the cost of the conversions.
+pub fn benchmark_rsa_marshaling(rsa: &mut Rsa<'_, Blocking>) {
+    let modulus: U2048 = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let signature = base.to_be_bytes();
+    let exponent = U2048::from_u32(RSA_PUBLIC_EXPONENT);
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+    let mut outbuf = [0_u32; U2048::LIMBS];
+
+    rsa.enable_disable_search_acceleration(true);
+    let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+        rsa,
+        exponent.as_words(),
+        modulus.as_words(),
+        m_prime,
+    );
+
+    let start_time = Instant::now();
+    for _ in 0..RSA_MARSHAL_ITERATIONS {
+        let input = U2048::from_be_slice(core::hint::black_box(&signature));
+        mod_exp.start_exponentiation(input.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+        core::hint::black_box(U2048::from_words(outbuf).to_be_bytes());
+    }
+    let total_us = start_time.elapsed().as_micros() as f64 / RSA_MARSHAL_ITERATIONS as f64;
+
+    let start_time = Instant::now();
+    for _ in 0..RSA_MARSHAL_ITERATIONS {
+        mod_exp.start_exponentiation(base.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+    }
+    let words_us = start_time.elapsed().as_micros() as f64 / RSA_MARSHAL_ITERATIONS as f64;
+    rsa.enable_disable_search_acceleration(false);
+
+    let conversion_us = (total_us - words_us).max(0.0);
+    info!(
+        "RSA-2048 verify with byte I/O, Iterations: {RSA_MARSHAL_ITERATIONS}, Total: {}, Exponentiation: {}, Conversion: {} ({:.1}% of total)",
+        Elapsed(total_us),
+        Elapsed(words_us),
+        Elapsed(conversion_us),
+        100.0 * conversion_us / total_us
+    );
+}
+
+/// Number of distinct moduli in the RSA key setup benchmark.
+pub const RSA_KEY_COUNT: usize = 8;
+
+/// Time switching the RSA accelerator between [`RSA_KEY_COUNT`] different
+/// 2048-bit moduli, as a device serving several keys would.
+///
+/// Each key's setup computes its Montgomery parameters in software and loads
+/// the modulus, exponent and `m_prime` into the accelerator by creating a new
+/// [`RsaModularExponentiation`]; the exponentiation with the dummy exponent is
+/// timed separately. The moduli are [`RSA_MODULUS`] plus small even offsets,
+/// so they stay odd and every key has its own `r` and `m_prime`.
+pub fn benchmark_rsa_key_setup(rsa: &mut Rsa<'_, Blocking>) {
+    let exponent: U2048 = RSA_EXPONENT.resize();
+    let mut params_us = 0;
+    let mut load_us = 0;
+    let mut exponentiation_us = 0;
+    let mut outbuf = [0_u32; U2048::LIMBS];
+
+    for key in 0..RSA_KEY_COUNT {
+        let modulus = RSA_MODULUS
+            .resize::<{ U2048::LIMBS }>()
+            .wrapping_add(&U2048::from_u32(2 * key as u32));
+        let base = RSA_BASE
+            .resize::<{ U2048::LIMBS }>()
+            .rem_vartime(&NonZero::new(modulus).unwrap());
+
+        let ((r, m_prime), params_time) = timed(|| rsa_montgomery_params(&modulus));
+        let (mut mod_exp, load_time) = timed(|| {
+            RsaModularExponentiation::<Op2048, _>::new(
+                rsa,
+                exponent.as_words(),
+                modulus.as_words(),
+                m_prime,
+            )
+        });
+        let ((), exponentiation_time) = timed(|| {
+            mod_exp.start_exponentiation(base.as_words(), r.as_words());
+            mod_exp.read_results(&mut outbuf);
+        });
+
+        params_us += params_time.as_micros();
+        load_us += load_time.as_micros();
+        exponentiation_us += exponentiation_time.as_micros();
+    }
+
+    let per_key = |total_us: u64| Elapsed(total_us as f64 / RSA_KEY_COUNT as f64);
+    let setup_us = params_us + load_us;
+    info!(
+        "RSA-2048 Key Setup, Keys: {RSA_KEY_COUNT}, Setup: {} (params {}, load {}), Exponentiation: {}, Setup share: {:.1}%",
+        per_key(setup_us),
+        per_key(params_us),
+        per_key(load_us),
+        per_key(exponentiation_us),
+        setup_us as f64 / (setup_us + exponentiation_us).max(1) as f64 * 100.0
+    );
+}
+
+/// Test RSA-2048 key for the CRT benchmark, generated for this benchmark
+/// only and not secret: the primes p > q, the private exponent d for
+/// e = 65537, the CRT exponents dP = d mod (p - 1) and dQ = d mod (q - 1),
+/// and qInv = q^-1 mod p. The modulus is p * q.
+pub const RSA_CRT_P: U1024 = Uint::from_be_hex(
+    "df0b2b8bde095dc833ceb74c9af969bb3493ea43498f17f9e05e76541a2430b2\
+    b0f694fb5158f5722a47019dcbd44d04f63df73ff4b5a27190a9cbc6333fe05d\
+    bcf2c77c435b6fce73b4376419b7cd1424444e2cdb1dba51c8c9ae8823d9c603\
+    0bc4b9a2abb5ceaaf2f74d7bc474b0630dff9f823e0baf098d9ce07a10ebaf9b",
+);
+pub const RSA_CRT_Q: U1024 = Uint::from_be_hex(
+    "daaf4b9808b73d4358c3b1feca53662126493733050c05e71dae87da6b5a01b6\
+    ef4c09e71886ed09477b593b423c63b3b5b773242bd9f02be43d0873eeebbd6f\
+    e20a0f131c51c9379615bd32d4c3520acb3887352ee052fa594ca0b3546983be\
+    987dfa843facd580100160ce6aa271d4461fc06df9eea97694d69199f08462b1",
+);
+pub const RSA_CRT_D: U2048 = Uint::from_be_hex(
+    "bb34118d5a0791ca4ae4166d1fd141350305f05b373ae003e794bac391949204\
+    467d927134f0d7547006da305d0e5c4078e48948d60aa418b70658f6a61adb2a\
+    c1a499e860c3044edf7e1300a1d02b9059e5f0ad5779ef814c53a26f3e90e771\
+    06145dcc02ec4e2745838e6c7450bbb23c493384d000709bafc30246fc12a29a\
+    49649b242890fad7ebdffc6515f6753bb05955b0ea5d74dbaf142eb547ce2e0e\
+    8486bb683c6e6eed5c872e3a5b92212b93539cfdfe4b3f450b2eca0c5b03ba3d\
+    30cb512eaad0b06ad645c38153d777e92577209c375dc8f951cdde3cb5581137\
+    453db9ed979b72cfe563334444605c31b444518bbf552910345f0443e89dff01",
+);
+pub const RSA_CRT_DP: U1024 = Uint::from_be_hex(
+    "c56c64b21be9dc79ede98673a72527424e0ab32bb5fb93b458b62434d322bf7c\
+    1b89575b90a89630e45d0f8035c0082061397e15bd2bdcca7acae094e21e2321\
+    b5b28b82613899217b66c434b552ec926c7b8d5ac4e46e5c1fc6ecd6922d1bc8\
+    ec6cc807c5c75a54145573df1e884eec8ea279f4d5a88fd97be0a50d46d6e70b",
+);
+pub const RSA_CRT_DQ: U1024 = Uint::from_be_hex(
+    "6c17bd9bd6a30c4b1fb30b664f87cdb191051b9dc25ee1040e2dd8c4e3cf478d\
+    aed6bd06579f49c70e683001148a5915d93a665578bf6ef1a30d70022e14a43c\
+    8b54418346ba3e6595700566fcb7d6f3b2e3ac21639c4a27a8c031a6b290bd28\
+    16a41e92040f6030beb88fab381637819f6fce640de824279306444dfd0ed2e1",
+);
+pub const RSA_CRT_QINV: U1024 = Uint::from_be_hex(
+    "c81e147a1f6a517d7d4fa3e07faf64a8c089532992d5fc98641bb833c89742eb\
+    a32c12dab2115a245130aef612f39fd70f4322288600ef8f664c287bba8ff206\
+    879d9d96fe5ae4617a2e5e5d8ce0ab78c7df02314f353224fa1d09fb5582b80b\
+    fd479c8899f25b575372e23c6b716452afc063c74b26280dd70e517bc7b5a36c",
+);
+
+/// Compare the RSA-2048 private-key operation using the Chinese Remainder
+/// Theorem with the same operation over the full modulus, for the test key
+/// [`RSA_CRT_P`].
+///
+/// All steps except computing the Montgomery parameters, which a signer
+/// caches per key, are timed; the full-modulus path excludes them too. Both
+/// results are compared to check the recombination.
+pub fn benchmark_rsa_crt(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    let modulus: U2048 = RSA_CRT_P.widening_mul(&RSA_CRT_Q);
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+
+    let mut full = [0_u32; U2048::LIMBS];
+    let full_result = run_rsa_benchmark::<Op2048, { U2048::LIMBS }>(
+        rsa,
+        "RSA-2048 (full modulus)",
+        &base,
+        &RSA_CRT_D,
+        &modulus,
+        &mut full,
+    )?;
+
+    let (crt, times) = rsa_crt_private(rsa, &base)?;
+    info!(
+        "RSA-2048 CRT, Reduce: {}, Mod p: {}, Mod q: {}, Recombine: {}, Total: {}, Full modulus: {}, Speedup: {:.1}x, Results match: {}",
+        Elapsed(times.reduce_us),
+        Elapsed(times.mod_p_us),
+        Elapsed(times.mod_q_us),
+        Elapsed(times.recombine_us),
+        Elapsed(times.total_us()),
+        Elapsed(full_result.time_us),
+        full_result.time_us / times.total_us(),
+        if *crt.as_words() == full { "yes" } else { "no" }
+    );
+    log_result(&full_result);
+    log_result(&BenchmarkResult {
+        algorithm: "RSA-2048 (CRT)",
+        time_us: times.total_us(),
+        ..full_result
+    });
+    Ok(())
+}
+
+/// Time of each step of [`rsa_crt_private`] in microseconds.
+struct CrtTimes {
+    reduce_us: f64,
+    mod_p_us: f64,
+    mod_q_us: f64,
+    recombine_us: f64,
+}
+
+impl CrtTimes {
+    fn total_us(&self) -> f64 {
+        self.reduce_us + self.mod_p_us + self.mod_q_us + self.recombine_us
+    }
+}
+
+/// The private-key operation `base^d mod n` of the test key [`RSA_CRT_P`]
+/// using the Chinese Remainder Theorem, with the time of each step.
+///
+/// Reduces `base` modulo p and q in software, runs one 1024-bit
+/// exponentiation with dP and dQ each on the engine and recombines the
+/// halves with Garner's formula `m2 + q * (qInv * (m1 - m2) mod p)` in
+/// software, which is how signing libraries implement the private
+/// operation. `base` has to be smaller than the modulus p * q.
+fn rsa_crt_private(
+    rsa: &mut Rsa<'_, Blocking>,
+    base: &U2048,
+) -> Result<(U2048, CrtTimes), BenchError> {
+    let p = NonZero::new(RSA_CRT_P).unwrap();
+
+    let ((base_p, base_q), reduce_time) = timed(|| {
+        let halves = base.split();
+        (
+            U1024::rem_wide_vartime(halves, &p),
+            U1024::rem_wide_vartime(halves, &NonZero::new(RSA_CRT_Q).unwrap()),
+        )
+    });
+
+    let mut m1 = [0_u32; U1024::LIMBS];
+    let mod_p = run_rsa_benchmark::<Op1024, { U1024::LIMBS }>(
+        rsa,
+        "RSA-1024 (mod p)",
+        &base_p,
+        &RSA_CRT_DP,
+        &RSA_CRT_P,
+        &mut m1,
+    )?;
+    let mut m2 = [0_u32; U1024::LIMBS];
+    let mod_q = run_rsa_benchmark::<Op1024, { U1024::LIMBS }>(
+        rsa,
+        "RSA-1024 (mod q)",
+        &base_q,
+        &RSA_CRT_DQ,
+        &RSA_CRT_Q,
+        &mut m2,
+    )?;
+
+    let (result, recombine_time) = timed(|| {
+        let m1 = U1024::from_words(m1);
+        let m2 = U1024::from_words(m2);
+        // m2 < q < p, so both halves are already reduced modulo p.
+        let h = RSA_CRT_QINV.mul_mod_vartime(&m1.sub_mod(&m2, &RSA_CRT_P), &p);
+        let hq: U2048 = h.widening_mul(&RSA_CRT_Q);
+        hq.wrapping_add(&m2.resize())
+    });
+
+    let times = CrtTimes {
+        reduce_us: reduce_time.as_micros() as f64,
+        mod_p_us: mod_p.time_us,
+        mod_q_us: mod_q.time_us,
+        recombine_us: recombine_time.as_micros() as f64,
+    };
+    Ok((result, times))
+}
+
+/// Signatures per batch in the RSA batch verification benchmark.
+pub const RSA_BATCH_SIZE: usize = 16;
+
+/// Verify a batch of [`RSA_BATCH_SIZE`] RSA-2048 signatures under the CRT
+/// test key one after the other, as a boot loader checking several signed
+/// components would, and log the total and per-signature time.
+///
+/// The signatures are made with `rsa_crt_private` beforehand. The public
+/// key is loaded once for the batch, timed as `Setup`; each verification
+/// is then one exponentiation with e = 65537 and search acceleration, and
+/// its result is compared with the signed value.
+pub fn benchmark_rsa_batch_verify(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    let modulus: U2048 = RSA_CRT_P.widening_mul(&RSA_CRT_Q);
+    let first = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let messages: [U2048; RSA_BATCH_SIZE] =
+        core::array::from_fn(|i| first.wrapping_add(&U2048::from_u32(i as u32)));
+    let mut signatures = [U2048::ZERO; RSA_BATCH_SIZE];
+    for (signature, message) in signatures.iter_mut().zip(&messages) {
+        *signature = rsa_crt_private(rsa, message)?.0;
+    }
+
+    let exponent = U2048::from_u32(RSA_PUBLIC_EXPONENT);
+    let mut outbuf = [0_u32; U2048::LIMBS];
+    let mut valid = 0;
+    rsa.enable_disable_search_acceleration(true);
+    let ((r, mut mod_exp), setup_time) = timed(|| {
+        let (r, m_prime) = rsa_montgomery_params(&modulus);
+        let mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+            rsa,
+            exponent.as_words(),
+            modulus.as_words(),
+            m_prime,
+        );
+        (r, mod_exp)
+    });
+    let ((), batch_time) = timed(|| {
+        for (signature, message) in signatures.iter().zip(&messages) {
+            mod_exp.start_exponentiation(signature.as_words(), r.as_words());
+            mod_exp.read_results(&mut outbuf);
+            if outbuf == *message.as_words() {
+                valid += 1;
+            }
+        }
+    });
+    rsa.enable_disable_search_acceleration(false);
+
+    let total_us = (setup_time + batch_time).as_micros() as f64;
+    info!(
+        "RSA-2048 batch verify (e = 65537), Signatures: {RSA_BATCH_SIZE}, Setup: {}, Total: {}, Per signature: {}, Verifications/s: {:.1}, Valid: {valid}/{RSA_BATCH_SIZE}",
+        Elapsed::from_micros(setup_time.as_micros()),
+        Elapsed(total_us),
+        Elapsed(batch_time.as_micros() as f64 / RSA_BATCH_SIZE as f64),
+        RSA_BATCH_SIZE as f64 / total_us * 1_000_000.0
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "RSA-2048 (batch verify)",
+        data_size: U2048::BYTES,
+        iterations: RSA_BATCH_SIZE,
+        time_us: total_us / RSA_BATCH_SIZE as f64,
+    });
+    Ok(())
+}
+
+/// Untimed verifications the RSA steady-state benchmark starts with.
+pub const RSA_STEADY_WARMUP: usize = 20;
+
+/// Timed verifications of the RSA steady-state benchmark.
+pub const RSA_STEADY_ITERATIONS: usize = 200;
+
+/// Slowest verifications left out of the trimmed mean, 5%.
+pub const RSA_STEADY_TRIM: usize = RSA_STEADY_ITERATIONS / 20;
+
+/// Find the sustained RSA-2048 verification rate: after
+/// [`RSA_STEADY_WARMUP`] verifications, time [`RSA_STEADY_ITERATIONS`] more
+/// one by one and log the mean and the mean without the
+/// [`RSA_STEADY_TRIM`] slowest, each with its verifications per second.
+///
+/// Each verification is an exponentiation of a signature of the CRT test
+/// key with e = 65537 and search acceleration, with the public key loaded
+/// once as a server would keep it.
+pub fn benchmark_rsa_steady_verify(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    let modulus: U2048 = RSA_CRT_P.widening_mul(&RSA_CRT_Q);
+    let message = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let (signature, _) = rsa_crt_private(rsa, &message)?;
+    let exponent = U2048::from_u32(RSA_PUBLIC_EXPONENT);
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+    let mut outbuf = [0_u32; U2048::LIMBS];
+
+    rsa.enable_disable_search_acceleration(true);
+    let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+        rsa,
+        exponent.as_words(),
+        modulus.as_words(),
+        m_prime,
+    );
+    for _ in 0..RSA_STEADY_WARMUP {
+        mod_exp.start_exponentiation(signature.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+    }
+    let mut samples = Samples::<RSA_STEADY_ITERATIONS>::new();
+    let mut valid = 0;
+    for _ in 0..RSA_STEADY_ITERATIONS {
+        let start_time = Instant::now();
+        mod_exp.start_exponentiation(signature.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+        samples.push(start_time.elapsed().as_micros());
+        if outbuf == *message.as_words() {
+            valid += 1;
+        }
+    }
+    rsa.enable_disable_search_acceleration(false);
+
+    let mut times = [0_u32; RSA_STEADY_ITERATIONS];
+    times.copy_from_slice(samples.as_slice());
+    let mean_us = Summary::of(times.iter().map(|&us| us as f64)).mean;
+    let trimmed_us = trimmed_mean(&mut times, RSA_STEADY_TRIM).unwrap_or(mean_us);
+    info!(
+        "RSA-2048 steady-state verify (e = {RSA_PUBLIC_EXPONENT}), Warmup: {RSA_STEADY_WARMUP}, Iterations: {RSA_STEADY_ITERATIONS}, Mean: {} ({:.1}/s), Trimmed mean (slowest {RSA_STEADY_TRIM} dropped): {} ({:.1}/s), Valid: {valid}/{RSA_STEADY_ITERATIONS}",
+        Elapsed(mean_us),
+        1_000_000.0 / mean_us,
+        Elapsed(trimmed_us),
+        1_000_000.0 / trimmed_us
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "RSA-2048 (steady-state verify)",
+        data_size: U2048::BYTES,
+        iterations: RSA_STEADY_ITERATIONS - RSA_STEADY_TRIM,
+        time_us: trimmed_us,
+    });
+    Ok(())
+}
+
+/// Compare the RSA-2048 private-key operation of the CRT test key over the
+/// full modulus n with and without base blinding, as used against timing
+/// attacks: `base * b^e mod n` is exponentiated with d and the result
+/// multiplied by `b^-1 mod n`.
+///
+/// A fixed blinding value b stands in for the random one a signer draws;
+/// the timing does not depend on it. Computing `b^e` and `b^-1` once is
+/// logged as `Setup`. Each operation then costs the blind and unblind
+/// multiplications plus squaring both factors to refresh them for the next
+/// one, all on the engine; these three steps make up the overhead. The
+/// unblinded result is compared with the plain one.
+pub fn benchmark_rsa_blinding(rsa: &mut Rsa<'_, Blocking>) -> Result<(), BenchError> {
+    let modulus: U2048 = RSA_CRT_P.widening_mul(&RSA_CRT_Q);
+    let n = NonZero::new(modulus).unwrap();
+    let base = RSA_BASE.resize::<{ U2048::LIMBS }>().rem_vartime(&n);
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+
+    let mut plain = [0_u32; U2048::LIMBS];
+    let plain_result = run_rsa_benchmark::<Op2048, { U2048::LIMBS }>(
+        rsa,
+        "RSA-2048 (unblinded)",
+        &base,
+        &RSA_CRT_D,
+        &modulus,
+        &mut plain,
+    )?;
+
+    let blinding = U2048::MAX.rem_vartime(&n);
+    let ((blind_factor, unblind_factor), setup_time) = timed(|| {
+        let mut blind_factor = [0_u32; U2048::LIMBS];
+        let exponent = U2048::from_u32(RSA_PUBLIC_EXPONENT);
+        let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+            rsa,
+            exponent.as_words(),
+            modulus.as_words(),
+            m_prime,
+        );
+        mod_exp.start_exponentiation(blinding.as_words(), r.as_words());
+        mod_exp.read_results(&mut blind_factor);
+        let inverse = blinding.inv_odd_mod(&Odd::new(modulus).unwrap()).unwrap();
+        (U2048::from_words(blind_factor), inverse)
+    });
+
+    let (blinded, blind_time) =
+        timed(|| rsa_mod_mul(rsa, &base, &blind_factor, &modulus, &r, m_prime));
+    let mut signed = [0_u32; U2048::LIMBS];
+    let exponentiation = run_rsa_benchmark::<Op2048, { U2048::LIMBS }>(
+        rsa,
+        "RSA-2048 (blinded)",
+        &blinded,
+        &RSA_CRT_D,
+        &modulus,
+        &mut signed,
+    )?;
+    let (result, unblind_time) = timed(|| {
+        let signed = U2048::from_words(signed);
+        rsa_mod_mul(rsa, &signed, &unblind_factor, &modulus, &r, m_prime)
+    });
+    let ((), refresh_time) = timed(|| {
+        rsa_mod_mul(rsa, &blind_factor, &blind_factor, &modulus, &r, m_prime);
+        rsa_mod_mul(rsa, &unblind_factor, &unblind_factor, &modulus, &r, m_prime);
+    });
+
+    let overhead_us = (blind_time + unblind_time + refresh_time).as_micros() as f64;
+    let total_us = exponentiation.time_us + overhead_us;
+    info!(
+        "RSA-2048 blinded exponentiation, Setup: {}, Blind: {}, Exponentiate: {}, Unblind: {}, Refresh: {}, Total: {}, Unblinded: {}, Overhead: {:.2}%, Results match: {}",
+        Elapsed::from_micros(setup_time.as_micros()),
+        Elapsed::from_micros(blind_time.as_micros()),
+        Elapsed(exponentiation.time_us),
+        Elapsed::from_micros(unblind_time.as_micros()),
+        Elapsed::from_micros(refresh_time.as_micros()),
+        Elapsed(total_us),
+        Elapsed(plain_result.time_us),
+        overhead_us / plain_result.time_us * 100.0,
+        if *result.as_words() == plain { "yes" } else { "no" }
+    );
+    log_result(&plain_result);
+    log_result(&BenchmarkResult {
+        time_us: total_us,
+        ..exponentiation
+    });
+    Ok(())
+}
+
+/// `a * b mod modulus` for RSA-2048 operands on the engine, with the
+/// Montgomery parameters `r` and `m_prime` of the modulus.
+fn rsa_mod_mul(
+    rsa: &mut Rsa<'_, Blocking>,
+    a: &U2048,
+    b: &U2048,
+    modulus: &U2048,
+    r: &U2048,
+    m_prime: u32,
+) -> U2048 {
+    let mut outbuf = [0_u32; U2048::LIMBS];
+    let mut mod_mul = RsaModularMultiplication::<Op2048, _>::new(
+        rsa,
+        a.as_words(),
+        modulus.as_words(),
+        r.as_words(),
+        m_prime,
+    );
+    mod_mul.start_modular_multiplication(b.as_words());
+    mod_mul.read_results(&mut outbuf);
+    U2048::from_words(outbuf)
+}
+
+/// Message sizes of the hash-then-sign benchmark.
+#[cfg(feature = "bench-sha")]
+pub const SIGN_MESSAGE_SIZES: [usize; 3] = [64, 1024, 16 * 1024];
+
+/// DER encoding of the SHA-256 `DigestInfo` prefix of an EMSA-PKCS1-v1_5
+/// encoded message (RFC 8017, section 9.2).
+#[cfg(feature = "bench-sha")]
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The EMSA-PKCS1-v1_5 encoding of a SHA-256 `digest` for an RSA-2048 key,
+/// `00 01 FF .. FF 00 || DigestInfo || digest`.
+#[cfg(feature = "bench-sha")]
+fn emsa_pkcs1_sha256(digest: &[u8; 32]) -> U2048 {
+    let mut encoded = [0xFF_u8; U2048::BYTES];
+    let prefix_start = U2048::BYTES - digest.len() - SHA256_DIGEST_INFO.len();
+    encoded[0] = 0x00;
+    encoded[1] = 0x01;
+    encoded[prefix_start - 1] = 0x00;
+    encoded[prefix_start..U2048::BYTES - digest.len()].copy_from_slice(&SHA256_DIGEST_INFO);
+    encoded[U2048::BYTES - digest.len()..].copy_from_slice(digest);
+    U2048::from_be_slice(&encoded)
+}
+
+/// Benchmark a complete RSASSA-PKCS1-v1_5 signature with SHA-256 for each of
+/// [`SIGN_MESSAGE_SIZES`]: hashing the message on the SHA engine, encoding
+/// the digest as `00 01 FF .. FF 00 || DigestInfo || digest` and the CRT
+/// private-key operation of the test key, and log the end-to-end latency
+/// with its breakdown.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_hash_then_sign(
+    rsa: &mut Rsa<'_, Blocking>,
+    sha: &mut Sha,
+) -> Result<(), BenchError> {
+    const MAX_SIZE: usize = SIGN_MESSAGE_SIZES[SIGN_MESSAGE_SIZES.len() - 1];
+    let message = [0xAB_u8; MAX_SIZE];
+    let mut digest = [0_u8; 32];
+
+    for size in SIGN_MESSAGE_SIZES {
+        let hash_time = benchmark_single_sha256(sha, &message[..size], &mut digest)?;
+
+        let (encoded, pad_time) = timed(|| emsa_pkcs1_sha256(&digest));
+        let (_signature, times) = rsa_crt_private(rsa, &encoded)?;
+
+        let hash_us = hash_time.as_micros() as f64;
+        let sign_us = pad_time.as_micros() as f64 + times.total_us();
+        let total_us = hash_us + sign_us;
+        info!(
+            "RSA-2048 hash-then-sign (SHA-256, CRT), Message: {size}, Hash: {}, Sign: {} (padding {}), Total: {}, Hash share: {:.2}%, Signatures/s: {:.1}",
+            Elapsed(hash_us),
+            Elapsed(sign_us),
+            Elapsed::from_micros(pad_time.as_micros()),
+            Elapsed(total_us),
+            hash_us / total_us * 100.0,
+            1_000_000.0 / total_us
+        );
+        log_result(&BenchmarkResult {
+            algorithm: "RSA-2048 sign (SHA-256)",
+            data_size: size,
+            iterations: 1,
+            time_us: total_us,
+        });
+    }
+    Ok(())
+}
+
+/// Transcript sizes hashed by the hash and sign overlap benchmark.
+#[cfg(feature = "bench-sha")]
+pub const OVERLAP_HASH_SIZES: [usize; 3] = [600, 4096, 16 * 1024];
+
+/// Signatures timed per size and schedule by the hash and sign overlap
+/// benchmark; each is a full-modulus private-key exponentiation.
+#[cfg(feature = "bench-sha")]
+pub const OVERLAP_ITERATIONS: usize = 4;
+
+/// Measure whether a SHA-256 transcript hash can hide behind an RSA-2048
+/// signature, as in a TLS handshake: for each of [`OVERLAP_HASH_SIZES`],
+/// [`OVERLAP_ITERATIONS`] times hash then sign one after the other, then
+/// start the private-key exponentiation of the CRT test key over the full
+/// modulus, hash while the RSA engine runs and read the signature.
+///
+/// The CPU drives the SHA engine while the RSA engine computes on its own,
+/// so an overlap saves up to the whole hash time. Both schedules must give
+/// the same digest and signature.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_hash_sign_overlap(
+    rsa: &mut Rsa<'_, Blocking>,
+    sha: &mut Sha,
+) -> Result<(), BenchError> {
+    const MAX_SIZE: usize = OVERLAP_HASH_SIZES[OVERLAP_HASH_SIZES.len() - 1];
+    let transcript = [0xAB_u8; MAX_SIZE];
+    let modulus: U2048 = RSA_CRT_P.widening_mul(&RSA_CRT_Q);
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+    let mut sequential = ([0_u8; 32], [0_u32; U2048::LIMBS]);
+    let mut overlapped = ([0_u8; 32], [0_u32; U2048::LIMBS]);
+
+    let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+        rsa,
+        RSA_CRT_D.as_words(),
+        modulus.as_words(),
+        m_prime,
+    );
+
+    for size in OVERLAP_HASH_SIZES {
+        let transcript = &transcript[..size];
+        set_current_benchmark("SHA-256 + RSA-2048 overlap", Some(size));
+
+        let mut hash_us = 0.0;
+        let start_time = Instant::now();
+        for _ in 0..OVERLAP_ITERATIONS {
+            let hash_time = benchmark_single_sha256(sha, transcript, &mut sequential.0)?;
+            hash_us += hash_time.as_micros() as f64;
+            mod_exp.start_exponentiation(base.as_words(), r.as_words());
+            mod_exp.read_results(&mut sequential.1);
+        }
+        let sequential_us = start_time.elapsed().as_micros() as f64 / OVERLAP_ITERATIONS as f64;
+        hash_us /= OVERLAP_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..OVERLAP_ITERATIONS {
+            mod_exp.start_exponentiation(base.as_words(), r.as_words());
+            benchmark_single_sha256(sha, transcript, &mut overlapped.0)?;
+            mod_exp.read_results(&mut overlapped.1);
+        }
+        let overlapped_us = start_time.elapsed().as_micros() as f64 / OVERLAP_ITERATIONS as f64;
+
+        let saved_us = sequential_us - overlapped_us;
+        info!(
+            "SHA-256 + RSA-2048 sign overlap, Transcript: {size}, Hash: {}, Sequential: {}, Overlapped: {}, Saved: {} ({:.0}% of the hash), Results match: {}",
+            Elapsed(hash_us),
+            Elapsed(sequential_us),
+            Elapsed(overlapped_us),
+            Elapsed(saved_us),
+            saved_us / hash_us * 100.0,
+            if overlapped == sequential { "yes" } else { "no" }
+        );
+    }
+    Ok(())
+}
+
+/// Size of the firmware image checked by the signed firmware verification
+/// benchmark.
+#[cfg(feature = "bench-sha")]
+pub const FIRMWARE_IMAGE_SIZE: usize = 512 * 1024;
+
+/// Verify a signed [`FIRMWARE_IMAGE_SIZE`] firmware image the way a secure
+/// boot or OTA step does: hash the image in 32 KB chunks, raise the
+/// RSASSA-PKCS1-v1_5 signature to the public exponent 65537 of the test key
+/// [`RSA_CRT_P`] with search acceleration, and compare the result with the
+/// encoded digest. Logs the total with the hash and verify breakdown.
+///
+/// The signature is made beforehand with `rsa_crt_private`, and the
+/// Montgomery parameters of the public key are computed outside the timing,
+/// as a verifier would keep them with the key.
+#[cfg(feature = "bench-sha")]
+pub fn benchmark_firmware_verify(
+    rsa: &mut Rsa<'_, Blocking>,
+    sha: &mut Sha,
+) -> Result<(), BenchError> {
+    const CHUNK_SIZE: usize = 32 * 1024;
+    let chunk = [0xAB_u8; CHUNK_SIZE];
+    let hash_image = |sha: &mut Sha, digest: &mut [u8; 32]| {
+        let mut hasher = sha.start::<Sha256>();
+        for _ in 0..FIRMWARE_IMAGE_SIZE / CHUNK_SIZE {
+            sha256_update(&mut hasher, &chunk)?;
+        }
+        sha256_finish(&mut hasher, digest)
+    };
+
+    let modulus: U2048 = RSA_CRT_P.widening_mul(&RSA_CRT_Q);
+    let mut digest = [0_u8; 32];
+    hash_image(sha, &mut digest)?;
+    let (signature, _) = rsa_crt_private(rsa, &emsa_pkcs1_sha256(&digest))?;
+    let exponent = U2048::from_u32(RSA_PUBLIC_EXPONENT);
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+
+    let (result, hash_time) = timed(|| hash_image(sha, &mut digest));
+    result?;
+
+    rsa.enable_disable_search_acceleration(true);
+    let mut outbuf = [0_u32; U2048::LIMBS];
+    let (valid, verify_time) = timed(|| {
+        let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+            rsa,
+            exponent.as_words(),
+            modulus.as_words(),
+            m_prime,
+        );
+        mod_exp.start_exponentiation(signature.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+        U2048::from_words(outbuf) == emsa_pkcs1_sha256(&digest)
+    });
+    rsa.enable_disable_search_acceleration(false);
+
+    let hash_us = hash_time.as_micros() as f64;
+    let verify_us = verify_time.as_micros() as f64;
+    let total_us = hash_us + verify_us;
+    if !valid {
+        error!("Signed firmware verification: signature does not match the image digest");
+    }
+    info!(
+        "Signed firmware verification (SHA-256, RSA-2048, e = {RSA_PUBLIC_EXPONENT}), Image: {FIRMWARE_IMAGE_SIZE}, Hash: {}, Verify: {}, Total: {}, Hash share: {:.1}%, Signature: {}",
+        Elapsed(hash_us),
+        Elapsed(verify_us),
+        Elapsed(total_us),
+        hash_us / total_us * 100.0,
+        if valid { "valid" } else { "INVALID" }
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "RSA-2048 verify (SHA-256 firmware image)",
+        data_size: FIRMWARE_IMAGE_SIZE,
+        iterations: 1,
+        time_us: total_us,
+    });
+    Ok(())
+}
+
+/// Number of timed exponentiations of the RSA stability run.
+pub const RSA_STABILITY_ITERATIONS: usize = 200;
+
+/// Repeat the dummy-exponent RSA-2048 exponentiation
+/// [`RSA_STABILITY_ITERATIONS`] times with the same operands and log the
+/// distribution of the per-exponentiation times.
+///
+/// The operands are loaded once, so the spread is that of the exponentiation
+/// alone, not of the setup.
+pub fn benchmark_rsa_stability(rsa: &mut Rsa<'_, Blocking>) {
+    let exponent: U2048 = RSA_EXPONENT.resize();
+    let modulus: U2048 = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let (r, m_prime) = rsa_montgomery_params(&modulus);
+
+    let mut outbuf = [0_u32; U2048::LIMBS];
+    let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
+        rsa,
+        exponent.as_words(),
+        modulus.as_words(),
+        m_prime,
+    );
+
+    let mut samples = Samples::<RSA_STABILITY_ITERATIONS>::new();
+    for _ in 0..RSA_STABILITY_ITERATIONS {
+        if aborted() {
+            break;
+        }
+        let start_time = Instant::now();
+        mod_exp.start_exponentiation(base.as_words(), r.as_words());
+        mod_exp.read_results(&mut outbuf);
+        samples.push(start_time.elapsed().as_micros());
+    }
+
+    #[cfg(feature = "raw-timings")]
+    raw_timings::emit_frame("RSA-2048", U2048::BYTES, samples.as_slice());
+    #[cfg(feature = "histogram")]
+    log_histogram("RSA-2048", U2048::BYTES, samples.as_slice());
+
+    let times = samples.as_slice();
+    let summary = Summary::of(times.iter().map(|&us| us as f64));
+    let min = times.iter().copied().min().unwrap_or(0);
+    let max = times.iter().copied().max().unwrap_or(0);
+    info!(
+        "RSA-2048 stability, Iterations: {}, Mean: {} ± {} (95% CI), StdDev: {}, Min: {}, Max: {}",
+        summary.count,
+        Elapsed(summary.mean),
+        Elapsed(summary.margin_95),
+        Elapsed(summary.std_dev),
+        Elapsed::from_micros(min.into()),
+        Elapsed::from_micros(max.into())
+    );
+}
+
+/// Log the SHA-256 digest of an RSA `result` (little-endian words), hashed as
+/// the big-endian byte encoding of the integer, e.g. Python's
+/// `hashlib.sha256(pow(b, e, m).to_bytes(256, "big"))` for 2048 bits.
+#[cfg(feature = "verify")]
+fn log_result_digest(sha: &mut Sha, label: &str, result: &[u32]) {
+    let bytes: Vec<u8> = result.iter().rev().flat_map(|w| w.to_be_bytes()).collect();
+    let mut digest = [0_u8; 32];
+    match benchmark_single_sha256(sha, &bytes, &mut digest) {
+        Ok(_) => info!("{label} result SHA-256: {}", Hex(&digest)),
+        Err(error) => failed(error),
+    }
+}
+
+/// Time the first RSA-2048 exponentiation of the run, with the public
+/// exponent 65537 to keep the warm repetitions short, against the warm ones
+/// after it, and return the time of the first in microseconds. Only the
+/// exponentiations are timed, not loading the key.
+pub fn cold_start_rsa(rsa: &mut Rsa<'_, Blocking>) -> Result<u64, BenchError> {
+    let modulus: U2048 = RSA_MODULUS.resize();
+    let base = RSA_BASE
+        .resize::<{ U2048::LIMBS }>()
+        .rem_vartime(&NonZero::new(modulus).unwrap());
+    let exponent = U2048::from_u32(RSA_PUBLIC_EXPONENT);
+    let mut outbuf = [0_u32; U2048::LIMBS];
+
+    rsa.enable_disable_search_acceleration(true);
+    let mut exponentiate = || {
+        run_rsa_benchmark::<Op2048, { U2048::LIMBS }>(
+            rsa,
+            "RSA-2048 (e = 65537)",
+            &base,
+            &exponent,
+            &modulus,
+            &mut outbuf,
+        )
+        .map(|result| result.time_us)
+    };
+    let times = exponentiate().and_then(|cold_us| {
+        let warm_us = (0..COLD_START_WARM_ITERATIONS)
+            .map(|_| exponentiate())
+            .sum::<Result<f64, BenchError>>()?;
+        Ok((cold_us as u64, warm_us / COLD_START_WARM_ITERATIONS as f64))
+    });
+    rsa.enable_disable_search_acceleration(false);
+    let (cold_us, warm_us) = times?;
+
+    log_cold_start("RSA-2048 (e = 65537)", U2048::BYTES, cold_us, warm_us);
+    Ok(cold_us)
+}
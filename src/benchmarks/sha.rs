@@ -0,0 +1,1317 @@
+//! SHA-256 benchmarks (`bench-sha`).
+//!
+//! [`run_sha256_benchmark`] measures one data size through the
+//! [`Sha256Benchmark`] adapter and logs nothing; the other benchmarks borrow
+//! the driver and log their figures as they go. [`sha256_update`] and
+//! [`sha256_finish`] feed the engine with a bounded wait and are also used by
+//! the AES and RSA benchmarks that hash.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use esp_hal::peripherals;
+use esp_hal::sha::{Context, Sha, Sha256, ShaDigest};
+use esp_hal::time::{Duration, Instant};
+use log::{error, info};
+
+#[cfg(feature = "hex-dump")]
+use super::log_hex;
+use super::{
+    aborted, hex_bytes, known_answer, log_cold_start, log_result, log_wake, set_current_benchmark,
+    timed, timestamp_overhead, TimeDistribution, BLOB_SIZE, COLD_START_SIZE,
+    COLD_START_WARM_ITERATIONS, WAKE_ITERATIONS, WAKE_SIZE,
+};
+#[cfg(feature = "datasheet-power")]
+use crate::datasheet_power;
+use crate::error::{BenchError, BusyWait};
+use crate::harness::{self, yield_now, Benchmark};
+#[cfg(feature = "raw-timings")]
+use crate::raw_timings;
+use crate::report::BenchmarkResult;
+use crate::stats::{BenchStats, Running, Samples, Summary};
+#[cfg(feature = "thresholds")]
+use crate::thresholds;
+use crate::units::{Delta, Elapsed, Throughput};
+
+/// Hash `data` with SHA-256 `iterations` times and return the mean time per
+/// digest, labelled `SHA-256`.
+pub fn run_sha256_benchmark(
+    sha: &mut Sha<'_>,
+    data: &[u8],
+    iterations: usize,
+) -> Result<BenchmarkResult<'static>, BenchError> {
+    harness::measure_blocking(&mut Sha256Benchmark::new(sha, data), iterations)
+}
+
+/// Hash exactly `data`, e.g. a protocol capture embedded with
+/// `include_bytes!`, with SHA-256 `iterations` times, and return the mean
+/// time per digest together with the spread of the per-digest times in
+/// microseconds, labelled `SHA-256`.
+pub fn run_sha256_benchmark_on(
+    sha: &mut Sha<'_>,
+    data: &[u8],
+    iterations: usize,
+) -> Result<(BenchmarkResult<'static>, Summary), BenchError> {
+    let mut output = [0_u8; 32];
+    let mut digests = Running::new();
+    let mut total_us = 0;
+
+    for _ in 0..iterations {
+        let start_time = Instant::now();
+        let mut digest = sha.start::<Sha256>();
+        sha256_update(&mut digest, data)?;
+        sha256_finish(&mut digest, &mut output)?;
+        let elapsed_us = start_time.elapsed().as_micros();
+        digests.push(elapsed_us as f64);
+        total_us += elapsed_us;
+    }
+
+    let result = BenchmarkResult {
+        algorithm: "SHA-256",
+        data_size: data.len(),
+        iterations,
+        time_us: total_us as f64 / iterations.max(1) as f64,
+    };
+    Ok((result, digests.summary()))
+}
+
+/// Feed all of `input` into `digest`.
+///
+/// `ShaDigest::update` only consumes data up to the end of the current 64-byte
+/// block and returns the rest, or `WouldBlock` while the engine is busy, so it
+/// has to be called until nothing remains. Fails with
+/// [`BenchError::DigestUpdate`] if the engine stays busy for
+/// [`ENGINE_TIMEOUT`](crate::error::ENGINE_TIMEOUT).
+pub fn sha256_update<'d, S: Borrow<Sha<'d>>>(
+    digest: &mut ShaDigest<'d, Sha256, S>,
+    mut input: &[u8],
+) -> Result<(), BenchError> {
+    let mut busy = BusyWait::default();
+    while !input.is_empty() {
+        match digest.update(input) {
+            Ok(remaining) => {
+                input = remaining;
+                busy = BusyWait::default();
+            }
+            Err(_) => {
+                if busy.expired() {
+                    return Err(BenchError::DigestUpdate);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finish `digest` and write the hash to `output`.
+pub fn sha256_finish<'d, S: Borrow<Sha<'d>>>(
+    digest: &mut ShaDigest<'d, Sha256, S>,
+    output: &mut [u8],
+) -> Result<(), BenchError> {
+    digest.finish(output).map_err(|_| BenchError::DigestFinish)
+}
+
+/// Save the state of `digest` to `context`.
+///
+/// `ShaDigest::save` returns `WouldBlock` while the engine is still busy with
+/// an update. Fails with [`BenchError::DigestUpdate`] if it stays busy for
+/// [`ENGINE_TIMEOUT`](crate::error::ENGINE_TIMEOUT).
+pub fn sha256_save<'d, S: Borrow<Sha<'d>>>(
+    digest: &mut ShaDigest<'d, Sha256, S>,
+    context: &mut Context<Sha256>,
+) -> Result<(), BenchError> {
+    let mut busy = BusyWait::default();
+    while digest.save(context).is_err() {
+        if busy.expired() {
+            return Err(BenchError::DigestUpdate);
+        }
+    }
+    Ok(())
+}
+
+/// SHA-256 digests of `data`.
+pub struct Sha256Benchmark<'a, 'd> {
+    sha: &'a mut Sha<'d>,
+    data: &'a [u8],
+    output: [u8; 32],
+}
+
+impl<'a, 'd> Sha256Benchmark<'a, 'd> {
+    /// A benchmark hashing `data` on `sha`.
+    pub fn new(sha: &'a mut Sha<'d>, data: &'a [u8]) -> Self {
+        Self {
+            sha,
+            data,
+            output: [0; 32],
+        }
+    }
+
+    /// The digest of the last run.
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.output
+    }
+}
+
+impl<'l> Benchmark<'l> for Sha256Benchmark<'_, '_> {
+    fn setup(&mut self) {}
+
+    async fn run(&mut self) -> Result<(), BenchError> {
+        let mut digest = self.sha.start::<Sha256>();
+        let mut input = self.data;
+        let mut busy = BusyWait::default();
+        while !input.is_empty() {
+            match digest.update(input) {
+                Ok(remaining) => {
+                    input = remaining;
+                    busy = BusyWait::default();
+                }
+                Err(_) => {
+                    if busy.expired() {
+                        return Err(BenchError::DigestUpdate);
+                    }
+                    yield_now().await;
+                }
+            }
+        }
+        while digest.finish(&mut self.output).is_err() {
+            if busy.expired() {
+                return Err(BenchError::DigestFinish);
+            }
+            yield_now().await;
+        }
+        Ok(())
+    }
+
+    fn report(&self, iterations: usize, time_us: f64) -> BenchmarkResult<'l> {
+        BenchmarkResult {
+            algorithm: "SHA-256",
+            data_size: self.data.len(),
+            iterations,
+            time_us,
+        }
+    }
+}
+
+/// Check [`benchmark_single_sha256`] against the one-block "abc" example of
+/// FIPS 180-2, the standard SHA-256 test vector.
+pub fn verify_sha256(sha: &mut Sha) -> Result<bool, BenchError> {
+    const DIGEST: [u8; 32] =
+        hex_bytes("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+    let mut digest = [0_u8; 32];
+    benchmark_single_sha256(sha, b"abc", &mut digest)?;
+    Ok(known_answer(
+        "SHA-256 known-answer test (FIPS 180-2 \"abc\")",
+        &DIGEST,
+        &digest,
+    ))
+}
+
+/// Number of timed SHA-256 digests per data size of the sweep, fewer for a
+/// `quick` run.
+pub const SHA_ITERATIONS: usize = if cfg!(feature = "quick") { 10 } else { 100 };
+
+/// Hash `input` [`SHA_ITERATIONS`] times with [`benchmark_single_sha256`],
+/// leaving the digest in `output`, and return the elapsed time of all
+/// iterations together with the distribution of the per-iteration times less
+/// the [`timestamp_overhead`].
+///
+/// With `raw-timings` the samples are emitted in the order they were taken
+/// before they are sorted for the statistics.
+fn benchmark_sha256_iterations(
+    sha: &mut Sha,
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<(Duration, BenchStats), BenchError> {
+    let overhead_us = timestamp_overhead().as_micros();
+    let mut samples = Samples::<SHA_ITERATIONS>::new();
+    let start_time = Instant::now();
+    for _ in 0..SHA_ITERATIONS {
+        let elapsed = benchmark_single_sha256(sha, input, output)?;
+        samples.push(elapsed.as_micros().saturating_sub(overhead_us));
+    }
+    let elapsed = start_time.elapsed();
+    #[cfg(feature = "raw-timings")]
+    raw_timings::emit_frame("SHA-256", input.len(), samples.as_slice());
+    let times = BenchStats::of(samples.as_mut_slice()).ok_or(BenchError::NoSamples)?;
+    Ok((elapsed, times))
+}
+
+pub fn benchmark_sha256(sha: &mut Sha, data_sizes: &[usize]) -> Result<Vec<f64>, BenchError> {
+    let mut input = [0_u8; 32 * 1024]; // Maximum buffer size for SHA-256
+    input.fill(0xAB); // Fill with a pattern for testing
+    let mut output = [0_u8; 32]; // SHA-256 produces a 32-byte digest
+    let mut throughputs = Vec::with_capacity(data_sizes.len());
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    for &size in data_sizes {
+        if aborted() {
+            break;
+        }
+        set_current_benchmark("SHA-256", Some(size));
+        let (elapsed, times) = benchmark_sha256_iterations(sha, &input[..size], &mut output)?;
+        // As for AES-CTR, the throughput is that of the elapsed time of all
+        // iterations, timestamps included. Bytes per microsecond is MB/s.
+        let time_us = elapsed.as_micros() as f64 / SHA_ITERATIONS as f64;
+        let throughput = size as f64 / time_us.max(1.0);
+        throughputs.push(throughput);
+        info!(
+            "SHA-256, DataSize: {size}, Throughput: {} (elapsed), {}",
+            Throughput(throughput),
+            TimeDistribution(&times)
+        );
+        #[cfg(feature = "thresholds")]
+        thresholds::check_throughput("SHA-256", size, throughput);
+        #[cfg(feature = "datasheet-power")]
+        datasheet_power::log_throughput("SHA-256", size, throughput, datasheet_power::SHA_POWER_MW);
+        log_result(&BenchmarkResult {
+            algorithm: "SHA-256",
+            data_size: size,
+            iterations: SHA_ITERATIONS,
+            time_us,
+        });
+        #[cfg(feature = "hex-dump")]
+        log_hex("SHA-256", size, &output);
+    }
+
+    Ok(throughputs)
+}
+
+/// Hashes timed per data size by the BLAKE3 benchmark; software hashing of
+/// small inputs is too short to time once.
+#[cfg(feature = "soft-blake3")]
+pub const BLAKE3_ITERATIONS: usize = 10;
+
+/// Hash each of `data_sizes` [`BLAKE3_ITERATIONS`] times with the software
+/// BLAKE3 of the `blake3` crate, which has no hardware support, and log its
+/// throughput next to `sha_throughputs`, the hardware SHA-256 sweep at the
+/// same sizes in bytes per microsecond.
+#[cfg(feature = "soft-blake3")]
+pub fn benchmark_blake3(data_sizes: &[usize], sha_throughputs: &[f64]) {
+    let input = [0xAB_u8; 32 * 1024];
+
+    // Pre-warm the code and data caches
+    core::hint::black_box(blake3::hash(&input));
+
+    for (i, &size) in data_sizes.iter().enumerate() {
+        if aborted() {
+            break;
+        }
+        set_current_benchmark("BLAKE3 (software)", Some(size));
+        let ((), elapsed) = timed(|| {
+            for _ in 0..BLAKE3_ITERATIONS {
+                core::hint::black_box(blake3::hash(core::hint::black_box(&input[..size])));
+            }
+        });
+        let time_us = elapsed.as_micros() as f64 / BLAKE3_ITERATIONS as f64;
+        let throughput = size as f64 / time_us.max(f64::MIN_POSITIVE);
+        match sha_throughputs.get(i) {
+            Some(&sha) => info!(
+                "BLAKE3 (software), DataSize: {size}, Time: {}, Throughput: {}, SHA-256 (hardware): {}, Ratio: {:.2}x",
+                Elapsed(time_us),
+                Throughput(throughput),
+                Throughput(sha),
+                throughput / sha
+            ),
+            None => info!(
+                "BLAKE3 (software), DataSize: {size}, Time: {}, Throughput: {}",
+                Elapsed(time_us),
+                Throughput(throughput)
+            ),
+        }
+        log_result(&BenchmarkResult {
+            algorithm: "BLAKE3 (software)",
+            data_size: size,
+            iterations: BLAKE3_ITERATIONS,
+            time_us,
+        });
+    }
+}
+
+/// Benchmark SHA-256 with interrupts enabled and again with them masked
+/// inside a critical section, reporting the difference caused by interrupt
+/// handling.
+#[cfg(feature = "interrupt-compare")]
+pub fn benchmark_sha256_interrupts_masked(
+    sha: &mut Sha,
+    data_sizes: &[usize],
+) -> Result<(), BenchError> {
+    let input = [0xAB_u8; 32 * 1024];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    for &size in data_sizes {
+        let enabled = benchmark_single_sha256(sha, &input[..size], &mut output)?;
+        let masked =
+            critical_section::with(|_| benchmark_single_sha256(sha, &input[..size], &mut output))?;
+        info!(
+            "SHA-256 (interrupts masked), DataSize: {size}, Time: {}, Enabled: {}, Change: {:+.1}%",
+            Elapsed::from_micros(masked.as_micros()),
+            Elapsed::from_micros(enabled.as_micros()),
+            (masked.as_micros() as f64 - enabled.as_micros() as f64) / enabled.as_micros() as f64
+                * 100.0
+        );
+    }
+    Ok(())
+}
+
+/// Size of each message in the many-small-messages SHA-256 benchmark.
+pub const SMALL_MESSAGE_SIZE: usize = 64;
+
+/// Number of messages in the many-small-messages SHA-256 benchmark.
+pub const SMALL_MESSAGE_COUNT: usize = 100;
+
+/// Compare hashing [`SMALL_MESSAGE_COUNT`] separate messages of
+/// [`SMALL_MESSAGE_SIZE`] bytes against hashing the same bytes as a single
+/// message, which shows the per-message start and finalization overhead.
+pub fn benchmark_sha256_many_small(sha: &mut Sha) -> Result<(), BenchError> {
+    const TOTAL_SIZE: usize = SMALL_MESSAGE_SIZE * SMALL_MESSAGE_COUNT;
+    let input = [0xAB_u8; TOTAL_SIZE];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    let start_time = Instant::now();
+    for message in input.chunks(SMALL_MESSAGE_SIZE) {
+        let mut digest = sha.start::<Sha256>();
+        sha256_update(&mut digest, message)?;
+        sha256_finish(&mut digest, &mut output)?;
+    }
+    let many = start_time.elapsed();
+
+    let single = benchmark_single_sha256(sha, &input, &mut output)?;
+
+    info!(
+        "SHA-256 ({SMALL_MESSAGE_COUNT} x {SMALL_MESSAGE_SIZE} byte messages), Time: {}, Per message: {}, Throughput: {}",
+        Elapsed::from_micros(many.as_micros()),
+        Elapsed(many.as_micros() as f64 / SMALL_MESSAGE_COUNT as f64),
+        Throughput::of(TOTAL_SIZE, many.as_micros() as f64)
+    );
+    info!(
+        "SHA-256 (1 x {TOTAL_SIZE} byte message), Time: {}, Throughput: {}",
+        Elapsed::from_micros(single.as_micros()),
+        Throughput::of(TOTAL_SIZE, single.as_micros() as f64)
+    );
+    info!(
+        "SHA-256 many small messages / one message: {:.2}x",
+        many.as_micros() as f64 / single.as_micros().max(1) as f64
+    );
+    Ok(())
+}
+
+/// Message size of the SHA-256 update size benchmark.
+pub const UPDATE_SIZE_MESSAGE: usize = 16 * 1024;
+
+/// Probe how many bytes one `ShaDigest::update` call accepts, then hash
+/// [`UPDATE_SIZE_MESSAGE`] bytes in updates of exactly that size, as one
+/// slice and in 1 KB chunks, and compare the throughput.
+///
+/// The driver consumes input only up to the end of the current 64-byte
+/// block and hands back the rest, so larger inputs take several calls
+/// whatever size they are passed in.
+pub fn benchmark_sha256_update_size(sha: &mut Sha) -> Result<(), BenchError> {
+    let input = [0xAB_u8; UPDATE_SIZE_MESSAGE];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    let mut digest = sha.start::<Sha256>();
+    let mut busy = BusyWait::default();
+    let remaining = loop {
+        match digest.update(&input) {
+            Ok(remaining) => break remaining.len(),
+            Err(_) if busy.expired() => return Err(BenchError::DigestUpdate),
+            Err(_) => {}
+        }
+    };
+    sha256_finish(&mut digest, &mut output)?;
+    let max_update = input.len() - remaining;
+    info!(
+        "SHA-256 maximum bytes per update: {max_update} (of {} offered)",
+        input.len()
+    );
+
+    let mut hash_in_chunks = |chunk_size: usize| {
+        let start_time = Instant::now();
+        let mut digest = sha.start::<Sha256>();
+        for chunk in input.chunks(chunk_size) {
+            sha256_update(&mut digest, chunk)?;
+        }
+        sha256_finish(&mut digest, &mut output)?;
+        Ok(start_time.elapsed())
+    };
+    for (label, chunk_size) in [
+        ("updates of the maximum size", max_update.max(1)),
+        ("one slice", input.len()),
+        ("1 KB chunks", 1024),
+    ] {
+        let elapsed = hash_in_chunks(chunk_size)?;
+        info!(
+            "SHA-256 ({label}), DataSize: {UPDATE_SIZE_MESSAGE}, Time: {}, Throughput: {}",
+            Elapsed::from_micros(elapsed.as_micros()),
+            Throughput::of(UPDATE_SIZE_MESSAGE, elapsed.as_micros() as f64)
+        );
+    }
+    Ok(())
+}
+
+/// Message sizes around the SHA-256 padding boundaries, with the reference
+/// digest of that many `a` bytes, i.e. Python's
+/// `hashlib.sha256(b"a" * size).digest()`.
+///
+/// Padding appends at least 9 bytes (`0x80` and the 64-bit length), so up
+/// to 55 bytes fit one block, 56 to 64 take two and 65 starts a block more
+/// for the data itself.
+pub const PADDING_VECTORS: [(usize, [u8; 32]); 5] = [
+    (
+        55,
+        [
+            0x9f, 0x43, 0x90, 0xf8, 0xd3, 0x0c, 0x2d, 0xd9, 0x2e, 0xc9, 0xf0, 0x95, 0xb6, 0x5e,
+            0x2b, 0x9a, 0xe9, 0xb0, 0xa9, 0x25, 0xa5, 0x25, 0x8e, 0x24, 0x1c, 0x9f, 0x1e, 0x91,
+            0x0f, 0x73, 0x43, 0x18,
+        ],
+    ),
+    (
+        56,
+        [
+            0xb3, 0x54, 0x39, 0xa4, 0xac, 0x6f, 0x09, 0x48, 0xb6, 0xd6, 0xf9, 0xe3, 0xc6, 0xaf,
+            0x0f, 0x5f, 0x59, 0x0c, 0xe2, 0x0f, 0x1b, 0xde, 0x70, 0x90, 0xef, 0x79, 0x70, 0x68,
+            0x6e, 0xc6, 0x73, 0x8a,
+        ],
+    ),
+    (
+        63,
+        [
+            0x7d, 0x3e, 0x74, 0xa0, 0x5d, 0x7d, 0xb1, 0x5b, 0xce, 0x4a, 0xd9, 0xec, 0x06, 0x58,
+            0xea, 0x98, 0xe3, 0xf0, 0x6e, 0xee, 0xcf, 0x16, 0xb4, 0xc6, 0xff, 0xf2, 0xda, 0x45,
+            0x7d, 0xdc, 0x2f, 0x34,
+        ],
+    ),
+    (
+        64,
+        [
+            0xff, 0xe0, 0x54, 0xfe, 0x7a, 0xe0, 0xcb, 0x6d, 0xc6, 0x5c, 0x3a, 0xf9, 0xb6, 0x1d,
+            0x52, 0x09, 0xf4, 0x39, 0x85, 0x1d, 0xb4, 0x3d, 0x0b, 0xa5, 0x99, 0x73, 0x37, 0xdf,
+            0x15, 0x46, 0x68, 0xeb,
+        ],
+    ),
+    (
+        65,
+        [
+            0x63, 0x53, 0x61, 0xc4, 0x8b, 0xb9, 0xea, 0xb1, 0x41, 0x98, 0xe7, 0x6e, 0xa8, 0xab,
+            0x7f, 0x1a, 0x41, 0x68, 0x5d, 0x6a, 0xd6, 0x2a, 0xa9, 0x14, 0x6d, 0x30, 0x1d, 0x4f,
+            0x17, 0xeb, 0x0a, 0xe0,
+        ],
+    ),
+];
+
+/// Digests per size in the SHA-256 padding benchmark.
+pub const PADDING_ITERATIONS: usize = 1000;
+
+/// Hash each size of [`PADDING_VECTORS`] and log the mean time per digest,
+/// the number of blocks compressed and the relative jump from the previous
+/// size, so the extra padding block at 56 bytes stands out.
+///
+/// Each digest is also checked against its reference, which exercises the
+/// driver's padding at exactly the sizes where it changes.
+pub fn benchmark_sha256_padding(sha: &mut Sha) -> Result<(), BenchError> {
+    let input = [b'a'; 65];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    let mut previous_us = None;
+    for (size, expected) in PADDING_VECTORS {
+        let message = &input[..size];
+        benchmark_single_sha256(sha, message, &mut output)?;
+        if output != expected {
+            error!("SHA-256 of {size} bytes does not match the reference digest");
+        }
+
+        let start_time = Instant::now();
+        for _ in 0..PADDING_ITERATIONS {
+            benchmark_single_sha256(sha, message, &mut output)?;
+        }
+        let time_us = start_time.elapsed().as_micros() as f64 / PADDING_ITERATIONS as f64;
+
+        info!(
+            "SHA-256 padding, DataSize: {size}, Blocks: {}, Time: {}, Jump: {}, Digest: {}",
+            (size + 9).div_ceil(64),
+            Elapsed(time_us),
+            Delta::between(previous_us, time_us),
+            if output == expected { "ok" } else { "MISMATCH" }
+        );
+        previous_us = Some(time_us);
+    }
+    Ok(())
+}
+
+/// Bytes each of the two streams of the SHA-256 context switch benchmark
+/// contributes per update.
+pub const CONTEXT_CHUNK_SIZE: usize = 64;
+
+/// Updates per stream in the SHA-256 context switch benchmark.
+pub const CONTEXT_CHUNKS: usize = 64;
+
+/// Measure the cost of multiplexing two SHA-256 streams on the one engine.
+///
+/// The streams are hashed first one after the other, and then with their
+/// [`CONTEXT_CHUNK_SIZE`] byte updates alternating. Before each alternating
+/// update the stream's digest is restored from its saved [`Context`]
+/// (hash state and partial block) and afterwards saved again, so the
+/// per-switch cost is the extra time of the alternating run divided by its
+/// `2 * CONTEXT_CHUNKS` switches. Both runs must produce the same digests.
+pub fn benchmark_sha256_context_switch(sha: &mut Sha) -> Result<(), BenchError> {
+    const STREAM_SIZE: usize = CONTEXT_CHUNK_SIZE * CONTEXT_CHUNKS;
+    let streams = [[0xAB_u8; STREAM_SIZE], [0x5A_u8; STREAM_SIZE]];
+    let mut serial_digests = [[0_u8; 32]; 2];
+    let mut switched_digests = [[0_u8; 32]; 2];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &streams[0], &mut serial_digests[0])?;
+
+    let start_time = Instant::now();
+    for (stream, output) in streams.iter().zip(&mut serial_digests) {
+        let mut digest = sha.start::<Sha256>();
+        for chunk in stream.chunks(CONTEXT_CHUNK_SIZE) {
+            sha256_update(&mut digest, chunk)?;
+        }
+        sha256_finish(&mut digest, output)?;
+    }
+    let serial = start_time.elapsed();
+
+    let mut contexts = [Context::<Sha256>::new(), Context::<Sha256>::new()];
+    let start_time = Instant::now();
+    for i in 0..CONTEXT_CHUNKS {
+        for (stream, context) in streams.iter().zip(&mut contexts) {
+            let mut digest = ShaDigest::restore(&mut *sha, context);
+            sha256_update(
+                &mut digest,
+                &stream[i * CONTEXT_CHUNK_SIZE..][..CONTEXT_CHUNK_SIZE],
+            )?;
+            sha256_save(&mut digest, context)?;
+        }
+    }
+    for (context, output) in contexts.iter_mut().zip(&mut switched_digests) {
+        sha256_finish(&mut ShaDigest::restore(&mut *sha, context), output)?;
+    }
+    let switched = start_time.elapsed();
+
+    if switched_digests != serial_digests {
+        error!("SHA-256 context switch: digests differ from the serial run");
+    }
+    info!(
+        "SHA-256 context switch (2 streams x {STREAM_SIZE} bytes, {CONTEXT_CHUNK_SIZE} byte updates), Serial: {}, Switched: {}, Per switch: {}",
+        Elapsed::from_micros(serial.as_micros()),
+        Elapsed::from_micros(switched.as_micros()),
+        Elapsed(
+            (switched.as_micros() as f64 - serial.as_micros() as f64) / (2 * CONTEXT_CHUNKS) as f64
+        )
+    );
+    Ok(())
+}
+
+/// Digests per output destination in the SHA-256 finish destination
+/// benchmark.
+pub const FINISH_ITERATIONS: usize = 1000;
+
+/// Digest output in main SRAM outside the stack and heap.
+static mut STATIC_DIGEST: [u8; 32] = [0; 32];
+
+/// Digest output in the low-power SRAM (RTC fast memory).
+#[esp_hal::ram(rtc_fast)]
+static mut LP_SRAM_DIGEST: [u8; 32] = [0; 32];
+
+/// Compare SHA-256 digests of one block, [`FINISH_ITERATIONS`] each, with
+/// `finish` writing the digest to a stack buffer, the heap, a `static` in
+/// main SRAM and a `static` in the low-power SRAM.
+///
+/// The digest is read out of the hash registers by the CPU, so only the
+/// speed of the memory it is stored to can differ. The whole digest is timed
+/// rather than `finish` alone, which is too short for the microsecond timer;
+/// the start and update are the same for every destination.
+pub fn benchmark_sha256_finish_destination(sha: &mut Sha) -> Result<(), BenchError> {
+    use core::ptr::addr_of_mut;
+
+    let input = [0xAB_u8; 64];
+    let mut expected = [0_u8; 32];
+    let mut stack = [0_u8; 32];
+    let mut heap = Box::new([0_u8; 32]);
+    benchmark_single_sha256(sha, &input, &mut expected)?;
+
+    // SAFETY: the statics are only ever referenced here, and this function
+    // does not run concurrently with itself.
+    let (static_digest, lp_sram_digest) = unsafe {
+        (
+            &mut *addr_of_mut!(STATIC_DIGEST),
+            &mut *addr_of_mut!(LP_SRAM_DIGEST),
+        )
+    };
+    let destinations: [(&str, &mut [u8; 32]); 4] = [
+        ("stack", &mut stack),
+        ("heap", &mut heap),
+        ("static, SRAM", static_digest),
+        ("static, LP SRAM", lp_sram_digest),
+    ];
+
+    let mut stack_us = None;
+    for (label, output) in destinations {
+        output.fill(0);
+        let start_time = Instant::now();
+        for _ in 0..FINISH_ITERATIONS {
+            benchmark_single_sha256(sha, &input, output)?;
+        }
+        let time_us = start_time.elapsed().as_micros() as f64 / FINISH_ITERATIONS as f64;
+
+        if *output != expected {
+            error!("SHA-256 finish, Destination: {label}: wrong digest");
+        }
+        info!(
+            "SHA-256 finish, Destination: {label} ({:#010x}), DataSize: 64, Time: {}, Delta: {}",
+            output.as_ptr() as usize,
+            Elapsed(time_us),
+            Delta::between(Some(*stack_us.get_or_insert(time_us)), time_us)
+        );
+    }
+    Ok(())
+}
+
+/// Object sizes of the SHA-256 batch benchmark.
+pub const BATCH_DIGEST_SIZES: [usize; 3] = [64, 1024, 4096];
+
+/// Independent digests computed per size in the SHA-256 batch benchmark.
+pub const BATCH_DIGESTS: usize = 1000;
+
+/// Hash [`BATCH_DIGESTS`] independent objects of each of
+/// [`BATCH_DIGEST_SIZES`] bytes back to back, as when verifying many files,
+/// and log the digests per second and the effective throughput.
+///
+/// Every digest pays its own start, padding and finish, the overhead a
+/// single large buffer amortizes away.
+pub fn benchmark_sha256_batch(sha: &mut Sha) -> Result<(), BenchError> {
+    const MAX_SIZE: usize = BATCH_DIGEST_SIZES[BATCH_DIGEST_SIZES.len() - 1];
+    let input = [0xAB_u8; MAX_SIZE];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    for size in BATCH_DIGEST_SIZES {
+        let start_time = Instant::now();
+        for _ in 0..BATCH_DIGESTS {
+            benchmark_single_sha256(sha, &input[..size], &mut output)?;
+        }
+        let elapsed_us = start_time.elapsed().as_micros() as f64;
+        info!(
+            "SHA-256 batch, DataSize: {size}, Digests: {BATCH_DIGESTS}, Time: {}, Per digest: {}, Digests/s: {:.0}, Throughput: {}",
+            Elapsed(elapsed_us),
+            Elapsed(elapsed_us / BATCH_DIGESTS as f64),
+            BATCH_DIGESTS as f64 / elapsed_us * 1_000_000.0,
+            Throughput::of(BATCH_DIGESTS * size, elapsed_us)
+        );
+    }
+    Ok(())
+}
+
+/// Sizes of the first `update` in the SHA-256 stream start benchmark: part
+/// of a block, which is only buffered, and one whole block, which starts
+/// the engine.
+pub const STREAM_FIRST_UPDATE_SIZES: [usize; 2] = [16, 64];
+
+/// Hashes begun per variant in the SHA-256 stream start benchmark.
+pub const STREAM_START_ITERATIONS: usize = 1000;
+
+/// Measure the latency of beginning a hash on incoming stream data: the
+/// `sha.start()` call alone, and the first small `update` of each of
+/// [`STREAM_FIRST_UPDATE_SIZES`] until the engine is idle again, which the
+/// bulk throughput hides.
+///
+/// Both take well under the microsecond resolution of the timer, so each is
+/// the average of [`STREAM_START_ITERATIONS`] hashes begun and discarded with
+/// `cancel`; the update cost is that of start, update and cancel minus the
+/// start loop.
+pub fn benchmark_sha256_stream_start(sha: &mut Sha) -> Result<(), BenchError> {
+    let input = [0xAB_u8; 64];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    let ((), start_time) = timed(|| {
+        for _ in 0..STREAM_START_ITERATIONS {
+            core::hint::black_box(sha.start::<Sha256>()).cancel();
+        }
+    });
+    let start_us = start_time.as_micros() as f64 / STREAM_START_ITERATIONS as f64;
+    info!(
+        "SHA-256 stream start, start(): {}, Iterations: {STREAM_START_ITERATIONS}",
+        Elapsed(start_us)
+    );
+
+    for size in STREAM_FIRST_UPDATE_SIZES {
+        let (result, elapsed) = timed(|| {
+            for _ in 0..STREAM_START_ITERATIONS {
+                let mut digest = sha.start::<Sha256>();
+                sha256_update(&mut digest, &input[..size])?;
+                let mut busy = BusyWait::default();
+                while digest.is_busy() {
+                    if busy.expired() {
+                        return Err(BenchError::DigestUpdate);
+                    }
+                }
+                digest.cancel();
+            }
+            Ok(())
+        });
+        result?;
+        let total_us = elapsed.as_micros() as f64 / STREAM_START_ITERATIONS as f64;
+        info!(
+            "SHA-256 stream start, First update: {size} bytes, Update: {}, Start + update: {}",
+            Elapsed((total_us - start_us).max(0.0)),
+            Elapsed(total_us)
+        );
+    }
+    Ok(())
+}
+
+/// Hash a [`BLOB_SIZE`] firmware blob end to end into one SHA-256 digest,
+/// fed in 32 KB chunks as an OTA update verifying the image from flash
+/// would, and log the total time and effective throughput.
+pub fn benchmark_sha256_blob(sha: &mut Sha) -> Result<(), BenchError> {
+    const BLOB_CHUNK: usize = 32 * 1024;
+    let chunk = [0xAB_u8; BLOB_CHUNK];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &chunk, &mut output)?;
+
+    let (result, elapsed) = timed(|| {
+        let mut digest = sha.start::<Sha256>();
+        for _ in 0..BLOB_SIZE / BLOB_CHUNK {
+            sha256_update(&mut digest, &chunk)?;
+        }
+        sha256_finish(&mut digest, &mut output)
+    });
+    result?;
+
+    let elapsed_us = elapsed.as_micros() as f64;
+    info!(
+        "SHA-256 1 MB blob ({BLOB_CHUNK} byte chunks), Total: {}, Throughput: {}",
+        Elapsed(elapsed_us),
+        Throughput::of(BLOB_SIZE, elapsed_us)
+    );
+    log_result(&BenchmarkResult {
+        algorithm: "SHA-256 (1 MB blob)",
+        data_size: BLOB_SIZE,
+        iterations: 1,
+        time_us: elapsed_us,
+    });
+    Ok(())
+}
+
+/// Length of the fixed context string of the SHA-256 prefix benchmark.
+pub const PREFIX_SIZE: usize = 32;
+
+/// Variable data sizes hashed after the prefix.
+pub const PREFIX_DATA_SIZES: [usize; 4] = [32, 256, 1024, 4096];
+
+/// Digests timed per size and variant in the SHA-256 prefix benchmark.
+pub const PREFIX_ITERATIONS: usize = 100;
+
+/// Compare hashing a fixed [`PREFIX_SIZE`] byte context string and the data
+/// as two `update` calls, as domain-separated hashes and KDFs do, with hashing
+/// the two pre-concatenated in one buffer.
+///
+/// The concatenated buffer is built once outside the timing, so the overhead
+/// is that of the split alone; whether it is worth the copy to avoid it is
+/// the comparison with the `memcpy` baseline of the same size.
+pub fn benchmark_sha256_prefix(sha: &mut Sha) -> Result<(), BenchError> {
+    const MAX_SIZE: usize = PREFIX_DATA_SIZES[PREFIX_DATA_SIZES.len() - 1];
+    let prefix = [0x5C_u8; PREFIX_SIZE];
+    let mut concatenated = [0xAB_u8; PREFIX_SIZE + MAX_SIZE];
+    concatenated[..PREFIX_SIZE].copy_from_slice(&prefix);
+
+    for size in PREFIX_DATA_SIZES {
+        let data = &concatenated[PREFIX_SIZE..][..size];
+        let mut split_digest = [0_u8; 32];
+        let mut joined_digest = [0_u8; 32];
+
+        let start_time = Instant::now();
+        for _ in 0..PREFIX_ITERATIONS {
+            let mut digest = sha.start::<Sha256>();
+            sha256_update(&mut digest, &prefix)?;
+            sha256_update(&mut digest, data)?;
+            sha256_finish(&mut digest, &mut split_digest)?;
+        }
+        let split_us = start_time.elapsed().as_micros() as f64 / PREFIX_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..PREFIX_ITERATIONS {
+            benchmark_single_sha256(sha, &concatenated[..PREFIX_SIZE + size], &mut joined_digest)?;
+        }
+        let joined_us = start_time.elapsed().as_micros() as f64 / PREFIX_ITERATIONS as f64;
+
+        if split_digest != joined_digest {
+            error!("SHA-256 prefix, DataSize: {size}: split and concatenated digests differ");
+        }
+        info!(
+            "SHA-256 {PREFIX_SIZE} byte prefix + data, DataSize: {size}, Split: {}, Concatenated: {}, Overhead: {:+} ({})",
+            Elapsed(split_us),
+            Elapsed(joined_us),
+            Elapsed(split_us - joined_us),
+            Delta::between(Some(joined_us), split_us)
+        );
+    }
+    Ok(())
+}
+
+/// Bytes hashed by the SHA-256 scatter-gather benchmark.
+pub const SHA_SCATTER_SIZE: usize = 16 * 1024;
+
+/// Numbers of equal fragments the SHA-256 scatter-gather benchmark splits
+/// [`SHA_SCATTER_SIZE`] into, down to 64-byte fragments.
+pub const SHA_SCATTER_FRAGMENTS: [usize; 5] = [4, 16, 64, 128, 256];
+
+/// Digests timed per fragmentation in the SHA-256 scatter-gather benchmark.
+pub const SHA_SCATTER_ITERATIONS: usize = 20;
+
+/// Hash [`SHA_SCATTER_SIZE`] bytes from one contiguous buffer and then from
+/// [`SHA_SCATTER_FRAGMENTS`] separately allocated fragments with one `update`
+/// per fragment, as for a chain of network buffers, and log the overhead of
+/// each fragmentation in total and per fragment.
+///
+/// Both hash the same data, so each fragmented digest is checked against
+/// the contiguous one.
+pub fn benchmark_sha256_scatter_gather(sha: &mut Sha) -> Result<(), BenchError> {
+    let data: Vec<u8> = (0..SHA_SCATTER_SIZE).map(|i| i as u8).collect();
+    let mut expected = [0_u8; 32];
+    let mut output = [0_u8; 32];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &data, &mut expected)?;
+
+    let start_time = Instant::now();
+    for _ in 0..SHA_SCATTER_ITERATIONS {
+        benchmark_single_sha256(sha, &data, &mut expected)?;
+    }
+    let contiguous_us = start_time.elapsed().as_micros() as f64 / SHA_SCATTER_ITERATIONS as f64;
+    info!(
+        "SHA-256 contiguous, DataSize: {SHA_SCATTER_SIZE}, Time: {}, Throughput: {}",
+        Elapsed(contiguous_us),
+        Throughput::of(SHA_SCATTER_SIZE, contiguous_us)
+    );
+
+    for count in SHA_SCATTER_FRAGMENTS {
+        let fragments: Vec<Box<[u8]>> = data
+            .chunks(SHA_SCATTER_SIZE / count)
+            .map(Box::from)
+            .collect();
+
+        let start_time = Instant::now();
+        for _ in 0..SHA_SCATTER_ITERATIONS {
+            let mut digest = sha.start::<Sha256>();
+            for fragment in &fragments {
+                sha256_update(&mut digest, fragment)?;
+            }
+            sha256_finish(&mut digest, &mut output)?;
+        }
+        let scattered_us = start_time.elapsed().as_micros() as f64 / SHA_SCATTER_ITERATIONS as f64;
+
+        info!(
+            "SHA-256 scatter-gather, DataSize: {SHA_SCATTER_SIZE}, Fragments: {count}, Time: {}, Contiguous: {}, Overhead: {}, Per fragment: {:+}, Match: {}",
+            Elapsed(scattered_us),
+            Elapsed(contiguous_us),
+            Delta::between(Some(contiguous_us), scattered_us),
+            Elapsed((scattered_us - contiguous_us) / count as f64),
+            output == expected
+        );
+    }
+    Ok(())
+}
+
+/// Handshake transcript sizes hashed by the TLS transcript benchmark.
+pub const TLS_TRANSCRIPT_SIZES: [usize; 3] = [128, 300, 600];
+
+/// Individually timed digests per transcript size, enough for a tight
+/// confidence interval.
+pub const TLS_TRANSCRIPT_ITERATIONS: usize = 1000;
+
+/// Transcript hashes per handshake: each side computes its own Finished
+/// verify_data and checks the peer's, each over the transcript so far.
+pub const TLS_FINISHED_HASHES: usize = 2;
+
+/// Time one-shot SHA-256 digests of each of [`TLS_TRANSCRIPT_SIZES`], the
+/// handshake transcripts a TLS Finished message hashes, and log the mean with
+/// its 95% confidence interval and the hashing cost of one handshake of
+/// [`TLS_FINISHED_HASHES`] transcript hashes.
+pub fn benchmark_sha256_tls_transcript(sha: &mut Sha) -> Result<(), BenchError> {
+    const MAX_SIZE: usize = TLS_TRANSCRIPT_SIZES[TLS_TRANSCRIPT_SIZES.len() - 1];
+    let transcript = [0x16_u8; MAX_SIZE];
+    let mut verify_data = [0_u8; 32];
+
+    for size in TLS_TRANSCRIPT_SIZES {
+        // Pre-warm the SHA engine
+        benchmark_single_sha256(sha, &transcript[..size], &mut verify_data)?;
+
+        let mut samples = Samples::<TLS_TRANSCRIPT_ITERATIONS>::new();
+        for _ in 0..TLS_TRANSCRIPT_ITERATIONS {
+            let elapsed = benchmark_single_sha256(sha, &transcript[..size], &mut verify_data)?;
+            samples.push(elapsed.as_micros());
+        }
+
+        let summary = Summary::of(samples.as_slice().iter().map(|&us| us as f64));
+        info!(
+            "SHA-256 TLS transcript, DataSize: {size}, Iterations: {}, Time: {} ± {} (95% CI, ±{:.2}%), Per handshake: {}",
+            summary.count,
+            Elapsed(summary.mean),
+            Elapsed(summary.margin_95),
+            summary.relative_margin_95() * 100.0,
+            Elapsed(summary.mean * TLS_FINISHED_HASHES as f64)
+        );
+        log_result(&BenchmarkResult {
+            algorithm: "SHA-256 (TLS transcript)",
+            data_size: size,
+            iterations: summary.count,
+            time_us: summary.mean,
+        });
+    }
+    Ok(())
+}
+
+/// Saves and restores timed by the SHA-256 context save/restore benchmark.
+pub const CONTEXT_SAVE_ITERATIONS: usize = 1000;
+
+/// Measure what one stream costs when many interleaved SHA-256 streams share
+/// the engine: the size of its saved [`Context`] and the time to save and
+/// restore it.
+///
+/// The digest holds a partial block, so the saves copy the message registers
+/// as well as the hash state, as they would between real updates. Saves are
+/// timed on their own first, then together with the restores; the restore
+/// time is the difference. Finishing the digest afterwards must give the hash
+/// of the data fed before the first save.
+pub fn benchmark_sha256_context_save_restore(sha: &mut Sha) -> Result<(), BenchError> {
+    let input = [0xAB_u8; CONTEXT_CHUNK_SIZE + CONTEXT_CHUNK_SIZE / 2];
+    let mut expected = [0_u8; 32];
+    benchmark_single_sha256(sha, &input, &mut expected)?;
+
+    let mut context = Context::<Sha256>::new();
+    let mut digest = sha.start::<Sha256>();
+    sha256_update(&mut digest, &input)?;
+    sha256_save(&mut digest, &mut context)?;
+
+    let start_time = Instant::now();
+    for _ in 0..CONTEXT_SAVE_ITERATIONS {
+        sha256_save(&mut digest, &mut context)?;
+    }
+    let save_us = start_time.elapsed().as_micros() as f64 / CONTEXT_SAVE_ITERATIONS as f64;
+
+    let start_time = Instant::now();
+    for _ in 0..CONTEXT_SAVE_ITERATIONS {
+        sha256_save(&mut digest, &mut context)?;
+        digest = ShaDigest::restore(digest.cancel(), &mut context);
+    }
+    let both_us = start_time.elapsed().as_micros() as f64 / CONTEXT_SAVE_ITERATIONS as f64;
+
+    let mut output = [0_u8; 32];
+    sha256_finish(&mut digest, &mut output)?;
+    if output != expected {
+        error!("SHA-256 context save/restore: digest differs after the restores");
+    }
+    info!(
+        "SHA-256 context save/restore, Context: {} bytes, Save: {}, Restore: {}, Save+restore: {} (mean of {CONTEXT_SAVE_ITERATIONS}), Digest: {}",
+        size_of::<Context<Sha256>>(),
+        Elapsed(save_us),
+        Elapsed(both_us - save_us),
+        Elapsed(both_us),
+        if output == expected { "ok" } else { "MISMATCH" }
+    );
+    Ok(())
+}
+
+pub fn benchmark_single_sha256(
+    sha: &mut Sha,
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<Duration, BenchError> {
+    let start_time = Instant::now();
+    let mut digest = sha.start::<Sha256>();
+    sha256_update(&mut digest, input)?;
+    sha256_finish(&mut digest, output)?;
+    Ok(start_time.elapsed())
+}
+
+/// HMAC-SHA256 of `message` under `key` (at most one 64-byte block), using
+/// the SHA peripheral for both hash passes.
+///
+/// The HMAC peripheral only accepts keys stored in eFuse, so the HMAC
+/// construction (RFC 2104) is done in software around the hardware SHA-256.
+pub fn hmac_sha256(
+    sha: &mut Sha,
+    key: &[u8],
+    message: &[u8],
+    mac: &mut [u8; 32],
+) -> Result<(), BenchError> {
+    const BLOCK_SIZE: usize = 64;
+    assert!(
+        key.len() <= BLOCK_SIZE,
+        "HMAC keys longer than a block are not supported"
+    );
+
+    let mut padded_key = [0_u8; BLOCK_SIZE];
+    padded_key[..key.len()].copy_from_slice(key);
+
+    let mut pad = padded_key.map(|b| b ^ 0x36);
+    let mut inner = [0_u8; 32];
+    let mut digest = sha.start::<Sha256>();
+    sha256_update(&mut digest, &pad)?;
+    sha256_update(&mut digest, message)?;
+    sha256_finish(&mut digest, &mut inner)?;
+
+    pad = padded_key.map(|b| b ^ 0x5c);
+    let mut digest = sha.start::<Sha256>();
+    sha256_update(&mut digest, &pad)?;
+    sha256_update(&mut digest, &inner)?;
+    sha256_finish(&mut digest, mac)?;
+    Ok(())
+}
+
+/// An HMAC-SHA256 key prepared for many messages: the SHA-256 states after
+/// hashing the inner and the outer padded key block.
+struct HmacSha256Key {
+    inner: Context<Sha256>,
+    outer: Context<Sha256>,
+}
+
+/// Hash the padded `key` (at most one 64-byte block) XOR ipad and XOR opad
+/// and save both states, the per-key half of [`hmac_sha256`].
+fn hmac_sha256_key(sha: &mut Sha, key: &[u8]) -> Result<HmacSha256Key, BenchError> {
+    const BLOCK_SIZE: usize = 64;
+    assert!(
+        key.len() <= BLOCK_SIZE,
+        "HMAC keys longer than a block are not supported"
+    );
+
+    let mut padded_key = [0_u8; BLOCK_SIZE];
+    padded_key[..key.len()].copy_from_slice(key);
+
+    let mut prepared = HmacSha256Key {
+        inner: Context::new(),
+        outer: Context::new(),
+    };
+    for (pad, context) in [(0x36, &mut prepared.inner), (0x5c, &mut prepared.outer)] {
+        let mut digest = sha.start::<Sha256>();
+        sha256_update(&mut digest, &padded_key.map(|b| b ^ pad))?;
+        sha256_save(&mut digest, context)?;
+    }
+    Ok(prepared)
+}
+
+/// HMAC-SHA256 of `message` under a key prepared by [`hmac_sha256_key`],
+/// the per-message half of [`hmac_sha256`]: both passes continue from the
+/// saved padded key states.
+fn hmac_sha256_prepared(
+    sha: &mut Sha,
+    key: &mut HmacSha256Key,
+    message: &[u8],
+    mac: &mut [u8; 32],
+) -> Result<(), BenchError> {
+    let mut inner = [0_u8; 32];
+    let mut digest = ShaDigest::restore(&mut *sha, &mut key.inner);
+    sha256_update(&mut digest, message)?;
+    sha256_finish(&mut digest, &mut inner)?;
+
+    let mut digest = ShaDigest::restore(&mut *sha, &mut key.outer);
+    sha256_update(&mut digest, &inner)?;
+    sha256_finish(&mut digest, mac)?;
+    Ok(())
+}
+
+/// Message sizes of the HMAC key reuse benchmark, from a short token to a
+/// kilobyte.
+pub const HMAC_KEY_REUSE_SIZES: [usize; 3] = [32, 256, 1024];
+
+/// Key setups and MACs timed per size by the HMAC key reuse benchmark.
+pub const HMAC_KEY_REUSE_ITERATIONS: u32 = 100;
+
+/// Split HMAC-SHA256 into its per-key and per-message cost: the mean time to
+/// prepare a key with `hmac_sha256_key`, to MAC a message with the prepared
+/// key and to MAC it from scratch with [`hmac_sha256`], which repeats the
+/// two padded key blocks. Both MACs must agree.
+pub fn benchmark_hmac_key_reuse(sha: &mut Sha) -> Result<(), BenchError> {
+    let key = [0x4B_u8; 32];
+    let message = [0xAB_u8; HMAC_KEY_REUSE_SIZES[HMAC_KEY_REUSE_SIZES.len() - 1]];
+    let mut mac = [0_u8; 32];
+    let mut expected = [0_u8; 32];
+
+    let start_time = Instant::now();
+    for _ in 0..HMAC_KEY_REUSE_ITERATIONS {
+        core::hint::black_box(hmac_sha256_key(sha, &key)?);
+    }
+    let setup_us = start_time.elapsed().as_micros() as f64 / HMAC_KEY_REUSE_ITERATIONS as f64;
+    let mut prepared = hmac_sha256_key(sha, &key)?;
+
+    for size in HMAC_KEY_REUSE_SIZES {
+        let message = &message[..size];
+        set_current_benchmark("HMAC-SHA256 key reuse", Some(size));
+
+        let start_time = Instant::now();
+        for _ in 0..HMAC_KEY_REUSE_ITERATIONS {
+            hmac_sha256_prepared(sha, &mut prepared, message, &mut mac)?;
+        }
+        let reused_us = start_time.elapsed().as_micros() as f64 / HMAC_KEY_REUSE_ITERATIONS as f64;
+
+        let start_time = Instant::now();
+        for _ in 0..HMAC_KEY_REUSE_ITERATIONS {
+            hmac_sha256(sha, &key, message, &mut expected)?;
+        }
+        let full_us = start_time.elapsed().as_micros() as f64 / HMAC_KEY_REUSE_ITERATIONS as f64;
+
+        if mac != expected {
+            error!("HMAC-SHA256 key reuse, DataSize: {size}: MAC differs from the unprepared one");
+        }
+        info!(
+            "HMAC-SHA256 key reuse, DataSize: {size}, Key setup: {}, Per message: {}, Full HMAC: {}, Saved: {:.1}%, MAC: {}",
+            Elapsed(setup_us),
+            Elapsed(reused_us),
+            Elapsed(full_us),
+            100.0 * (1.0 - reused_us / full_us),
+            if mac == expected { "ok" } else { "MISMATCH" }
+        );
+    }
+    Ok(())
+}
+
+/// Flash-resident copy of the SHA-256 benchmark input.
+///
+/// Immutable statics are placed in `.rodata`, which is memory-mapped from
+/// flash through the cache, so hashing this slice reads flash directly without
+/// an intermediate RAM copy.
+#[cfg(feature = "sha-flash")]
+pub static FLASH_INPUT: [u8; 32 * 1024] = [0xAB; 32 * 1024];
+
+/// Flash offset read by the explicit flash-read benchmark: the start of the
+/// factory app partition in the default partition table, i.e. the firmware
+/// image itself.
+#[cfg(feature = "sha-flash")]
+const FLASH_READ_OFFSET: u32 = 0x10000;
+
+/// Benchmark SHA-256 over data that lives in flash, compared to RAM.
+///
+/// For each data size three variants are timed:
+/// * RAM - hashing a buffer already loaded into SRAM (the regular benchmark).
+/// * Mapped - hashing [`FLASH_INPUT`] in place through the flash cache.
+/// * Read - reading the data with `esp-storage` into a small RAM buffer chunk
+///   by chunk and hashing each chunk, including the time spent reading.
+#[cfg(feature = "sha-flash")]
+pub fn benchmark_sha256_flash(sha: &mut Sha, data_sizes: &[usize]) -> Result<(), BenchError> {
+    use embedded_storage::ReadStorage;
+    use esp_storage::FlashStorage;
+
+    let mut input = [0_u8; 32 * 1024];
+    input.fill(0xAB);
+    let mut output = [0_u8; 32];
+    let mut flash = FlashStorage::new();
+    let mut chunk = [0_u8; 4096];
+
+    // Pre-warm the SHA-256
+    benchmark_single_sha256(sha, &input, &mut output)?;
+
+    for &size in data_sizes {
+        let ram = benchmark_single_sha256(sha, &input[..size], &mut output)?;
+
+        let mapped_input = core::hint::black_box(&FLASH_INPUT[..size]);
+        let mapped = benchmark_single_sha256(sha, mapped_input, &mut output)?;
+
+        let start_time = Instant::now();
+        let mut digest = sha.start::<Sha256>();
+        let mut offset = 0;
+        while offset < size {
+            let len = chunk.len().min(size - offset);
+            flash
+                .read(FLASH_READ_OFFSET + offset as u32, &mut chunk[..len])
+                .unwrap();
+            sha256_update(&mut digest, &chunk[..len])?;
+            offset += len;
+        }
+        sha256_finish(&mut digest, &mut output)?;
+        let read = start_time.elapsed();
+
+        info!(
+            "SHA-256 (flash), DataSize: {size}, RAM: {}, Mapped: {}, Read: {}",
+            Elapsed::from_micros(ram.as_micros()),
+            Elapsed::from_micros(mapped.as_micros()),
+            Elapsed::from_micros(read.as_micros())
+        );
+    }
+    Ok(())
+}
+
+/// Time the first SHA-256 digest of the run against the warm ones after it,
+/// and return the time of the first in microseconds.
+pub fn cold_start_sha256(sha: &mut Sha) -> Result<u64, BenchError> {
+    let input = [0xAB_u8; COLD_START_SIZE];
+    let mut output = [0_u8; 32];
+    let cold_us = benchmark_single_sha256(sha, &input, &mut output)?.as_micros();
+
+    let start_time = Instant::now();
+    for _ in 0..COLD_START_WARM_ITERATIONS {
+        benchmark_single_sha256(sha, &input, &mut output)?;
+    }
+    let warm_us = start_time.elapsed().as_micros() as f64 / COLD_START_WARM_ITERATIONS as f64;
+
+    log_cold_start("SHA-256", COLD_START_SIZE, cold_us, warm_us);
+    Ok(cold_us)
+}
+
+/// Measure the wake cost of the SHA peripheral clock like
+/// [`benchmark_aes_wake`](super::aes::benchmark_aes_wake): drop `sha`, which gates and resets the peripheral,
+/// create the driver again and time the first digest.
+///
+/// Returns the driver created last, with the result of the measurement.
+pub fn benchmark_sha256_wake(mut sha: Sha<'_>) -> (Sha<'_>, Result<(), BenchError>) {
+    let input = [0xAB_u8; WAKE_SIZE];
+    let mut output = [0_u8; 32];
+
+    let mut steady = Samples::<WAKE_ITERATIONS>::new();
+    for _ in 0..WAKE_ITERATIONS {
+        match benchmark_single_sha256(&mut sha, &input, &mut output) {
+            Ok(elapsed) => steady.push(elapsed.as_micros()),
+            Err(error) => return (sha, Err(error)),
+        }
+    }
+
+    let mut ungate = Samples::<WAKE_ITERATIONS>::new();
+    let mut first = Samples::<WAKE_ITERATIONS>::new();
+    for _ in 0..WAKE_ITERATIONS {
+        drop(sha);
+        // SAFETY: the previous driver owning the SHA peripheral has been
+        // dropped.
+        let elapsed;
+        (sha, elapsed) = timed(|| Sha::new(unsafe { peripherals::SHA::steal() }));
+        ungate.push(elapsed.as_micros());
+
+        match benchmark_single_sha256(&mut sha, &input, &mut output) {
+            Ok(elapsed) => first.push(elapsed.as_micros()),
+            Err(error) => return (sha, Err(error)),
+        }
+    }
+
+    log_wake(
+        "SHA-256",
+        ungate.as_slice(),
+        first.as_slice(),
+        steady.as_slice(),
+    );
+    (sha, Ok(()))
+}
@@ -6,20 +6,159 @@
     holding buffers for the duration of a data transfer."
 )]
 
-use core::u32;
+#[cfg(not(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "bench-rsa",
+    feature = "bench-rng",
+    feature = "bench-ecc"
+)))]
+compile_error!(
+    "enable at least one of the `bench-aes`, `bench-sha`, `bench-rsa`, `bench-rng` and `bench-ecc` features"
+);
+#[cfg(all(feature = "aes-dma-ch1", feature = "aes-dma-ch2"))]
+compile_error!("enable at most one of the `aes-dma-ch1` and `aes-dma-ch2` features");
+#[cfg(all(feature = "quick", feature = "rsa-stability"))]
+compile_error!("enable at most one of the `quick` and `rsa-stability` features");
 
-use crypto_bigint::{Uint, U2048};
+use core::marker::PhantomData;
+
+#[cfg(any(feature = "flash-results", feature = "prometheus-output"))]
+use alloc::string::String;
+#[cfg(feature = "prometheus-output")]
+use alloc::string::ToString;
+#[cfg(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+use alloc::vec::Vec;
+#[cfg(feature = "flash-results")]
+use core::fmt::Write;
+#[cfg(feature = "bench-rsa")]
+use crypto_bigint::U2048;
+#[cfg(any(feature = "serial-abort", feature = "flash-results"))]
+use esp32c6_crypto::abort;
+#[cfg(feature = "baseline")]
+use esp32c6_crypto::baseline;
+use esp32c6_crypto::benchmarks;
+#[cfg(feature = "bench-aes")]
+use esp32c6_crypto::benchmarks::aes::{self, AES_DMA_CHANNEL, MAX_DMA_BUFFER_SIZE};
+#[cfg(feature = "soft-compare")]
+use esp32c6_crypto::benchmarks::compare;
+#[cfg(feature = "concurrent")]
+use esp32c6_crypto::benchmarks::concurrent;
+#[cfg(feature = "bench-ecc")]
+use esp32c6_crypto::benchmarks::ecc;
+#[cfg(feature = "hmac-efuse")]
+use esp32c6_crypto::benchmarks::hmac;
+#[cfg(feature = "bench-rng")]
+use esp32c6_crypto::benchmarks::rng;
+#[cfg(feature = "bench-rsa")]
+use esp32c6_crypto::benchmarks::rsa;
+#[cfg(feature = "bench-sha")]
+use esp32c6_crypto::benchmarks::sha;
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+use esp32c6_crypto::benchmarks::COLD_START_SIZE;
+use esp32c6_crypto::clock_monitor;
+#[cfg(any(feature = "bench-sha", feature = "bench-rsa"))]
+use esp32c6_crypto::error::BenchError;
+#[cfg(feature = "flash-results")]
+use esp32c6_crypto::flash_results;
+use esp32c6_crypto::output;
+#[cfg(all(
+    feature = "power-sensor",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+use esp32c6_crypto::power::{EnergyMeter, Ina219};
+#[cfg(feature = "json-output")]
+use esp32c6_crypto::report::Json;
+#[cfg(any(
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+use esp32c6_crypto::report::ModeRow;
+#[cfg(feature = "soft-compare")]
+use esp32c6_crypto::report::COMPARISON_CSV_HEADER;
+#[cfg(feature = "csv-output")]
+use esp32c6_crypto::report::{Csv, CSV_HEADER};
+#[cfg(feature = "prometheus-output")]
+use esp32c6_crypto::report::{Metric, Prometheus};
+#[cfg(any(
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+use esp32c6_crypto::results;
+#[cfg(feature = "thresholds")]
+use esp32c6_crypto::thresholds;
+use esp32c6_crypto::units::Elapsed;
+#[cfg(all(
+    any(feature = "throughput-profile", feature = "power-sensor"),
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+use esp32c6_crypto::units::Throughput;
 use esp_backtrace as _;
-use esp_hal::aes::dma::{AesDma, CipherMode};
-use esp_hal::aes::{Aes, Mode};
+#[cfg(feature = "bench-aes")]
+use esp_hal::aes::Aes;
 use esp_hal::clock::CpuClock;
-use esp_hal::dma::{DmaRxBuf, DmaTxBuf};
+#[cfg(feature = "bench-aes")]
+use esp_hal::dma::CHUNK_SIZE;
+#[cfg(feature = "bench-ecc")]
+use esp_hal::ecc::Ecc;
+#[cfg(feature = "button-trigger")]
+use esp_hal::gpio::{Input, InputConfig, Pull};
+#[cfg(feature = "hmac-efuse")]
+use esp_hal::hmac::Hmac;
+#[cfg(all(
+    feature = "power-sensor",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+use esp_hal::i2c::{self, master::I2c};
+use esp_hal::main;
+#[cfg(feature = "bench-rng")]
+use esp_hal::rng::Trng;
+#[cfg(feature = "bench-rsa")]
 use esp_hal::rsa::operand_sizes::Op2048;
-use esp_hal::rsa::{Rsa, RsaModularExponentiation};
-use esp_hal::sha::{Sha, Sha256};
-use esp_hal::time::{Duration, Instant};
-use esp_hal::{dma_buffers, main};
-use log::{debug, info};
+#[cfg(feature = "bench-rsa")]
+use esp_hal::rsa::Rsa;
+#[cfg(feature = "bench-sha")]
+use esp_hal::sha::Sha;
+#[cfg(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "bench-rsa",
+    feature = "bench-rng",
+    feature = "flash-results",
+    feature = "button-trigger",
+    not(feature = "wfi-idle")
+))]
+use esp_hal::time::Duration;
+use esp_hal::time::Instant;
+#[cfg(all(
+    feature = "power-sensor",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+use esp_hal::time::Rate;
+#[cfg(any(feature = "flash-results", feature = "serial-abort"))]
+use esp_hal::usb_serial_jtag::UsbSerialJtag;
+#[cfg(any(feature = "bench-rsa", feature = "bench-ecc"))]
+use esp_hal::Blocking;
+#[cfg(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "bench-rsa",
+    feature = "flash-results"
+))]
+use log::error;
+use log::info;
+use log::warn;
 
 extern crate alloc;
 
@@ -27,186 +166,815 @@ extern crate alloc;
 // For more information see: <https://docs.espressif.com/projects/esp-idf/en/stable/esp32/api-reference/system/app_image_format.html#application-description>
 esp_bootloader_esp_idf::esp_app_desc!();
 
-fn benchmark_aes_dma(aes: AesDma, data_sizes: &[usize]) {
-    // Pre-warm the AES DMA
-    let (mut aes, _) = benchmark_single_aes_dma(aes, 64);
+/// The single data size of the AES and SHA benchmarks in a `quick` run.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+const QUICK_DATA_SIZE: usize = 1024;
 
-    // Benchmark for each data size
-    for &size in data_sizes {
-        let throughput;
-        (aes, throughput) = benchmark_single_aes_dma(aes, size);
+/// Which engines gave the right answer for their known-answer vector. The
+/// benchmarks of an engine that did not are skipped, since their timings
+/// would be of a misconfigured engine.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+#[derive(Clone, Copy, Debug)]
+struct Verified {
+    #[cfg(feature = "bench-aes")]
+    aes: bool,
+    #[cfg(feature = "bench-sha")]
+    sha: bool,
+    #[cfg(feature = "bench-rsa")]
+    rsa: bool,
+}
+
+/// Run one operation on each engine against its known-answer vector, with
+/// [`aes::verify_aes_ctr`], [`sha::verify_sha256`] and
+/// [`rsa::verify_rsa_modexp`], and log which benchmarks are skipped.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+fn verify_engines(mut drivers: Drivers<'_>) -> (Drivers<'_>, Verified) {
+    output::banner("Known Answers");
+
+    #[cfg(feature = "bench-aes")]
+    let aes;
+    #[cfg(feature = "bench-aes")]
+    {
+        benchmarks::set_current_benchmark("AES-CTR known-answer test", None);
+        (drivers.aes, aes) = match drivers.aes.take().map(aes::verify_aes_ctr) {
+            Some(Ok((bench, passed))) => (Some(bench), passed),
+            Some(Err((error, bench))) => {
+                benchmarks::failed(error);
+                (bench, false)
+            }
+            None => (None, false),
+        };
+        if !aes {
+            error!("Skipping the AES benchmarks");
+        }
+    }
+
+    #[cfg(feature = "bench-sha")]
+    let sha;
+    #[cfg(feature = "bench-sha")]
+    {
+        benchmarks::set_current_benchmark("SHA-256 known-answer test", None);
+        sha = sha::verify_sha256(&mut drivers.sha).unwrap_or_else(|error| {
+            benchmarks::failed(error);
+            false
+        });
+        if !sha {
+            error!("Skipping the SHA-256 benchmarks");
+        }
+    }
+
+    #[cfg(feature = "bench-rsa")]
+    let rsa;
+    #[cfg(feature = "bench-rsa")]
+    {
+        benchmarks::set_current_benchmark("RSA-2048 known-answer test", None);
+        rsa = rsa::verify_rsa_modexp(&mut drivers.rsa);
+        if !rsa {
+            error!("Skipping the RSA benchmarks");
+        }
+    }
+
+    let verified = Verified {
+        #[cfg(feature = "bench-aes")]
+        aes,
+        #[cfg(feature = "bench-sha")]
+        sha,
+        #[cfg(feature = "bench-rsa")]
+        rsa,
+    };
+    (drivers, verified)
+}
+
+/// Data size processed repeatedly by the throughput-over-time profile.
+#[cfg(all(
+    feature = "throughput-profile",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+const PROFILE_DATA_SIZE: usize = 4096;
+
+/// Total duration of each throughput-over-time profile.
+#[cfg(all(
+    feature = "throughput-profile",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+const PROFILE_DURATION: Duration = Duration::from_secs(60);
+
+/// Interval between throughput samples of the throughput-over-time profile.
+#[cfg(all(
+    feature = "throughput-profile",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+const PROFILE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Run `step` back to back for [`PROFILE_DURATION`] and log the throughput of
+/// every [`PROFILE_INTERVAL`], tagged with the time since boot.
+///
+/// Each call of `step` processes `data_size` bytes and threads its driver
+/// state through, so a host tool can plot throughput against time from the
+/// `Throughput over time` lines to reveal thermal throttling. The profile
+/// ends at the first error of `step`.
+#[cfg(all(
+    feature = "throughput-profile",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+fn profile_throughput<T, E>(
+    label: &str,
+    data_size: usize,
+    mut state: T,
+    mut step: impl FnMut(T) -> Result<T, E>,
+) -> Result<T, E> {
+    let profile_start = Instant::now();
+    while profile_start.elapsed() < PROFILE_DURATION && !benchmarks::aborted() {
+        let interval_start = Instant::now();
+        let mut bytes = 0;
+        while interval_start.elapsed() < PROFILE_INTERVAL {
+            state = step(state)?;
+            bytes += data_size;
+        }
+        let elapsed = interval_start.elapsed();
         info!(
-            "AES-CTR, DataSize: {size}, Throughput: {:.2} MB/s",
-            throughput / 1_000_000.0
+            "Throughput over time, {label}, DataSize: {data_size}, Timestamp: {} ms, Throughput: {}",
+            Instant::now().duration_since_epoch().as_millis(),
+            Throughput::of(bytes, elapsed.as_micros() as f64)
         );
     }
+    Ok(state)
+}
+
+/// Profile AES-CTR throughput over time at [`PROFILE_DATA_SIZE`].
+#[cfg(all(feature = "throughput-profile", feature = "bench-aes"))]
+fn profile_aes_dma(bench: aes::AesBench<'_>) -> aes::AesResult<'_> {
+    let size = PROFILE_DATA_SIZE.min(bench.capacity());
+    profile_throughput("AES-CTR", size, bench, |bench| bench.encrypt(size, || {}))
 }
 
-/// Benchmark AES-CTR with DMA using a fixed buffer size.
-/// This function initializes the AES DMA, processes data in chunks, and measures throughput.
-/// # Arguments
-/// * `aes` - The AES DMA instance to use for processing.
-/// * `buffer_size` - The size of the buffer to use for each AES operation,
-/// limited to a maximum of 32 KB.
-/// # Returns
-/// A tuple containing the AES DMA instance and the throughput in bytes per second.
+/// Profile SHA-256 throughput over time at [`PROFILE_DATA_SIZE`].
+#[cfg(all(feature = "throughput-profile", feature = "bench-sha"))]
+fn profile_sha256(sha: &mut Sha) -> Result<(), BenchError> {
+    let input = [0xAB_u8; PROFILE_DATA_SIZE];
+    let mut output = [0_u8; 32];
+
+    profile_throughput("SHA-256", PROFILE_DATA_SIZE, (), |()| {
+        sha::benchmark_single_sha256(sha, &input, &mut output).map(|_| ())
+    })
+}
+
+/// How long each energy measurement runs its workload.
+#[cfg(all(
+    feature = "power-sensor",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+const ENERGY_DURATION: Duration = Duration::from_millis(500);
+
+/// Minimum time between power samples during an energy measurement.
+///
+/// The INA219 averages each sample over the 532 µs before it, so with this
+/// much pure workload between two reads a sample does not include the I2C
+/// traffic of the previous one.
+#[cfg(all(
+    feature = "power-sensor",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+const ENERGY_SAMPLE_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Run `step` back to back for [`ENERGY_DURATION`], sampling the power every
+/// [`ENERGY_SAMPLE_INTERVAL`], and log the measured energy efficiency.
 ///
-fn benchmark_single_aes_dma(mut aes: AesDma, buffer_size: usize) -> (AesDma, f64) {
-    // Use a fixed buffer size for the macro, then limit the actual processing
-    const MAX_BUFFER_SIZE: usize = 32 * 1024; // 32 KB maximum buffer
-    let (output, rx_descriptors, input, tx_descriptors) = dma_buffers!(MAX_BUFFER_SIZE);
-    let mut output = DmaRxBuf::new(rx_descriptors, output).unwrap();
-    let mut input = DmaTxBuf::new(tx_descriptors, input).unwrap();
+/// The throughput only counts the time spent in `step`, not in the sensor
+/// reads, and the bytes per joule are that throughput divided by the mean
+/// power. The measurement ends at the first error of `step`.
+#[cfg(all(
+    feature = "power-sensor",
+    any(feature = "bench-aes", feature = "bench-sha")
+))]
+fn measure_energy<T, E>(
+    sensor: &mut Ina219<'_>,
+    label: &str,
+    data_size: usize,
+    mut state: T,
+    mut step: impl FnMut(T) -> Result<T, E>,
+) -> Result<T, E> {
+    let mut meter = EnergyMeter::default();
+    let mut busy_us = 0;
+    let mut bytes = 0;
 
-    // Ensure buffer_size doesn't exceed maximum
-    let actual_buffer_size = buffer_size.min(MAX_BUFFER_SIZE);
+    let start_time = Instant::now();
+    let mut last_sample = start_time;
+    while start_time.elapsed() < ENERGY_DURATION {
+        let step_start = Instant::now();
+        state = step(state)?;
+        busy_us += step_start.elapsed().as_micros();
+        bytes += data_size;
 
-    let keybuf = [0_u8; 32];
+        if last_sample.elapsed() >= ENERGY_SAMPLE_INTERVAL {
+            meter.sample(sensor);
+            last_sample = Instant::now();
+        }
+    }
 
-    debug!(
-        "AES DMA benchmark started with buffer size: {} bytes",
-        actual_buffer_size
+    let Some(watts) = meter.mean_power() else {
+        warn!(
+            "Energy, {label}, DataSize: {data_size}: no power samples ({} failed reads)",
+            meter.errors()
+        );
+        return Ok(state);
+    };
+    let throughput = bytes as f64 / busy_us as f64 * 1_000_000.0; // bytes per second
+    info!(
+        "Energy, {label}, DataSize: {data_size}, Power: {:.1} mW, Throughput: {}, Efficiency: {:.0} bytes/J, Samples: {}",
+        watts * 1000.0,
+        Throughput(throughput / 1_000_000.0),
+        throughput / watts,
+        meter.samples()
     );
+    Ok(state)
+}
 
-    // Benchmark the AES process call
-    let start_time = Instant::now();
-    const ITERATIONS: usize = 100; // Reduced iterations for larger buffers
-    for _ in 0..ITERATIONS {
-        let transfer = aes
-            .process(
-                actual_buffer_size / 16,
-                output,
-                input,
-                Mode::Encryption256,
-                CipherMode::Ctr,
-                keybuf,
-            )
-            .map_err(|e| e.0)
-            .unwrap();
-        (aes, output, input) = transfer.wait();
-    }
-    let elapsed = start_time.elapsed();
-
-    debug!(
-        "AES DMA process completed in {} microseconds for {} iterations",
-        elapsed.as_micros(),
-        ITERATIONS
-    );
-    debug!(
-        "Average time per iteration: {:.2} microseconds",
-        elapsed.as_micros() as f64 / ITERATIONS as f64
-    );
-    let data_processed = ITERATIONS * actual_buffer_size;
-    let throughput: f64 = data_processed as f64 / elapsed.as_micros() as f64 * 1_000_000.0; // bytes per second
-    debug!("Throughput: {:.2} MB/s", throughput / 1_000_000.0);
-    debug!("Throughput: {:.2} Mbps", throughput * 8.0 / 1_000_000.0);
-
-    (aes, throughput)
-}
-
-fn benchmark_sha256(sha: &mut Sha, data_sizes: &[usize]) {
-    let mut input = [0_u8; 32 * 1024]; // Maximum buffer size for SHA-256
-    input.fill(0xAB); // Fill with a pattern for testing
-    let mut output = [0_u8; 32]; // SHA-256 produces a 32-byte digest
-
-    // Pre-warm the SHA-256
-    benchmark_single_sha256(sha, &input, &mut output);
-
-    for &size in data_sizes {
-        let elapsed = benchmark_single_sha256(sha, &input[..size], &mut output);
+/// Measure the energy efficiency of AES-CTR at each data size.
+#[cfg(all(feature = "power-sensor", feature = "bench-aes"))]
+fn measure_aes_energy<'d>(
+    mut bench: aes::AesBench<'d>,
+    sensor: &mut Ina219<'_>,
+    data_sizes: &[usize],
+) -> aes::AesResult<'d> {
+    for &size in data_sizes {
+        bench = measure_energy(sensor, "AES-CTR", size, bench, |bench| {
+            bench.encrypt(size, || {})
+        })?;
+    }
+    Ok(bench)
+}
+
+/// Measure the energy efficiency of SHA-256 at each data size.
+#[cfg(all(feature = "power-sensor", feature = "bench-sha"))]
+fn measure_sha256_energy(
+    sha: &mut Sha,
+    sensor: &mut Ina219<'_>,
+    data_sizes: &[usize],
+) -> Result<(), BenchError> {
+    let input = [0xAB_u8; 32 * 1024];
+    let mut output = [0_u8; 32];
+
+    for &size in data_sizes {
+        measure_energy(sensor, "SHA-256", size, (), |()| {
+            sha::benchmark_single_sha256(sha, &input[..size], &mut output).map(|_| ())
+        })?;
+    }
+    Ok(())
+}
+
+/// Log the full matrix of benchmarks that would run, without touching any
+/// crypto peripheral.
+#[cfg_attr(
+    not(any(feature = "bench-aes", feature = "bench-sha")),
+    allow(
+        unused_variables,
+        reason = "only the AES and SHA benchmarks sweep data sizes"
+    )
+)]
+fn log_benchmark_plan(data_sizes: &[usize]) {
+    #[cfg(feature = "bench-aes")]
+    use esp32c6_crypto::benchmarks::aes::*;
+    #[cfg(feature = "soft-compare")]
+    use esp32c6_crypto::benchmarks::compare::*;
+    #[cfg(feature = "concurrent")]
+    use esp32c6_crypto::benchmarks::concurrent::*;
+    #[cfg(feature = "bench-ecc")]
+    use esp32c6_crypto::benchmarks::ecc::*;
+    #[cfg(feature = "hmac-efuse")]
+    use esp32c6_crypto::benchmarks::hmac::*;
+    #[cfg(feature = "bench-rng")]
+    use esp32c6_crypto::benchmarks::rng::*;
+    #[cfg(feature = "bench-rsa")]
+    use esp32c6_crypto::benchmarks::rsa::*;
+    #[cfg(feature = "bench-sha")]
+    use esp32c6_crypto::benchmarks::sha::*;
+    #[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+    use esp32c6_crypto::benchmarks::*;
+
+    info!("Dry run: planned benchmark matrix");
+    if cfg!(feature = "quick") {
+        #[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+        info!("  Known-answer tests of the AES-CTR DMA, SHA-256 and RSA-2048 paths");
+        #[cfg(feature = "bench-aes")]
+        info!(
+            "  AES-CTR (AES-256, DMA), DataSize: {QUICK_DATA_SIZE}, Iterations: {AES_ITERATIONS}"
+        );
+        #[cfg(feature = "bench-sha")]
+        info!("  SHA-256, DataSize: {QUICK_DATA_SIZE}, Iterations: {SHA_ITERATIONS}");
+        #[cfg(feature = "bench-rsa")]
+        info!("  RSA-2048 Modular Exponentiation (dummy exponent), Iterations: 1");
+        #[cfg(feature = "bench-rng")]
+        info!("  RNG (TRNG), DataSize: {RNG_BULK_SIZE} and {RNG_KEYGEN_SIZE}, Iterations: {RNG_ITERATIONS}");
+        return;
+    }
+    #[cfg(feature = "bench-rsa")]
+    if cfg!(feature = "rsa-stability") {
+        info!("  RSA-2048 Modular Exponentiation (dummy exponent), Iterations: {RSA_STABILITY_ITERATIONS}");
+        return;
+    }
+    #[cfg(feature = "bench-aes")]
+    info!("  Init: Aes::new, Aes::with_dma ({AES_DMA_CHANNEL})");
+    #[cfg(feature = "bench-sha")]
+    info!("  Init: Sha::new");
+    #[cfg(feature = "bench-rsa")]
+    info!("  Init: Rsa::new");
+    #[cfg(feature = "bench-rng")]
+    info!("  Init: Trng::new");
+    #[cfg(feature = "bench-ecc")]
+    info!("  Init: Ecc::new");
+    #[cfg(feature = "bench-aes")]
+    info!("  Cold start: AES-CTR, DataSize: {COLD_START_SIZE}, Warm iterations: {COLD_START_WARM_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  Cold start: SHA-256, DataSize: {COLD_START_SIZE}, Warm iterations: {COLD_START_WARM_ITERATIONS}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  Cold start: RSA-2048 (e = 65537), Warm iterations: {COLD_START_WARM_ITERATIONS}");
+    #[cfg(any(
+        feature = "bench-aes",
+        feature = "bench-sha",
+        feature = "bench-rsa",
+        feature = "bench-rng"
+    ))]
+    info!("  Cold path: init plus first result of each engine");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR DMA known-answer test (NIST SP 800-38A F.5.5 key, zero counter)");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 known-answer test (FIPS 180-2 \"abc\")");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 modexp known-answer test (4^13 mod 497 = 445)");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR IV known-answer test (NIST SP 800-38A F.5.5)");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-GCM known-answer test (GCM spec test case 16)");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CMAC known-answer test (RFC 4493 examples 1-4)");
+    #[cfg(feature = "soft-aes-check")]
+    info!("  AES-CTR software cross-check (hardware counter and CPU counter from IV), DataSize: {CROSS_CHECK_SIZE}");
+    #[cfg(all(feature = "bench-aes", not(feature = "target-ci")))]
+    for &size in data_sizes {
+        info!("  AES-CTR (AES-256, DMA), DataSize: {size}, Iterations: {AES_ITERATIONS}");
+    }
+    #[cfg(feature = "target-ci")]
+    for &size in data_sizes {
+        info!("  AES-CTR (AES-256, DMA), DataSize: {size}, Iterations: {MIN_ITERATIONS} to {MAX_ITERATIONS} until ±{TARGET_CI_PERCENT}% (95% CI), at most {MAX_TIME_MS} ms");
+    }
+    #[cfg(feature = "bench-aes")]
+    for &size in data_sizes {
+        info!(
+            "  memcpy (AES input to output buffer), DataSize: {size}, Iterations: {AES_ITERATIONS}"
+        );
+    }
+    #[cfg(feature = "soft-compare")]
+    for &size in data_sizes {
+        info!("  AES-CTR (software, aes crate), DataSize: {size}, Iterations: {SOFT_COMPARE_ITERATIONS}");
+    }
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR iteration count sweep, DataSize: {ITERATION_SWEEP_SIZE}, Iterations: {ITERATION_SWEEP_COUNTS:?}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR (sector), DataSize: {SECTOR_SIZE}, Iterations: {SECTOR_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!(
+        "  AES-CTR buffer boundary, Sizes: {}, {MAX_DMA_BUFFER_SIZE} and {} (at full buffers), Iterations: {AES_ITERATIONS}",
+        MAX_DMA_BUFFER_SIZE - 1,
+        MAX_DMA_BUFFER_SIZE + 1
+    );
+    #[cfg(feature = "bench-aes")]
+    info!("  Heap budget: extra DMA buffer pairs of {HEAP_PROBE_SIZES:?} bytes");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR reused vs per-call DMA buffers, Sizes: {BUFFER_REUSE_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "heap-pressure")]
+    info!("  AES-CTR buffer reuse with the heap nearly full, Sizes: {BUFFER_REUSE_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  Logger jitter: AES-CTR with logging deferred and interspersed, DataSize: {LOG_JITTER_SIZE}, Iterations: {LOG_JITTER_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    for &size in data_sizes {
+        info!("  AES-CTR (keystream, AES-256, DMA, zero input), DataSize: {size}, Iterations: {AES_ITERATIONS}");
+    }
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR output discarded vs retained, Sizes: {RETENTION_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR pre-filled DMA input vs copy then encrypt, Sizes: {PREFILLED_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR partial final block, Sizes: {PARTIAL_BLOCK_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR 96-bit nonce + 32-bit counter vs 128-bit counter, Sizes: {CTR32_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR {BLOB_SIZE} byte blob in DMA buffer chunks, hardware and continuous counter");
+    #[cfg(feature = "bench-aes")]
+    for &size in data_sizes {
+        info!("  AES-CFB8 and AES-CFB128 (AES-256, DMA), DataSize: {size}, Iterations: {AES_ITERATIONS}");
+    }
+    #[cfg(feature = "bench-aes")]
+    for &size in data_sizes {
+        info!(
+            "  AES-CBC and AES-ECB (AES-256, DMA), DataSize: {size}, Iterations: {AES_ITERATIONS}"
+        );
+    }
+    #[cfg(feature = "bench-aes")]
+    info!(
+        "  AES-CTR DMA completion polling, Sizes: {POLLING_SIZES:?}, Iterations: {AES_ITERATIONS}"
+    );
+    #[cfg(feature = "bench-aes")]
+    for &size in data_sizes {
+        info!("  AES-CMAC (AES-256, DMA), DataSize: {size}, Iterations: {AES_ITERATIONS}");
+    }
+    #[cfg(feature = "aes-cpu-load")]
+    for &size in data_sizes {
+        info!(
+            "  AES-CTR (AES-256, DMA, CPU loaded), DataSize: {size}, Iterations: {AES_ITERATIONS}"
+        );
+    }
+    #[cfg(all(feature = "interrupt-compare", feature = "bench-aes"))]
+    for &size in data_sizes {
+        info!("  AES-CTR (AES-256, DMA, interrupts enabled and masked), DataSize: {size}, Iterations: {AES_ITERATIONS}");
+    }
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR input patterns (zeros, ones, alternating, random), DataSize: {PATTERN_DATA_SIZE}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR at each DMA descriptor boundary ({CHUNK_SIZE} bytes per descriptor), Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR fixed key vs new key per call, Sizes: {REKEY_SIZES:?}, Keys: {}, Iterations: {REKEY_ITERATIONS}", aes::REKEY_KEYS.len());
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR rekey latency, DataSize: 16, Transitions: {REKEY_TRANSITIONS}, Settle: {REKEY_SETTLE_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR key alignment, Offsets: {KEY_ALIGNMENT_OFFSETS:?}, DataSize: 16, Iterations: {REKEY_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR with and without zeroizing key and buffers, Sizes: {ZEROIZE_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR scatter-gather vs contiguous, Size: {SCATTER_GATHER_SIZE}, Segments: {SCATTER_GATHER_SEGMENTS:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR DMA attached once vs per call, Sizes: {REATTACH_SIZES:?}, Iterations: {REATTACH_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR clock gating wake vs steady state, DataSize: {WAKE_SIZE}, Iterations: {WAKE_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR single block, DataSize: 16, Iterations: {SINGLE_BLOCK_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR DMA burst settings (off, TX, RX, TX+RX), Sizes: {BURST_SIZES:?}, Iterations: {AES_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    info!(
+        "  AES-CTR (AES-256, DMA), {BLOCKS_PER_CALL_TOTAL} bytes in calls of 1, 2, 4, ... blocks"
+    );
+    #[cfg(feature = "bench-aes")]
+    info!("  AES-CTR mixed workload (AES-256, DMA), Sizes: {MIXED_WORKLOAD:?} (size, weight), Rounds: {MIXED_WORKLOAD_ROUNDS}");
+    #[cfg(feature = "bench-aes")]
+    info!("  TLS 1.3 record (AES-256-GCM), Plaintext: {TLS_RECORD_SIZE}, Iterations: {TLS_RECORD_ITERATIONS}");
+    #[cfg(feature = "bench-aes")]
+    for aad_size in aes::GCM_AAD_SIZES {
+        info!("  AES-GCM (AES-256), AAD: {aad_size}, Payload: {GCM_PAYLOAD_SIZE}, Iterations: {GCM_ITERATIONS}");
+    }
+    #[cfg(feature = "bench-aes")]
+    info!(
+        "  AES-CTR stream, Ring: {STREAM_SLOTS} x {STREAM_SLOT_SIZE}, Duration: {} ms",
+        aes::STREAM_DURATION.as_millis()
+    );
+    #[cfg(all(feature = "bench-aes", feature = "bench-sha"))]
+    for &size in data_sizes {
+        info!("  AES-CTR + HMAC-SHA256 (encrypt-then-MAC), DataSize: {size}, Iterations: {ETM_ITERATIONS}");
+    }
+    #[cfg(all(feature = "bench-aes", feature = "bench-sha"))]
+    info!("  AES-CTR then SHA-256 of the ciphertext (store with integrity), Sizes: {INTEGRITY_SIZES:?}, Iterations: {INTEGRITY_ITERATIONS}");
+    #[cfg(all(feature = "bench-aes", feature = "bench-sha"))]
+    info!("  AES-CTR <-> SHA-256 peripheral switching, DataSize: {SWITCH_DATA_SIZE}, Iterations: {SWITCH_ITERATIONS}");
+    #[cfg(all(feature = "bench-aes", feature = "bench-sha"))]
+    info!("  AES-CTR + SHA-256 sequential vs in parallel, Sizes: {PARALLEL_SIZES:?}, Iterations: {PARALLEL_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    for &size in data_sizes {
+        info!("  SHA-256, DataSize: {size}, Iterations: {SHA_ITERATIONS}");
+    }
+    #[cfg(feature = "soft-blake3")]
+    for &size in data_sizes {
+        info!("  BLAKE3 (software), DataSize: {size}, Iterations: {BLAKE3_ITERATIONS}");
+    }
+    #[cfg(feature = "soft-compare")]
+    for &size in data_sizes {
+        info!("  SHA-256 (software, sha2 crate), DataSize: {size}, Iterations: {SOFT_COMPARE_ITERATIONS}");
+    }
+    #[cfg(all(feature = "interrupt-compare", feature = "bench-sha"))]
+    for &size in data_sizes {
+        info!("  SHA-256 (interrupts enabled and masked), DataSize: {size}, Iterations: 1");
+    }
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 {SMALL_MESSAGE_COUNT} x {SMALL_MESSAGE_SIZE} byte messages vs one message, Iterations: 1");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 maximum update size probe, then {UPDATE_SIZE_MESSAGE} bytes in updates of that size, one slice and 1 KB chunks");
+    #[cfg(feature = "bench-sha")]
+    info!(
+        "  SHA-256 padding boundaries, Sizes: {:?}, Iterations: {PADDING_ITERATIONS}",
+        sha::PADDING_VECTORS.map(|(size, _)| size)
+    );
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 2 interleaved streams (context save/restore) vs serial, Updates: {CONTEXT_CHUNKS} x {CONTEXT_CHUNK_SIZE} bytes per stream");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 context save and restore, Iterations: {CONTEXT_SAVE_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 {PREFIX_SIZE} byte prefix + data, split vs concatenated, Sizes: {PREFIX_DATA_SIZES:?}, Iterations: {PREFIX_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 contiguous vs scatter-gather, DataSize: {SHA_SCATTER_SIZE}, Fragments: {SHA_SCATTER_FRAGMENTS:?}, Iterations: {SHA_SCATTER_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 TLS transcript, Sizes: {TLS_TRANSCRIPT_SIZES:?}, Iterations: {TLS_TRANSCRIPT_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  HMAC-SHA256 key reuse, Sizes: {HMAC_KEY_REUSE_SIZES:?}, Iterations: {HMAC_KEY_REUSE_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 batch of independent digests, Sizes: {BATCH_DIGEST_SIZES:?}, Digests: {BATCH_DIGESTS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 stream start, start() and first update of {STREAM_FIRST_UPDATE_SIZES:?} bytes, Iterations: {STREAM_START_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 {BLOB_SIZE} byte blob in 32 KB chunks");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 clock gating wake vs steady state, DataSize: {WAKE_SIZE}, Iterations: {WAKE_ITERATIONS}");
+    #[cfg(feature = "bench-sha")]
+    info!("  SHA-256 finish to stack, heap, SRAM and LP SRAM, DataSize: 64, Iterations: {FINISH_ITERATIONS}");
+    #[cfg(feature = "sha-flash")]
+    for &size in data_sizes {
+        info!("  SHA-256 (flash: RAM, mapped, read), DataSize: {size}, Iterations: 1");
+    }
+    #[cfg(feature = "bench-ecc")]
+    info!("  ECC point multiplication (P-192, P-256), Iterations: {ECC_ITERATIONS}");
+    #[cfg(feature = "bench-ecc")]
+    info!("  ECDSA P-256 sign and verify (RFC 6979 A.2.5), Iterations: {ECC_ITERATIONS}");
+    #[cfg(feature = "bench-rng")]
+    info!("  RNG (TRNG), DataSize: {RNG_BULK_SIZE}, Iterations: {RNG_ITERATIONS}");
+    #[cfg(feature = "bench-rng")]
+    info!("  RNG (RSA-2048 key generation), DataSize: {RNG_KEYGEN_SIZE}, Iterations: {RNG_ITERATIONS}");
+    #[cfg(feature = "bench-rng")]
+    info!("  RNG steady state, DataSize: {RNG_KEYGEN_SIZE}, Iterations: {RNG_STEADY_ITERATIONS}");
+    #[cfg(feature = "hmac-efuse")]
+    info!("  HMAC upstream (ToUser), Key: {HMAC_KEY_ID:?}, DataSize: 32, Iterations: {HMAC_ITERATIONS}");
+    #[cfg(feature = "hmac-efuse")]
+    info!("  HMAC downstream (ToJtag, ToDs) configure, Key: {HMAC_KEY_ID:?}");
+    #[cfg(feature = "hmac-efuse")]
+    for &size in data_sizes {
+        info!("  HMAC-SHA256 (peripheral), Key: {HMAC_KEY_ID:?}, DataSize: {size}, Iterations: {HMAC_SWEEP_ITERATIONS}");
+    }
+    #[cfg(all(feature = "throughput-profile", feature = "bench-aes"))]
+    info!(
+        "  AES-CTR throughput over time, DataSize: {PROFILE_DATA_SIZE}, Duration: {} s, Interval: {} ms",
+        PROFILE_DURATION.as_secs(),
+        PROFILE_INTERVAL.as_millis()
+    );
+    #[cfg(all(feature = "throughput-profile", feature = "bench-sha"))]
+    info!(
+        "  SHA-256 throughput over time, DataSize: {PROFILE_DATA_SIZE}, Duration: {} s, Interval: {} ms",
+        PROFILE_DURATION.as_secs(),
+        PROFILE_INTERVAL.as_millis()
+    );
+    #[cfg(all(feature = "power-sensor", feature = "bench-aes"))]
+    for &size in data_sizes {
         info!(
-            "SHA-256, DataSize: {size}, Time: {} us",
-            elapsed.as_micros()
+            "  AES-CTR energy (INA219), DataSize: {size}, Duration: {} ms",
+            ENERGY_DURATION.as_millis()
         );
     }
+    #[cfg(all(feature = "power-sensor", feature = "bench-sha"))]
+    for &size in data_sizes {
+        info!(
+            "  SHA-256 energy (INA219), DataSize: {size}, Duration: {} ms",
+            ENERGY_DURATION.as_millis()
+        );
+    }
+    #[cfg(feature = "bench-rsa")]
+    info!(
+        "  RSA-2048 Modular Exponentiation (dummy, private and e = 65537 exponents), Iterations: 1"
+    );
+    #[cfg(feature = "soft-compare")]
+    info!("  RSA-2048 Modular Exponentiation (dummy exponent, software, crypto-bigint), Iterations: 1");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-1024/2048/3072 Uint word conversions, Iterations: {RSA_CONVERSION_ITERATIONS}");
+    #[cfg(feature = "bench-rsa")]
+    for bits in rsa::RSA_EXPONENT_BITS {
+        info!("  RSA-2048 Modular Exponentiation ({bits}-bit exponent), Iterations: 1");
+    }
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-32/512/1024/2048 floor (e = {RSA_PUBLIC_EXPONENT}), Iterations: {RSA_FLOOR_ITERATIONS}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 verify, e = {RSA_VERIFY_EXPONENTS:?}, Iterations: {RSA_FLOOR_ITERATIONS}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 verify with byte I/O, Iterations: {RSA_MARSHAL_ITERATIONS}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 key setup and exponentiation, Keys: {RSA_KEY_COUNT}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 private-key operation, CRT (2 x RSA-1024) and full modulus");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 batch verification (e = 65537), Signatures: {RSA_BATCH_SIZE}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 steady-state verification (e = 65537), Warmup: {RSA_STEADY_WARMUP}, Iterations: {RSA_STEADY_ITERATIONS}");
+    #[cfg(feature = "bench-rsa")]
+    info!("  RSA-2048 blinded private-key operation and unblinded");
+    #[cfg(all(feature = "interrupt-compare", feature = "bench-rsa"))]
+    info!("  RSA-2048 Modular Exponentiation (interrupts enabled and masked), Iterations: 1");
+    #[cfg(all(feature = "bench-sha", feature = "bench-rsa"))]
+    info!(
+        "  RSA-2048 hash-then-sign (SHA-256, PKCS#1 v1.5, CRT), Message sizes: {SIGN_MESSAGE_SIZES:?}"
+    );
+    #[cfg(all(feature = "bench-sha", feature = "bench-rsa"))]
+    info!("  Signed firmware verification (SHA-256, RSA-2048), Image: {FIRMWARE_IMAGE_SIZE}");
+    #[cfg(all(feature = "bench-sha", feature = "bench-rsa"))]
+    info!("  SHA-256 + RSA-2048 sign, sequential and overlapped, Transcript sizes: {OVERLAP_HASH_SIZES:?}, Iterations: {OVERLAP_ITERATIONS}");
+    #[cfg(feature = "concurrent")]
+    info!(
+        "  Concurrent engines (AES-CTR, SHA-256, RSA-2048), alone and joined, DataSize: {CONCURRENT_SIZE}, Iterations: {CONCURRENT_ITERATIONS:?}"
+    );
 }
 
-fn benchmark_single_sha256(sha: &mut Sha, input: &[u8], output: &mut [u8]) -> Duration {
-    let start_time = Instant::now();
-    let mut digest = sha.start::<Sha256>();
-    digest.update(input).unwrap();
-    digest.finish(output).unwrap();
-    start_time.elapsed()
+/// Message size of the SHA-256 digest the RSA-2048 signing time is compared
+/// with, a typical signed message.
+#[cfg(any(
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+const SIGN_RATIO_HASH_SIZE: usize = 256;
+
+/// Log the results recorded during the run in the machine-readable formats
+/// selected by the `csv-output`, `json-output` and `prometheus-output`
+/// features, prefixed with `CSV: `, `JSON: ` and `PROM: ` so a host script
+/// can pick the lines out of the log, and with `baseline` their change
+/// against the baselines. The Prometheus lines come grouped by [`Metric`],
+/// each group after its `# TYPE` line, as the exposition format requires.
+///
+/// The results come out sorted by label, data size and iteration count, so
+/// the lines of two runs line up for `diff`. The AES-CTR, AES-CBC and AES-ECB
+/// results then follow as one [`ModeRow`] per data size, and the RSA-2048
+/// private-key exponentiation as a multiple of a [`SIGN_RATIO_HASH_SIZE`]
+/// byte SHA-256 digest.
+#[cfg(any(
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+fn log_results() {
+    output::banner("Results");
+    #[cfg(feature = "csv-output")]
+    info!("CSV: {CSV_HEADER}");
+    #[cfg(feature = "flash-results")]
+    let mut csv = String::from(CSV_HEADER) + "\n";
+    #[cfg(feature = "prometheus-output")]
+    info!("PROM: {}", Metric::TimeUs.type_line());
+    #[cfg(feature = "prometheus-output")]
+    let mut throughputs: Vec<String> = Vec::new();
+    let mut modes: Vec<ModeRow> = Vec::new();
+    let (mut sign_us, mut hash_us) = (None, None);
+    results::drain_sorted(|result| {
+        match (result.algorithm, result.data_size) {
+            ("RSA-2048 (private exponent)", _) => sign_us = Some(result.time_us),
+            ("SHA-256", SIGN_RATIO_HASH_SIZE) => hash_us = Some(result.time_us),
+            _ => {}
+        }
+        if let Some(column) = ModeRow::column(result.algorithm) {
+            let row = match modes
+                .iter()
+                .position(|row| row.data_size == result.data_size)
+            {
+                Some(index) => &mut modes[index],
+                None => {
+                    modes.push(ModeRow::new(result.data_size));
+                    modes.last_mut().unwrap()
+                }
+            };
+            row.throughputs[column] = Some(result.throughput_mb_per_s());
+        }
+        #[cfg(feature = "csv-output")]
+        info!("CSV: {}", Csv(result));
+        #[cfg(feature = "json-output")]
+        info!("JSON: {}", Json(result));
+        #[cfg(feature = "prometheus-output")]
+        {
+            info!("PROM: {}", Prometheus(result, Metric::TimeUs));
+            throughputs.push(Prometheus(result, Metric::ThroughputMbps).to_string());
+        }
+        #[cfg(feature = "flash-results")]
+        writeln!(csv, "{}", Csv(result)).unwrap();
+        #[cfg(feature = "baseline")]
+        baseline::report(result);
+    });
+    #[cfg(feature = "prometheus-output")]
+    {
+        info!("PROM: {}", Metric::ThroughputMbps.type_line());
+        for line in &throughputs {
+            info!("PROM: {line}");
+        }
+    }
+    modes.sort_unstable_by_key(|row| row.data_size);
+    for row in &modes {
+        info!("AES modes, {row}");
+    }
+    if let (Some(sign_us), Some(hash_us)) = (sign_us, hash_us.filter(|&us| us > 0.0)) {
+        info!(
+            "RSA-2048 sign ≈ {:.0}× a {SIGN_RATIO_HASH_SIZE}-byte SHA-256 ({} / {})",
+            sign_us / hash_us,
+            Elapsed(sign_us),
+            Elapsed(hash_us)
+        );
+    }
+    #[cfg(feature = "flash-results")]
+    match flash_results::store(csv.as_bytes()) {
+        Ok(stored) if stored < csv.len() => {
+            warn!("Flash results truncated to {stored} of {} bytes", csv.len())
+        }
+        Ok(stored) => info!(
+            "Stored {stored} bytes of results in flash at {:#x}",
+            flash_results::OFFSET
+        ),
+        Err(e) => error!("Storing results in flash failed: {e:?}"),
+    }
 }
 
-fn timestamp_overhead() -> Duration {
-    // Measure the overhead of timestamping
-    let start_time = Instant::now();
-    start_time.elapsed()
-}
-
-fn benchmark_rsa(mut rsa: Rsa<'_, esp_hal::Blocking>) {
-    // Dummy values for RSA modular exponentiation
-    // These values are not secure and should not be used in production.
-    // They are only for benchmarking purposes.
-    // The values are chosen to be large enough to fit in 2048 bits.
-    const BIGNUM_1: U2048 = Uint::from_be_hex(
-        "c7f61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b71\
-        9d8ca980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878c\
-        7f61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b719d\
-        8ca980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878c7f\
-        61058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b719d8c\
-        a980b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878c7f61\
-        058f96db3bd87dbab08ab03b4f7f2f864eac249144adea6a65f97803b719d8ca9\
-        80b7b3c0389c1c7c67dc353c5e0ec11f5fc8ce7f6073796cc8f73fa878",
-    );
-    const BIGNUM_2: U2048 = Uint::from_be_hex(
-        "1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
-        fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
-        1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
-        fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
-        1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
-        fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51\
-        1763db3344e97be15d04de4868badb12a38046bb793f7630d87cf100aa1c759a\
-        fac15a01f3c4c83ec2d2f666bd22f71c3c1f075ec0e2cb0cb29994d091b73f51",
-    );
-    const BIGNUM_3: U2048 = Uint::from_be_hex(
-        "6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
-        df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
-        6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
-        df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
-        6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
-        df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361\
-        6b6bb3d2b6cbeb45a769eaa0384e611e1b89b0c9b45a045aca1c5fd6e8785b38\
-        df7118cf5dd45b9b63d293b67aeafa9ba25feb8712f188cb139b7d9b9af1c361",
-    );
-
-    let r: U2048 = Uint::MAX;
-
-    let mut outbuf = [0_u32; U2048::LIMBS];
-    let mut mod_exp = RsaModularExponentiation::<Op2048, _>::new(
-        &mut rsa,
-        BIGNUM_2.as_words(),
-        BIGNUM_3.as_words(),
-        u32::MAX - 1,
-    );
+/// How long after reset [`abort::DUMP_BYTE`] prints the results stored in
+/// flash instead of running the benchmarks.
+#[cfg(feature = "flash-results")]
+const DUMP_COMMAND_WINDOW: Duration = Duration::from_secs(3);
 
-    let start_time = Instant::now();
-    mod_exp.start_exponentiation(BIGNUM_1.as_words(), r.as_words());
-    mod_exp.read_results(&mut outbuf);
-    let elapsed = start_time.elapsed();
+/// Wait up to [`DUMP_COMMAND_WINDOW`] for [`abort::DUMP_BYTE`].
+#[cfg(feature = "flash-results")]
+fn wait_for_dump_command() -> bool {
+    let start = Instant::now();
+    while start.elapsed() < DUMP_COMMAND_WINDOW {
+        if abort::take_dump_request() {
+            return true;
+        }
+    }
+    false
+}
 
-    info!(
-        "RSA-2048 Modular Exponentiation completed in {} miliseconds",
-        elapsed.as_millis()
-    );
+/// Print the results stored in flash if [`abort::DUMP_BYTE`] was received
+/// since the last check. Called between runs and while idle, so a dump
+/// requested during a run comes after it, with that run's results.
+#[cfg(feature = "flash-results")]
+fn serve_dump_request() {
+    if abort::take_dump_request() {
+        log_flash_results();
+    }
+}
+
+/// Log the results stored in flash by a previous run, one CSV line each.
+#[cfg(feature = "flash-results")]
+fn log_flash_results() {
+    match flash_results::load() {
+        Ok(Some(csv)) => {
+            for line in String::from_utf8_lossy(&csv).lines() {
+                info!("FLASH: {line}");
+            }
+        }
+        Ok(None) => warn!("No results stored in flash"),
+        Err(e) => error!("Reading results from flash failed: {e:?}"),
+    }
+}
+
+/// Result-style log lines emitted by [`log_overhead`].
+const LOG_OVERHEAD_ITERATIONS: u32 = 20;
+
+/// Time formatting and emitting a log line like the benchmark results,
+/// averaged over [`LOG_OVERHEAD_ITERATIONS`] lines that end up in the log.
+fn log_overhead() -> Elapsed {
+    let start_time = Instant::now();
+    for i in 0..LOG_OVERHEAD_ITERATIONS {
+        info!(
+            "Log overhead sample {i}, DataSize: 1024, Time: {}, Throughput: {:.2} MB/s",
+            Elapsed(50.52),
+            1024.0 / 50.52
+        );
+    }
+    Elapsed(start_time.elapsed().as_micros() as f64 / LOG_OVERHEAD_ITERATIONS as f64)
 }
 
 #[main]
 fn main() -> ! {
     // generator version: 0.4.0
-    esp_println::logger::init_logger_from_env();
+    output::init();
 
     let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
     let peripherals = esp_hal::init(config);
 
     info!("Setting up Heap Allocator");
 
-    esp_alloc::heap_allocator!(size: 64 * 1024);
+    esp_alloc::heap_allocator!(size: 96 * 1024);
+
+    #[cfg(any(feature = "flash-results", feature = "serial-abort"))]
+    abort::install(UsbSerialJtag::new(peripherals.USB_DEVICE));
+    #[cfg(feature = "flash-results")]
+    {
+        info!(
+            "Send '{}' within {} s to print the results stored in flash instead of running, or at any time to print them after the run",
+            abort::DUMP_BYTE as char,
+            DUMP_COMMAND_WINDOW.as_secs()
+        );
+        if wait_for_dump_command() {
+            log_flash_results();
+            idle();
+        }
+    }
+    #[cfg(feature = "serial-abort")]
+    info!("Send Ctrl-C or 'q' to abort a benchmark run");
 
-    let _ = timestamp_overhead(); // Pre-warm the timestamping
-    let overhead = timestamp_overhead();
-    info!("Timestamp overhead: {} us", overhead.as_micros());
+    let overhead = benchmarks::timestamp_overhead();
+    info!(
+        "Timestamp overhead: {}",
+        Elapsed::from_micros(overhead.as_micros())
+    );
+    let per_line = log_overhead();
+    info!("Log overhead: {per_line} per info! line (mean of {LOG_OVERHEAD_ITERATIONS})");
 
     let data_sizes = [
         64,
@@ -221,22 +989,1408 @@ fn main() -> ! {
         32 * 1024,
     ];
 
-    info!("Starting AES-CTR DMA Benchmark");
-    let aes = Aes::new(peripherals.AES).with_dma(peripherals.DMA_CH0);
-    benchmark_aes_dma(aes, &data_sizes);
+    if cfg!(feature = "dry-run") {
+        log_benchmark_plan(&data_sizes);
+        idle();
+    }
+
+    info!("Measuring peripheral initialization");
+    #[cfg(feature = "bench-aes")]
+    let (aes, aes_init) = benchmarks::timed(|| Aes::new(peripherals.AES));
+    #[cfg(feature = "bench-aes")]
+    let (aes, aes_dma_init) = benchmarks::timed(|| {
+        #[cfg(not(any(feature = "aes-dma-ch1", feature = "aes-dma-ch2")))]
+        let channel = peripherals.DMA_CH0;
+        #[cfg(feature = "aes-dma-ch1")]
+        let channel = peripherals.DMA_CH1;
+        #[cfg(feature = "aes-dma-ch2")]
+        let channel = peripherals.DMA_CH2;
+        aes.with_dma(channel)
+    });
+    #[cfg(feature = "bench-sha")]
+    let (sha, sha_init) = benchmarks::timed(|| Sha::new(peripherals.SHA));
+    #[cfg(feature = "bench-rsa")]
+    let (rsa, rsa_init) = benchmarks::timed(|| Rsa::new(peripherals.RSA));
+    #[cfg(feature = "bench-rng")]
+    let (rng, rng_init) = benchmarks::timed(|| Trng::new(peripherals.RNG, peripherals.ADC1));
+    #[cfg(feature = "bench-ecc")]
+    let (ecc, ecc_init) = benchmarks::timed(|| Ecc::new(peripherals.ECC));
+    #[cfg(feature = "bench-aes")]
+    {
+        info!(
+            "Init, Aes::new: {}",
+            Elapsed::from_micros(aes_init.as_micros())
+        );
+        info!(
+            "Init, Aes::with_dma ({AES_DMA_CHANNEL}): {}",
+            Elapsed::from_micros(aes_dma_init.as_micros())
+        );
+    }
+    #[cfg(feature = "bench-sha")]
+    info!(
+        "Init, Sha::new: {}",
+        Elapsed::from_micros(sha_init.as_micros())
+    );
+    #[cfg(feature = "bench-rsa")]
+    info!(
+        "Init, Rsa::new: {}",
+        Elapsed::from_micros(rsa_init.as_micros())
+    );
+    #[cfg(feature = "bench-rng")]
+    info!(
+        "Init, Trng::new: {}",
+        Elapsed::from_micros(rng_init.as_micros())
+    );
+    #[cfg(feature = "bench-ecc")]
+    info!(
+        "Init, Ecc::new: {}",
+        Elapsed::from_micros(ecc_init.as_micros())
+    );
+
+    #[cfg(feature = "bench-aes")]
+    let aes = Some(
+        aes::AesBench::allocate(aes, MAX_DMA_BUFFER_SIZE).unwrap_or_else(|error| panic!("{error}")),
+    );
 
-    info!("Starting SHA256 Benchmark");
-    let mut sha = Sha::new(peripherals.SHA);
-    benchmark_sha256(&mut sha, &data_sizes);
+    // GPIO6 (SDA) and GPIO7 (SCL) are free on the ESP32-C6-DevKitC-1; change
+    // the pins here to match the sensor wiring.
+    #[cfg(all(
+        feature = "power-sensor",
+        any(feature = "bench-aes", feature = "bench-sha")
+    ))]
+    let power = Ina219::new(
+        I2c::new(
+            peripherals.I2C0,
+            i2c::master::Config::default().with_frequency(Rate::from_khz(400)),
+        )
+        .unwrap()
+        .with_sda(peripherals.GPIO6)
+        .with_scl(peripherals.GPIO7),
+    );
 
-    info!("Starting RSA Benchmark");
-    let rsa = Rsa::new(peripherals.RSA);
-    benchmark_rsa(rsa);
+    let drivers = Drivers {
+        #[cfg(feature = "bench-aes")]
+        aes,
+        #[cfg(feature = "bench-sha")]
+        sha,
+        #[cfg(feature = "bench-rsa")]
+        rsa,
+        #[cfg(feature = "bench-rng")]
+        rng,
+        #[cfg(feature = "bench-ecc")]
+        ecc,
+        #[cfg(all(
+            feature = "power-sensor",
+            any(feature = "bench-aes", feature = "bench-sha")
+        ))]
+        power,
+        #[cfg(feature = "hmac-efuse")]
+        hmac: Hmac::new(peripherals.HMAC),
+        #[cfg(any(
+            feature = "bench-aes",
+            feature = "bench-sha",
+            feature = "bench-rsa",
+            feature = "bench-rng"
+        ))]
+        init_times: InitTimes {
+            #[cfg(feature = "bench-aes")]
+            aes: aes_init + aes_dma_init,
+            #[cfg(feature = "bench-sha")]
+            sha: sha_init,
+            #[cfg(feature = "bench-rsa")]
+            rsa: rsa_init,
+            #[cfg(feature = "bench-rng")]
+            rng: rng_init,
+        },
+        _lifetime: PhantomData,
+    };
 
-    loop {
-        let delay_start = Instant::now();
-        while delay_start.elapsed() < Duration::from_millis(500) {}
+    #[cfg(feature = "button-trigger")]
+    {
+        // GPIO9 is the BOOT button on the ESP32-C6-DevKitC-1. Change the pin
+        // here (and in the message below) to use a different button.
+        let button = Input::new(
+            peripherals.GPIO9,
+            InputConfig::default().with_pull(Pull::Up),
+        );
+        let mut drivers = drivers;
+        loop {
+            info!("Press the button on GPIO9 to run the benchmarks");
+            wait_for_button_press(&button);
+            drivers = run_benchmarks(drivers, &data_sizes);
+        }
+    }
+
+    #[cfg(not(feature = "button-trigger"))]
+    {
+        #[cfg_attr(not(feature = "serial-abort"), allow(unused_mut))]
+        let mut drivers = run_benchmarks(drivers, &data_sizes);
+        #[cfg(feature = "serial-abort")]
+        while benchmarks::aborted() && wait_for_restart() {
+            drivers = run_benchmarks(drivers, &data_sizes);
+        }
+        teardown(drivers);
+        idle()
     }
 
     // for inspiration have a look at the examples at https://github.com/esp-rs/esp-hal/tree/esp-hal-v1.0.0-beta.1/examples/src/bin
 }
+
+/// Release the drivers and the DMA buffers after the last run, so nothing
+/// that a transfer could use is held across [`idle`].
+///
+/// Every benchmark waits for its transfers to complete, so none is in flight
+/// here.
+#[cfg(not(feature = "button-trigger"))]
+fn teardown(drivers: Drivers<'_>) {
+    #[cfg(feature = "bench-aes")]
+    drop(drivers.aes.map(aes::AesBench::release));
+    #[cfg(not(feature = "bench-aes"))]
+    drop(drivers);
+    info!(
+        "Released the drivers, heap in use: {} bytes",
+        esp_alloc::HEAP.used()
+    );
+}
+
+/// The drivers of the benchmarks compiled into this build.
+struct Drivers<'d> {
+    /// `None` once a transfer hung and there was no heap left to attach the
+    /// AES engine again.
+    #[cfg(feature = "bench-aes")]
+    aes: Option<aes::AesBench<'d>>,
+    #[cfg(feature = "bench-sha")]
+    sha: Sha<'d>,
+    #[cfg(feature = "bench-rsa")]
+    rsa: Rsa<'d, Blocking>,
+    #[cfg(feature = "bench-rng")]
+    rng: Trng<'d>,
+    #[cfg(feature = "bench-ecc")]
+    ecc: Ecc<'d, Blocking>,
+    #[cfg(all(
+        feature = "power-sensor",
+        any(feature = "bench-aes", feature = "bench-sha")
+    ))]
+    power: Ina219<'d>,
+    #[cfg(feature = "hmac-efuse")]
+    hmac: Hmac<'d>,
+    #[cfg(any(
+        feature = "bench-aes",
+        feature = "bench-sha",
+        feature = "bench-rsa",
+        feature = "bench-rng"
+    ))]
+    init_times: InitTimes,
+    _lifetime: PhantomData<&'d ()>,
+}
+
+/// How long creating each driver took at boot, the first half of the cold
+/// path latencies.
+#[cfg(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "bench-rsa",
+    feature = "bench-rng"
+))]
+#[derive(Clone, Copy)]
+struct InitTimes {
+    /// `Aes::new` and `Aes::with_dma`.
+    #[cfg(feature = "bench-aes")]
+    aes: Duration,
+    #[cfg(feature = "bench-sha")]
+    sha: Duration,
+    #[cfg(feature = "bench-rsa")]
+    rsa: Duration,
+    #[cfg(feature = "bench-rng")]
+    rng: Duration,
+}
+
+/// Bytes read from the RNG as the first operation of its cold path, one
+/// AES-256 key.
+#[cfg(feature = "bench-rng")]
+const COLD_PATH_RNG_SIZE: usize = 32;
+
+/// Log the cold path latency of `label`: creating the driver at boot
+/// (`init`) plus the first operation over `size` bytes (`first_us`), the
+/// time a cold boot that needs exactly one such operation waits for its
+/// result.
+#[cfg(any(
+    feature = "bench-aes",
+    feature = "bench-sha",
+    feature = "bench-rsa",
+    feature = "bench-rng"
+))]
+fn log_cold_path(label: &str, size: usize, init: Duration, first_us: u64) {
+    info!(
+        "Cold path, {label}, DataSize: {size}, Init: {}, First result: {}, Total: {}",
+        Elapsed::from_micros(init.as_micros()),
+        Elapsed::from_micros(first_us),
+        Elapsed::from_micros(init.as_micros() + first_us)
+    );
+}
+
+/// Log the start of `benchmark` and record it as in progress.
+fn starting(benchmark: &'static str) {
+    info!("Starting {benchmark}");
+    benchmarks::set_current_benchmark(benchmark, None);
+    check_cpu_clock(benchmark);
+}
+
+/// CPU frequency the suite configures at boot, in kHz.
+const CONFIGURED_CPU_KHZ: u32 = CpuClock::max() as u32 * 1000;
+
+/// `khz` in MHz, for logging.
+fn mhz(khz: u32) -> f64 {
+    khz as f64 / 1000.0
+}
+
+/// Sample the CPU frequency before `benchmark` and warn if it is not the
+/// configured [`CpuClock::max`], since the timings would then not compare
+/// with the rest of the run.
+fn check_cpu_clock(benchmark: &'static str) {
+    match clock_monitor::sample() {
+        Some(khz) if khz != CONFIGURED_CPU_KHZ => warn!(
+            "CPU clock at {} MHz instead of {} MHz before {benchmark}",
+            mhz(khz),
+            mhz(CONFIGURED_CPU_KHZ)
+        ),
+        Some(_) => {}
+        None => warn!("CPU clock source unknown before {benchmark}"),
+    }
+}
+
+/// Log the range of CPU frequencies sampled during the run.
+fn log_cpu_clock_range() {
+    let Some(range) = clock_monitor::range() else {
+        return;
+    };
+    if range.is_steady_at(CONFIGURED_CPU_KHZ) {
+        info!("CPU clock steady at {} MHz", mhz(CONFIGURED_CPU_KHZ));
+    } else {
+        warn!(
+            "CPU clock drifted during the run: {}..{} MHz, configured {} MHz; timings are not comparable",
+            mhz(range.min_khz),
+            mhz(range.max_khz),
+            mhz(CONFIGURED_CPU_KHZ)
+        );
+    }
+}
+
+/// The bench an AES benchmark handed back, logging its error if it failed.
+#[cfg(feature = "bench-aes")]
+fn recover(result: aes::AesResult<'_>) -> Option<aes::AesBench<'_>> {
+    result.map(Some).unwrap_or_else(|(error, bench)| {
+        benchmarks::failed(error);
+        if bench.is_none() {
+            error!("No AES driver left, skipping the remaining AES benchmarks");
+        }
+        bench
+    })
+}
+
+/// Run [`aes::benchmark_aes_dma`] on the AES bench, unless a hung transfer
+/// lost it, and return the throughput for each of `data_sizes`, or none if it
+/// failed.
+#[cfg(feature = "bench-aes")]
+fn run_aes_dma(bench: &mut Option<aes::AesBench<'_>>, data_sizes: &[usize]) -> Vec<f64> {
+    let Some(taken) = bench.take() else {
+        return Vec::new();
+    };
+    info!("Starting AES-CTR DMA Benchmark ({AES_DMA_CHANNEL})");
+    benchmarks::set_current_benchmark("AES-CTR DMA Benchmark", None);
+    match aes::benchmark_aes_dma(taken, data_sizes) {
+        Ok((taken, throughputs)) => {
+            *bench = Some(taken);
+            throughputs
+        }
+        Err(failure) => {
+            *bench = recover(Err(failure));
+            Vec::new()
+        }
+    }
+}
+
+/// Start `benchmark` and run it with `run` on the AES bench, unless a hung
+/// transfer lost the bench, keeping the one it hands back.
+#[cfg(feature = "bench-aes")]
+fn run_aes<'d>(
+    bench: &mut Option<aes::AesBench<'d>>,
+    benchmark: &'static str,
+    run: impl FnOnce(aes::AesBench<'d>) -> aes::AesResult<'d>,
+) {
+    if let Some(taken) = bench.take() {
+        starting(benchmark);
+        *bench = recover(run(taken));
+    }
+}
+
+/// Log the error of a SHA or RSA benchmark that failed.
+#[cfg(any(feature = "bench-sha", feature = "bench-rsa"))]
+fn check(result: Result<(), BenchError>) {
+    if let Err(error) = result {
+        benchmarks::failed(error);
+    }
+}
+
+/// Hook of the `esp-backtrace` panic and exception handlers, called before
+/// they print the message and backtrace: names the benchmark in progress,
+/// e.g. `panicked during AES-CTR size=1024`.
+#[no_mangle]
+fn custom_pre_backtrace() {
+    let (benchmark, size) = benchmarks::current_benchmark();
+    match size {
+        Some(size) => esp_println::println!("panicked during {benchmark} size={size}"),
+        None => esp_println::println!("panicked during {benchmark}"),
+    }
+}
+
+/// Run the benchmarks once with the drivers created at boot, log the wall
+/// time from the first benchmark to the last and the collected results.
+///
+/// With the `serial-abort` feature an abort request ends the run early, after
+/// the benchmark in progress, and the results so far are still logged.
+fn run_benchmarks<'d>(drivers: Drivers<'d>, data_sizes: &[usize]) -> Drivers<'d> {
+    #[cfg(feature = "serial-abort")]
+    abort::clear();
+    clock_monitor::reset();
+
+    let start_time = Instant::now();
+    let drivers = if cfg!(feature = "quick") {
+        run_quick_benchmarks(drivers)
+    } else {
+        run_full_benchmarks(drivers, data_sizes)
+    };
+    info!(
+        "Total benchmark suite completed in {}",
+        Elapsed::from_micros(start_time.elapsed().as_micros())
+    );
+    log_cpu_clock_range();
+    #[cfg(feature = "serial-abort")]
+    if abort::requested() {
+        warn!("Benchmark run aborted, logging the results so far");
+    }
+
+    #[cfg(any(
+        feature = "csv-output",
+        feature = "json-output",
+        feature = "prometheus-output",
+        feature = "baseline"
+    ))]
+    log_results();
+
+    #[cfg(feature = "thresholds")]
+    thresholds::report();
+
+    #[cfg(feature = "flash-results")]
+    serve_dump_request();
+
+    drivers
+}
+
+/// Prompt after an aborted run for [`abort::RESTART_BYTE`] to start another
+/// run, or one of [`abort::ABORT_BYTES`] to stop, and return whether to run
+/// again. A restart byte sent before the prompt is ignored.
+#[cfg(all(feature = "serial-abort", not(feature = "button-trigger")))]
+fn wait_for_restart() -> bool {
+    abort::clear();
+    abort::take_restart();
+    info!(
+        "Send '{}' to start a new run, or Ctrl-C or 'q' to stop",
+        abort::RESTART_BYTE as char
+    );
+    loop {
+        #[cfg(feature = "flash-results")]
+        serve_dump_request();
+        if abort::take_restart() {
+            return true;
+        }
+        if abort::requested() {
+            return false;
+        }
+    }
+}
+
+/// Run the full benchmark suite, stopping early if [`benchmarks::aborted`].
+#[cfg_attr(
+    not(any(feature = "bench-aes", feature = "bench-sha")),
+    allow(
+        unused_variables,
+        reason = "only the AES and SHA benchmarks sweep data sizes"
+    )
+)]
+fn run_full_benchmarks<'d>(mut drivers: Drivers<'d>, data_sizes: &[usize]) -> Drivers<'d> {
+    #[cfg(feature = "bench-rsa")]
+    if cfg!(feature = "rsa-stability") {
+        starting("RSA Stability Benchmark");
+        rsa::benchmark_rsa_stability(&mut drivers.rsa);
+        return drivers;
+    }
+
+    // Before anything else touches the engines, so the first operation of
+    // each is really the first of the run.
+    #[cfg(any(
+        feature = "bench-aes",
+        feature = "bench-sha",
+        feature = "bench-rsa",
+        feature = "bench-rng"
+    ))]
+    {
+        output::banner("Cold Start");
+        starting("Cold Start Benchmark");
+        #[cfg(feature = "bench-aes")]
+        let aes_first_us;
+        #[cfg(feature = "bench-aes")]
+        {
+            (drivers.aes, aes_first_us) = match drivers.aes.take().map(aes::cold_start_aes) {
+                Some(Ok((bench, first_us))) => (Some(bench), Some(first_us)),
+                Some(Err((error, bench))) => {
+                    benchmarks::failed(error);
+                    (bench, None)
+                }
+                None => (None, None),
+            };
+        }
+        #[cfg(feature = "bench-sha")]
+        let sha_first_us = sha::cold_start_sha256(&mut drivers.sha)
+            .map_err(benchmarks::failed)
+            .ok();
+        #[cfg(feature = "bench-rsa")]
+        let rsa_first_us = rsa::cold_start_rsa(&mut drivers.rsa)
+            .map_err(benchmarks::failed)
+            .ok();
+        #[cfg(feature = "bench-rng")]
+        let (_, rng_first) = benchmarks::timed(|| {
+            let mut key = [0_u8; COLD_PATH_RNG_SIZE];
+            drivers.rng.read(&mut key);
+            key
+        });
+
+        // The drivers were created at boot, so the first results are really
+        // the first after construction only in the run after a reset.
+        let init_times = drivers.init_times;
+        #[cfg(feature = "bench-aes")]
+        if let (Some(first_us), Some(bench)) = (aes_first_us, &drivers.aes) {
+            log_cold_path(
+                "AES-CTR",
+                COLD_START_SIZE.min(bench.capacity()),
+                init_times.aes,
+                first_us,
+            );
+        }
+        #[cfg(feature = "bench-sha")]
+        if let Some(first_us) = sha_first_us {
+            log_cold_path("SHA-256", COLD_START_SIZE, init_times.sha, first_us);
+        }
+        #[cfg(feature = "bench-rsa")]
+        if let Some(first_us) = rsa_first_us {
+            log_cold_path(
+                "RSA-2048 (e = 65537)",
+                U2048::BYTES,
+                init_times.rsa,
+                first_us,
+            );
+        }
+        #[cfg(feature = "bench-rng")]
+        log_cold_path(
+            "RNG",
+            COLD_PATH_RNG_SIZE,
+            init_times.rng,
+            rng_first.as_micros(),
+        );
+    }
+
+    #[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+    let verified;
+    #[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+    {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        (drivers, verified) = verify_engines(drivers);
+    }
+
+    #[cfg(feature = "soft-compare")]
+    info!("CMP: {COMPARISON_CSV_HEADER}");
+
+    // The AES benchmarks only run the data sizes that fit the DMA buffers.
+    #[cfg(feature = "bench-aes")]
+    let aes_sizes = match &drivers.aes {
+        Some(bench) => &data_sizes[..data_sizes.partition_point(|&size| size <= bench.capacity())],
+        None => &[],
+    };
+
+    #[cfg(feature = "bench-aes")]
+    if verified.aes {
+        output::banner("AES");
+        drivers.aes = drivers
+            .aes
+            .take()
+            .and_then(|bench| recover(aes::self_test_aes(bench)));
+
+        let aes_throughputs = run_aes_dma(&mut drivers.aes, aes_sizes);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        if let Some(bench) = drivers.aes.take() {
+            starting("memcpy Baseline");
+            let (bench, memcpy_throughputs) = aes::benchmark_memcpy(bench, aes_sizes);
+            drivers.aes = Some(bench);
+            aes::log_memcpy_efficiency(aes_sizes, &aes_throughputs, &memcpy_throughputs);
+        }
+
+        #[cfg(feature = "soft-compare")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            starting("AES-CTR Software Comparison");
+            compare::compare_aes_ctr(aes_sizes, &aes_throughputs);
+        }
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "Iteration Count Sweep",
+            aes::benchmark_iteration_sweep,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Sector Benchmark",
+            aes::benchmark_aes_sector,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Buffer Boundary Benchmark",
+            aes::benchmark_aes_buffer_boundary,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        if let Some(bench) = &drivers.aes {
+            starting("Heap Budget Diagnostic");
+            aes::benchmark_heap_budget(bench.capacity());
+        }
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Buffer Reuse Benchmark",
+            aes::benchmark_aes_buffer_reuse,
+        );
+
+        #[cfg(feature = "heap-pressure")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            run_aes(
+                &mut drivers.aes,
+                "AES-CTR Heap Pressure Benchmark",
+                aes::benchmark_aes_heap_pressure,
+            );
+        }
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "Logger Jitter Benchmark",
+            aes::benchmark_log_jitter,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(&mut drivers.aes, "AES-CTR Keystream Benchmark", |bench| {
+            aes::benchmark_aes_keystream(bench, aes_sizes)
+        });
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Output Retention Benchmark",
+            aes::benchmark_aes_output_retention,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Pre-filled DMA Input Benchmark",
+            aes::benchmark_aes_prefilled_input,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Partial Final Block Benchmark",
+            aes::benchmark_aes_partial_block,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR 96/32 Counter Layout Benchmark",
+            aes::benchmark_aes_ctr32,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR 1 MB Blob Benchmark",
+            aes::benchmark_aes_blob,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CFB8 / AES-CFB128 Benchmark",
+            |bench| aes::benchmark_aes_cfb(bench, aes_sizes),
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(&mut drivers.aes, "AES-CBC / AES-ECB Benchmark", |bench| {
+            aes::benchmark_aes_modes(bench, aes_sizes)
+        });
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR DMA Polling Benchmark",
+            aes::benchmark_aes_dma_polling,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(&mut drivers.aes, "AES-CMAC Benchmark", |bench| {
+            aes::benchmark_aes_cmac(bench, aes_sizes)
+        });
+
+        #[cfg(feature = "aes-cpu-load")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            run_aes(
+                &mut drivers.aes,
+                "AES-CTR DMA Benchmark under CPU load",
+                |bench| aes::benchmark_aes_dma_under_load(bench, aes_sizes),
+            );
+        }
+
+        #[cfg(feature = "interrupt-compare")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            run_aes(
+                &mut drivers.aes,
+                "AES-CTR DMA Benchmark with interrupts masked",
+                |bench| aes::benchmark_aes_dma_interrupts_masked(bench, aes_sizes),
+            );
+        }
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Data Independence Check",
+            aes::benchmark_aes_data_independence,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "DMA Descriptor Chain Benchmark",
+            aes::benchmark_dma_descriptor_chain,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Rekeying Benchmark",
+            aes::benchmark_aes_rekey,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Rekey Latency Benchmark",
+            aes::benchmark_aes_rekey_latency,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Key Alignment Benchmark",
+            aes::benchmark_aes_key_alignment,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Zeroize Benchmark",
+            aes::benchmark_aes_zeroize,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Scatter-Gather Benchmark",
+            aes::benchmark_aes_scatter_gather,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES DMA Re-attachment Benchmark",
+            aes::benchmark_aes_reattach,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES Clock Gating Wake Benchmark",
+            aes::benchmark_aes_wake,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Single Block Latency Benchmark",
+            aes::benchmark_aes_single_block,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES DMA Burst Benchmark",
+            aes::benchmark_aes_dma_burst,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Blocks per Call Benchmark",
+            aes::benchmark_aes_blocks_per_call,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Mixed Workload Benchmark",
+            aes::benchmark_aes_mixed_workload,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "TLS Record Benchmark",
+            aes::benchmark_tls_record,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-GCM Additional Data Benchmark",
+            aes::benchmark_aes_gcm_aad,
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Streaming Benchmark",
+            aes::benchmark_aes_stream,
+        );
+    }
+
+    #[cfg(all(feature = "bench-aes", feature = "bench-sha"))]
+    if verified.aes && verified.sha {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("AES + SHA-256");
+        run_aes(&mut drivers.aes, "Encrypt-then-MAC Benchmark", |bench| {
+            aes::benchmark_encrypt_then_mac(bench, &mut drivers.sha, aes_sizes)
+        });
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "Store with Integrity Benchmark",
+            |bench| aes::benchmark_store_with_integrity(bench, &mut drivers.sha),
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "Peripheral Switching Benchmark",
+            |bench| aes::benchmark_peripheral_switching(bench, &mut drivers.sha),
+        );
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        run_aes(
+            &mut drivers.aes,
+            "AES and SHA Parallelism Benchmark",
+            |bench| aes::benchmark_aes_sha_parallel(bench, &mut drivers.sha),
+        );
+    }
+
+    #[cfg(feature = "bench-sha")]
+    if verified.sha {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("SHA-256");
+        starting("SHA256 Benchmark");
+        let sha_throughputs =
+            sha::benchmark_sha256(&mut drivers.sha, data_sizes).unwrap_or_else(|error| {
+                benchmarks::failed(error);
+                Vec::new()
+            });
+        benchmarks::log_saturation("SHA-256", data_sizes, &sha_throughputs);
+
+        #[cfg(feature = "soft-blake3")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            starting("BLAKE3 Software Benchmark");
+            sha::benchmark_blake3(data_sizes, &sha_throughputs);
+        }
+
+        #[cfg(feature = "soft-compare")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            starting("SHA-256 Software Comparison");
+            compare::compare_sha256(data_sizes, &sha_throughputs);
+        }
+
+        #[cfg(feature = "interrupt-compare")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            starting("SHA256 Benchmark with interrupts masked");
+            check(sha::benchmark_sha256_interrupts_masked(
+                &mut drivers.sha,
+                data_sizes,
+            ));
+        }
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Many Small Messages Benchmark");
+        check(sha::benchmark_sha256_many_small(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Update Size Benchmark");
+        check(sha::benchmark_sha256_update_size(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Padding Boundary Benchmark");
+        check(sha::benchmark_sha256_padding(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Context Switch Benchmark");
+        check(sha::benchmark_sha256_context_switch(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Context Save/Restore Benchmark");
+        check(sha::benchmark_sha256_context_save_restore(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Prefix Benchmark");
+        check(sha::benchmark_sha256_prefix(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Scatter-Gather Benchmark");
+        check(sha::benchmark_sha256_scatter_gather(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 TLS Transcript Benchmark");
+        check(sha::benchmark_sha256_tls_transcript(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("HMAC-SHA256 Key Reuse Benchmark");
+        check(sha::benchmark_hmac_key_reuse(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Batch Benchmark");
+        check(sha::benchmark_sha256_batch(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Stream Start Benchmark");
+        check(sha::benchmark_sha256_stream_start(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 1 MB Blob Benchmark");
+        check(sha::benchmark_sha256_blob(&mut drivers.sha));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA Clock Gating Wake Benchmark");
+        let result;
+        (drivers.sha, result) = sha::benchmark_sha256_wake(drivers.sha);
+        check(result);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("SHA256 Finish Destination Benchmark");
+        check(sha::benchmark_sha256_finish_destination(&mut drivers.sha));
+
+        #[cfg(feature = "sha-flash")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            starting("SHA256 Flash Benchmark");
+            check(sha::benchmark_sha256_flash(&mut drivers.sha, data_sizes));
+        }
+    }
+
+    #[cfg(feature = "bench-rsa")]
+    if verified.rsa {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("RSA");
+        starting("RSA Benchmark");
+        let exponentiation = rsa::benchmark_rsa(
+            &mut drivers.rsa,
+            #[cfg(feature = "verify")]
+            &mut drivers.sha,
+        )
+        .map_err(benchmarks::failed)
+        .ok();
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        if let Some(exponentiation) = exponentiation {
+            starting("RSA Word Conversion Benchmark");
+            rsa::benchmark_rsa_word_conversions(exponentiation);
+        }
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Exponent Size Benchmark");
+        check(rsa::benchmark_rsa_exponent_sizes(&mut drivers.rsa));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Floor Benchmark");
+        rsa::benchmark_rsa_floor(&mut drivers.rsa);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Public Exponent Benchmark");
+        rsa::benchmark_rsa_public_exponents(&mut drivers.rsa);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Byte Marshaling Benchmark");
+        rsa::benchmark_rsa_marshaling(&mut drivers.rsa);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Key Setup Benchmark");
+        rsa::benchmark_rsa_key_setup(&mut drivers.rsa);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA CRT Benchmark");
+        check(rsa::benchmark_rsa_crt(&mut drivers.rsa));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Batch Verification Benchmark");
+        check(rsa::benchmark_rsa_batch_verify(&mut drivers.rsa));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Steady-State Verification Benchmark");
+        check(rsa::benchmark_rsa_steady_verify(&mut drivers.rsa));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("RSA Blinding Benchmark");
+        check(rsa::benchmark_rsa_blinding(&mut drivers.rsa));
+
+        #[cfg(feature = "interrupt-compare")]
+        {
+            if benchmarks::aborted() {
+                return drivers;
+            }
+            starting("RSA Benchmark with interrupts masked");
+            check(rsa::benchmark_rsa_interrupts_masked(&mut drivers.rsa));
+        }
+    }
+
+    #[cfg(all(feature = "bench-sha", feature = "bench-rsa"))]
+    if verified.sha && verified.rsa {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("SHA-256 + RSA");
+        starting("Hash-then-Sign Benchmark");
+        check(rsa::benchmark_hash_then_sign(
+            &mut drivers.rsa,
+            &mut drivers.sha,
+        ));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("Signed Firmware Verification Benchmark");
+        check(rsa::benchmark_firmware_verify(
+            &mut drivers.rsa,
+            &mut drivers.sha,
+        ));
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("Hash and Sign Overlap Benchmark");
+        check(rsa::benchmark_hash_sign_overlap(
+            &mut drivers.rsa,
+            &mut drivers.sha,
+        ));
+    }
+
+    #[cfg(feature = "concurrent")]
+    if verified.aes && verified.sha && verified.rsa {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("Concurrent Engines");
+        if let Some(bench) = drivers.aes.take() {
+            starting("Concurrent Engines Benchmark");
+            (drivers.aes, drivers.rsa) =
+                concurrent::benchmark_concurrent_engines(bench, &mut drivers.sha, drivers.rsa);
+        }
+    }
+
+    #[cfg(feature = "bench-ecc")]
+    {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("ECC");
+        starting("ECC Benchmark");
+        ecc::benchmark_ecc(&mut drivers.ecc);
+    }
+
+    #[cfg(feature = "bench-rng")]
+    {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("RNG");
+        starting("RNG Benchmark");
+        rng::benchmark_rng(&mut drivers.rng);
+    }
+
+    #[cfg(feature = "hmac-efuse")]
+    {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("HMAC");
+        starting("HMAC Modes Benchmark");
+        hmac::benchmark_hmac_modes(&mut drivers.hmac);
+
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        starting("HMAC Peripheral Sweep");
+        hmac::benchmark_hmac(&mut drivers.hmac, data_sizes);
+    }
+
+    #[cfg(all(feature = "throughput-profile", feature = "bench-aes"))]
+    if verified.aes {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("Throughput Profile");
+        run_aes(
+            &mut drivers.aes,
+            "AES-CTR Throughput Profile",
+            profile_aes_dma,
+        );
+    }
+
+    #[cfg(all(feature = "throughput-profile", feature = "bench-sha"))]
+    if verified.sha {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        #[cfg(not(feature = "bench-aes"))]
+        output::banner("Throughput Profile");
+        starting("SHA256 Throughput Profile");
+        check(profile_sha256(&mut drivers.sha));
+    }
+
+    #[cfg(all(feature = "power-sensor", feature = "bench-aes"))]
+    if verified.aes {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        output::banner("Energy");
+        run_aes(&mut drivers.aes, "AES-CTR Energy Measurement", |bench| {
+            measure_aes_energy(bench, &mut drivers.power, aes_sizes)
+        });
+    }
+
+    #[cfg(all(feature = "power-sensor", feature = "bench-sha"))]
+    if verified.sha {
+        if benchmarks::aborted() {
+            return drivers;
+        }
+        #[cfg(not(feature = "bench-aes"))]
+        output::banner("Energy");
+        starting("SHA256 Energy Measurement");
+        check(measure_sha256_energy(
+            &mut drivers.sha,
+            &mut drivers.power,
+            data_sizes,
+        ));
+    }
+
+    drivers
+}
+
+/// Run one data size ([`QUICK_DATA_SIZE`]) of AES-CTR and SHA-256 with few
+/// iterations, one RSA-2048 exponentiation, the ECC benchmark and the RNG
+/// reads, as a smoke test after flashing. Replaces the full suite in `quick` builds.
+///
+/// With `soft-compare` the software comparisons run for the same single
+/// size and exponentiation.
+fn run_quick_benchmarks(mut drivers: Drivers<'_>) -> Drivers<'_> {
+    #[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+    let verified;
+    #[cfg(any(feature = "bench-aes", feature = "bench-sha", feature = "bench-rsa"))]
+    {
+        (drivers, verified) = verify_engines(drivers);
+    }
+
+    #[cfg(feature = "soft-compare")]
+    info!("CMP: {COMPARISON_CSV_HEADER}");
+
+    #[cfg(feature = "bench-aes")]
+    if verified.aes {
+        output::banner("AES");
+        drivers.aes = drivers
+            .aes
+            .take()
+            .and_then(|bench| recover(aes::self_test_aes(bench)));
+
+        let _aes_throughputs = run_aes_dma(&mut drivers.aes, &[QUICK_DATA_SIZE]);
+
+        #[cfg(feature = "soft-compare")]
+        {
+            starting("AES-CTR Software Comparison");
+            compare::compare_aes_ctr(&[QUICK_DATA_SIZE], &_aes_throughputs);
+        }
+    }
+
+    #[cfg(feature = "bench-sha")]
+    if verified.sha {
+        output::banner("SHA-256");
+        starting("SHA256 Benchmark");
+        let _sha_throughputs = sha::benchmark_sha256(&mut drivers.sha, &[QUICK_DATA_SIZE])
+            .unwrap_or_else(|error| {
+                benchmarks::failed(error);
+                Vec::new()
+            });
+
+        #[cfg(feature = "soft-compare")]
+        {
+            starting("SHA-256 Software Comparison");
+            compare::compare_sha256(&[QUICK_DATA_SIZE], &_sha_throughputs);
+        }
+    }
+
+    #[cfg(feature = "bench-rsa")]
+    if verified.rsa {
+        output::banner("RSA");
+        starting("RSA Benchmark");
+        match rsa::benchmark_rsa_modexp::<Op2048, { U2048::LIMBS }>(
+            &mut drivers.rsa,
+            "RSA-2048",
+            &rsa::RSA_EXPONENT.resize(),
+        ) {
+            Ok((_elapsed, _result)) => {
+                #[cfg(feature = "soft-compare")]
+                compare::compare_rsa_modexp(_elapsed, &_result);
+            }
+            Err(error) => benchmarks::failed(error),
+        }
+    }
+
+    #[cfg(feature = "bench-ecc")]
+    {
+        output::banner("ECC");
+        starting("ECC Benchmark");
+        ecc::benchmark_ecc(&mut drivers.ecc);
+    }
+
+    #[cfg(feature = "bench-rng")]
+    {
+        output::banner("RNG");
+        starting("RNG Benchmark");
+        rng::benchmark_rng(&mut drivers.rng);
+    }
+
+    drivers
+}
+
+/// Block until the active-low `button` is pressed, debouncing the edge.
+///
+/// A button that is still held from the previous press has to be released
+/// first, so each press starts exactly one run.
+#[cfg(feature = "button-trigger")]
+fn wait_for_button_press(button: &Input) {
+    const DEBOUNCE: Duration = Duration::from_millis(20);
+
+    while button.is_low() {}
+    loop {
+        while button.is_high() {
+            #[cfg(feature = "flash-results")]
+            serve_dump_request();
+        }
+        let press_start = Instant::now();
+        while press_start.elapsed() < DEBOUNCE {}
+        if button.is_low() {
+            return;
+        }
+    }
+}
+
+/// Park the core for good once there is nothing left to do, printing the
+/// results stored in flash on request with `flash-results`.
+///
+/// With the `wfi-idle` feature the core sleeps in `wfi` between interrupts
+/// instead of spinning, which lowers the idle current while the chip stays
+/// reachable over USB serial and JTAG.
+fn idle() -> ! {
+    loop {
+        #[cfg(feature = "flash-results")]
+        serve_dump_request();
+        #[cfg(feature = "wfi-idle")]
+        // SAFETY: `wfi` only stalls the hart until the next interrupt is
+        // pending; it touches no memory or registers.
+        unsafe {
+            core::arch::asm!("wfi", options(nomem, nostack));
+        }
+        #[cfg(not(feature = "wfi-idle"))]
+        {
+            let delay_start = Instant::now();
+            while delay_start.elapsed() < Duration::from_millis(500) {}
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Watch for CPU clock changes during a run.
+//!
+//! The timings are only comparable if the CPU runs at the same frequency for
+//! the whole suite. [`sample`] decodes the frequency the clock tree is
+//! actually running at from the PCR clock source and divider registers,
+//! rather than the value [`esp_hal::clock::Clocks`] recorded at start-up,
+//! and keeps the lowest and highest frequency seen so far for [`range`].
+
+use core::cell::Cell;
+
+use critical_section::Mutex;
+use esp_hal::peripherals::PCR;
+
+/// Frequency of the crystal, the `XTAL` CPU clock source.
+const XTAL_KHZ: u32 = 40_000;
+/// Frequency of the fixed SPLL the `PLL` CPU clock source divides down.
+const PLL_KHZ: u32 = 480_000;
+/// Nominal frequency of the uncalibrated `RC_FAST` oscillator.
+const RC_FAST_KHZ: u32 = 17_500;
+
+/// Lowest and highest CPU frequency seen by [`sample`], in kHz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockRange {
+    /// Lowest frequency seen.
+    pub min_khz: u32,
+    /// Highest frequency seen.
+    pub max_khz: u32,
+}
+
+impl ClockRange {
+    /// Whether every sample was at `khz`.
+    pub fn is_steady_at(&self, khz: u32) -> bool {
+        self.min_khz == khz && self.max_khz == khz
+    }
+}
+
+static RANGE: Mutex<Cell<Option<ClockRange>>> = Mutex::new(Cell::new(None));
+
+/// The frequency the CPU runs at right now, in kHz, or `None` if the clock
+/// source register holds a reserved value.
+///
+/// The HS (PLL) path divides by the HP root HS divider and the CPU HS
+/// divider; the LS (XTAL and RC_FAST) path by the corresponding LS dividers.
+pub fn cpu_clock_khz() -> Option<u32> {
+    let pcr = PCR::regs();
+    let sysclk = pcr.sysclk_conf().read();
+    let cpu = pcr.cpu_freq_conf().read();
+    let ls_divider =
+        (u32::from(sysclk.ls_div_num().bits()) + 1) * (u32::from(cpu.cpu_ls_div_num().bits()) + 1);
+    match sysclk.soc_clk_sel().bits() {
+        0 => Some(XTAL_KHZ / ls_divider),
+        1 => {
+            let hs_divider = if cpu.cpu_hs_div_num().bits() == 0 && cpu.cpu_hs_120m_force().bit() {
+                4
+            } else {
+                (u32::from(sysclk.hs_div_num().bits()) + 1)
+                    * (u32::from(cpu.cpu_hs_div_num().bits()) + 1)
+            };
+            Some(PLL_KHZ / hs_divider)
+        }
+        2 => Some(RC_FAST_KHZ / ls_divider),
+        _ => None,
+    }
+}
+
+/// Read the CPU frequency, add it to the [`range`] and return it.
+pub fn sample() -> Option<u32> {
+    let khz = cpu_clock_khz()?;
+    critical_section::with(|cs| {
+        let range = RANGE.borrow(cs);
+        let widened = match range.get() {
+            Some(seen) => ClockRange {
+                min_khz: seen.min_khz.min(khz),
+                max_khz: seen.max_khz.max(khz),
+            },
+            None => ClockRange {
+                min_khz: khz,
+                max_khz: khz,
+            },
+        };
+        range.set(Some(widened));
+    });
+    Some(khz)
+}
+
+/// The frequencies seen since the last [`reset`], or `None` before the first
+/// [`sample`].
+pub fn range() -> Option<ClockRange> {
+    critical_section::with(|cs| RANGE.borrow(cs).get())
+}
+
+/// Forget the frequencies seen so far, at the start of a run.
+pub fn reset() {
+    critical_section::with(|cs| RANGE.borrow(cs).set(None));
+}
@@ -0,0 +1,48 @@
+//! Efficiency figures from datasheet power values.
+//!
+//! Without a power sensor the benchmarks can still compare primitives by a
+//! figure of merit: throughput per milliwatt, using a typical active power for
+//! each peripheral's workload instead of a measurement:
+//!
+//! ```text
+//! Efficiency (datasheet power), AES-CTR, DataSize: 1024, Power: 125.4 mW, Throughput: 0.162 MB/s per mW
+//! ```
+//!
+//! The constants below all default to the same figure, the typical supply
+//! current of the ESP32-C6 with the CPU running at 160 MHz and the radio off
+//! (about 38 mA at 3.3 V). Replace them with the datasheet values for the
+//! module, clock and peripheral in use; the `power-sensor` feature measures
+//! the real power instead.
+
+use log::info;
+
+use crate::units::Throughput;
+
+/// Power in milliwatts while the AES engine runs.
+pub const AES_POWER_MW: f64 = 3.3 * 38.0;
+
+/// Power in milliwatts while the SHA engine runs.
+pub const SHA_POWER_MW: f64 = 3.3 * 38.0;
+
+/// Power in milliwatts while the RSA engine runs.
+pub const RSA_POWER_MW: f64 = 3.3 * 38.0;
+
+/// Log the throughput per milliwatt of a result over `data_size` bytes at
+/// `mb_per_s`, drawing `power_mw`.
+pub fn log_throughput(label: &str, data_size: usize, mb_per_s: f64, power_mw: f64) {
+    info!(
+        "Efficiency (datasheet power), {label}, DataSize: {data_size}, Power: {power_mw:.1} mW, Throughput: {:.3} per mW",
+        Throughput(mb_per_s / power_mw)
+    );
+}
+
+/// Log the energy per operation and the operations per second per milliwatt
+/// of one operation on `data_size` byte operands that took `micros`
+/// microseconds, drawing `power_mw`.
+pub fn log_operation(label: &str, data_size: usize, micros: f64, power_mw: f64) {
+    info!(
+        "Efficiency (datasheet power), {label}, DataSize: {data_size}, Power: {power_mw:.1} mW, Energy: {:.3} mJ/op, Rate: {:.4} ops/s per mW",
+        power_mw * micros / 1_000_000.0,
+        1_000_000.0 / micros / power_mw
+    );
+}
@@ -0,0 +1,92 @@
+//! Failures of the benchmark helpers.
+//!
+//! The AES, SHA and RSA helpers return a [`BenchError`] instead of panicking, so
+//! the suite can log what went wrong in which benchmark and carry on with the
+//! next one. Waiting on the engines is bounded by [`ENGINE_TIMEOUT`], which
+//! turns a hung peripheral into [`BenchError::TransferWait`] or
+//! [`BenchError::DigestUpdate`] instead of a stalled run. The HMAC benchmarks
+//! bound their waits with the same [`BusyWait`].
+
+use core::fmt;
+
+use esp_hal::dma::DmaError;
+use esp_hal::time::{Duration, Instant};
+
+/// Longest an AES DMA transfer or a SHA update may keep the engine busy
+/// before it counts as hung; the largest benchmark transfers take a few
+/// milliseconds.
+pub const ENGINE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Why a benchmark step failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchError {
+    /// Not even `size` byte DMA buffers fit in the heap.
+    DmaBufferAlloc {
+        /// The smallest size tried, in bytes.
+        size: usize,
+    },
+    /// The AES engine did not accept a DMA transfer.
+    Process(DmaError),
+    /// An AES DMA transfer did not finish within [`ENGINE_TIMEOUT`].
+    TransferWait,
+    /// The SHA engine stayed busy for [`ENGINE_TIMEOUT`] without taking more
+    /// input.
+    DigestUpdate,
+    /// The SHA engine did not produce the digest.
+    DigestFinish,
+    /// A benchmark took no samples to compute statistics from.
+    NoSamples,
+    /// A benchmark ran without the driver or operands it loads in
+    /// [`Benchmark::setup`](crate::harness::Benchmark::setup).
+    NotSetUp,
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DmaBufferAlloc { size } => {
+                write!(f, "could not allocate {size} byte DMA buffers")
+            }
+            Self::Process(error) => write!(f, "AES DMA transfer not started: {error:?}"),
+            Self::TransferWait => write!(
+                f,
+                "AES DMA transfer not finished after {} ms",
+                ENGINE_TIMEOUT.as_millis()
+            ),
+            Self::DigestUpdate => write!(
+                f,
+                "SHA engine busy for {} ms, update not accepted",
+                ENGINE_TIMEOUT.as_millis()
+            ),
+            Self::DigestFinish => f.write_str("SHA digest could not be finished"),
+            Self::NoSamples => f.write_str("no samples taken"),
+            Self::NotSetUp => f.write_str("benchmark run before it was set up"),
+        }
+    }
+}
+
+/// Tracks how long a polling loop has waited on an engine.
+///
+/// The clock is only read every [`BusyWait::POLL_INTERVAL`] polls, so the
+/// check adds next to nothing to the loops being timed.
+#[derive(Debug, Default)]
+pub struct BusyWait {
+    polls: u32,
+    since: Option<Instant>,
+}
+
+impl BusyWait {
+    /// Polls between two reads of the clock.
+    pub const POLL_INTERVAL: u32 = 1024;
+
+    /// Count one more poll of a busy engine and report whether it has been
+    /// busy for longer than [`ENGINE_TIMEOUT`].
+    pub fn expired(&mut self) -> bool {
+        self.polls = self.polls.wrapping_add(1);
+        if !self.polls.is_multiple_of(Self::POLL_INTERVAL) {
+            return false;
+        }
+        let since = *self.since.get_or_insert_with(Instant::now);
+        since.elapsed() > ENGINE_TIMEOUT
+    }
+}
@@ -0,0 +1,63 @@
+//! Benchmark results kept in a reserved flash region.
+//!
+//! With `flash-results` the CSV output of every run is also written to the
+//! `results` partition of `partitions.csv`, replacing the previous run's, so
+//! it survives a reset and can be read back over serial later. The region
+//! holds a small header followed by the CSV text:
+//!
+//! | Offset | Size | Contents                     |
+//! |--------|------|------------------------------|
+//! | 0      | 4    | Magic `BRES`                 |
+//! | 4      | 4    | Text length `n`, little-endian |
+//! | 8      | `n`  | CSV text, one record per line |
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use embedded_storage::{ReadStorage, Storage};
+use esp_storage::{FlashStorage, FlashStorageError};
+
+/// Flash offset of the `results` partition.
+pub const OFFSET: u32 = 0x3F_0000;
+
+/// Size of the `results` partition in bytes.
+pub const CAPACITY: usize = 64 * 1024;
+
+const MAGIC: [u8; 4] = *b"BRES";
+const HEADER_LEN: usize = 8;
+
+/// Replace the stored results with `csv`, truncated to what fits the region.
+///
+/// Returns the number of bytes stored. The header is cleared first and
+/// written last, so an interrupted write leaves no valid results rather than
+/// a mix of old and new ones.
+pub fn store(csv: &[u8]) -> Result<usize, FlashStorageError> {
+    let len = csv.len().min(CAPACITY - HEADER_LEN);
+    let mut header = [0_u8; HEADER_LEN];
+
+    let mut flash = FlashStorage::new();
+    flash.write(OFFSET, &header)?;
+    flash.write(OFFSET + HEADER_LEN as u32, &csv[..len])?;
+
+    header[..4].copy_from_slice(&MAGIC);
+    header[4..].copy_from_slice(&(len as u32).to_le_bytes());
+    flash.write(OFFSET, &header)?;
+    Ok(len)
+}
+
+/// The stored CSV text, or `None` if the region holds no valid results.
+pub fn load() -> Result<Option<Vec<u8>>, FlashStorageError> {
+    let mut flash = FlashStorage::new();
+    let mut header = [0_u8; HEADER_LEN];
+    flash.read(OFFSET, &mut header)?;
+
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    if header[..4] != MAGIC || len > CAPACITY - HEADER_LEN {
+        return Ok(None);
+    }
+    let mut csv = vec![0_u8; len];
+    flash.read(OFFSET + HEADER_LEN as u32, &mut csv)?;
+    Ok(Some(csv))
+}
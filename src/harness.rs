@@ -0,0 +1,128 @@
+//! Engine benchmarks behind one [`Benchmark`] trait.
+//!
+//! Each benchmark sets up its operands once, then runs one iteration per
+//! call of [`Benchmark::run`] and turns the mean time into a
+//! [`BenchmarkResult`]. [`measure_blocking`] runs one to completion; the entry
+//! points of [`crate::benchmarks`] are built on it. The implementations live
+//! with the benchmarks of their engine:
+//! [`AesDmaBenchmark`](crate::benchmarks::aes::AesDmaBenchmark),
+//! [`Sha256Benchmark`](crate::benchmarks::sha::Sha256Benchmark) and
+//! [`RsaBenchmark`].
+//!
+//! `run` is an `async fn` so that several benchmarks can share the CPU, but
+//! the waiting is cooperative polling, not interrupt-driven: AES checks its
+//! DMA transfer and SHA retries the engine between yields, so a task only
+//! makes progress when it is polled. Only [`RsaBenchmark`] on the async RSA
+//! driver (`Rsa::into_async`) awaits an interrupt, and even that is polled by
+//! a busy executor rather than woken on an embassy executor. With the
+//! `concurrent` feature, [`measure_together`] polls three of them in turn,
+//! which keeps the AES engine, its DMA channel, the SHA engine and the RSA
+//! engine busy at the same time:
+//!
+//! ```ignore
+//! let (aes, sha, rsa) = harness::measure_together(
+//!     (&mut aes, 100),
+//!     (&mut sha, 20),
+//!     (&mut rsa, 4),
+//! );
+//! ```
+//!
+//! Every iteration is timed from start to finish, so while a benchmark waits
+//! for the CPU to come back from another one, or for a bus another engine
+//! holds, that time counts against it. Running each one alone first gives
+//! the figures to compare with.
+//!
+//! [`RsaBenchmark`]: crate::benchmarks::rsa::RsaBenchmark
+
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+use core::future::poll_fn;
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use esp_hal::time::Instant;
+
+use crate::error::BenchError;
+use crate::report::BenchmarkResult;
+
+/// One engine benchmark. `'l` is the lifetime of the label in its results.
+pub trait Benchmark<'l> {
+    /// Prepare the operands, outside the timing. Called before every
+    /// measurement, so it has to be cheap to repeat.
+    fn setup(&mut self);
+
+    /// Run one iteration, yielding whenever the engine is busy.
+    fn run(&mut self) -> impl Future<Output = Result<(), BenchError>>;
+
+    /// The result of `iterations` runs taking `time_us` each on average.
+    fn report(&self, iterations: usize, time_us: f64) -> BenchmarkResult<'l>;
+}
+
+/// Set up `benchmark`, run it `iterations` times and return the mean time
+/// per run. Stops at the first failed run.
+pub async fn measure<'l, B: Benchmark<'l>>(
+    benchmark: &mut B,
+    iterations: usize,
+) -> Result<BenchmarkResult<'l>, BenchError> {
+    benchmark.setup();
+    let start_time = Instant::now();
+    for _ in 0..iterations {
+        benchmark.run().await?;
+    }
+    let time_us = start_time.elapsed().as_micros() as f64 / iterations.max(1) as f64;
+    Ok(benchmark.report(iterations, time_us))
+}
+
+/// [`measure`] `benchmark` on its own, polling it until it is done.
+pub fn measure_blocking<'l, B: Benchmark<'l>>(
+    benchmark: &mut B,
+    iterations: usize,
+) -> Result<BenchmarkResult<'l>, BenchError> {
+    let mut measurement = pin!(measure(benchmark, iterations));
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(result) = measurement.as_mut().poll(&mut context) {
+            return result;
+        }
+    }
+}
+
+/// [`measure`] three benchmarks joined, each with its number of
+/// iterations, polling them in turn until all are done.
+#[cfg(feature = "concurrent")]
+pub fn measure_together<'l, A, B, C>(
+    a: (&mut A, usize),
+    b: (&mut B, usize),
+    c: (&mut C, usize),
+) -> (
+    Result<BenchmarkResult<'l>, BenchError>,
+    Result<BenchmarkResult<'l>, BenchError>,
+    Result<BenchmarkResult<'l>, BenchError>,
+)
+where
+    A: Benchmark<'l>,
+    B: Benchmark<'l>,
+    C: Benchmark<'l>,
+{
+    embassy_futures::block_on(embassy_futures::join::join3(
+        measure(a.0, a.1),
+        measure(b.0, b.1),
+        measure(c.0, c.1),
+    ))
+}
+
+/// Let the other joined benchmarks run once before continuing.
+#[cfg(any(feature = "bench-aes", feature = "bench-sha"))]
+pub(crate) async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|context| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
@@ -1 +1,31 @@
 #![no_std]
+
+pub use esp32c6_crypto_core::{ghash, hex, histogram, report, stats, units};
+
+pub mod clock_monitor;
+pub mod output;
+
+#[cfg(any(feature = "serial-abort", feature = "flash-results"))]
+pub mod abort;
+#[cfg(feature = "baseline")]
+pub mod baseline;
+pub mod benchmarks;
+#[cfg(feature = "datasheet-power")]
+pub mod datasheet_power;
+pub mod error;
+#[cfg(feature = "flash-results")]
+pub mod flash_results;
+pub mod harness;
+#[cfg(feature = "power-sensor")]
+pub mod power;
+#[cfg(feature = "raw-timings")]
+pub mod raw_timings;
+#[cfg(any(
+    feature = "csv-output",
+    feature = "json-output",
+    feature = "prometheus-output",
+    feature = "baseline"
+))]
+pub mod results;
+#[cfg(feature = "thresholds")]
+pub mod thresholds;
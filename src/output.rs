@@ -0,0 +1,201 @@
+//! Output transport for the benchmark log and raw timing frames.
+//!
+//! By default everything goes to the serial console through `esp-println`.
+//! With the `rtt` feature the same output is sent over RTT for use with an
+//! attached debug probe instead:
+//!
+//! | Up channel | Name         | Contents                                  |
+//! |------------|--------------|-------------------------------------------|
+//! | 0          | `Terminal`   | Log output, formatted like `esp-println`  |
+//! | 1          | `RawTimings` | Raw timing frames (`raw-timings` feature) |
+//!
+//! Both RTT channels block while full, so no results are lost but the
+//! benchmark stalls until the host reads them.
+//!
+//! [`banner`] separates the sections of the log with the `banners` feature.
+//!
+//! To tell boards apart in logs aggregated from a board farm, every log line
+//! can start with a tag, set at build time through the `BOARD_TAG`
+//! environment variable, e.g. `BOARD_TAG=board-3 cargo run --release` logs
+//! `[board-3] INFO - ...`. `BOARD_TAG=mac` uses the board's eFuse MAC
+//! address instead. Without a tag the output is unchanged.
+
+#[cfg(feature = "rtt")]
+use core::cell::RefCell;
+use core::fmt;
+
+#[cfg(feature = "rtt")]
+use critical_section::Mutex;
+use esp_hal::efuse::Efuse;
+#[cfg(feature = "rtt")]
+use rtt_target::{rprintln, ChannelMode, UpChannel};
+
+/// Install the logger for the selected transport.
+///
+/// Must be called exactly once, before anything is logged.
+pub fn init() {
+    #[cfg(not(feature = "rtt"))]
+    if BOARD_TAG.is_empty() {
+        esp_println::logger::init_logger_from_env();
+    } else {
+        // SAFETY: called once during start-up, before any other logging.
+        unsafe {
+            log::set_logger_racy(&Logger).unwrap();
+            log::set_max_level_racy(LEVEL_FILTER);
+        }
+    }
+
+    #[cfg(feature = "rtt")]
+    {
+        let channels = rtt_target::rtt_init! {
+            up: {
+                0: {
+                    size: 1024,
+                    mode: ChannelMode::BlockIfFull,
+                    name: "Terminal"
+                }
+                1: {
+                    size: 4096,
+                    mode: ChannelMode::BlockIfFull,
+                    name: "RawTimings"
+                }
+            }
+        };
+        rtt_target::set_print_channel(channels.up.0);
+        critical_section::with(|cs| RAW_CHANNEL.replace(cs, Some(channels.up.1)));
+
+        // SAFETY: called once during start-up, before any other logging.
+        unsafe {
+            log::set_logger_racy(&Logger).unwrap();
+            log::set_max_level_racy(LEVEL_FILTER);
+        }
+    }
+}
+
+/// Log a banner opening the section of results for `title`, e.g.
+/// `======= AES =======`, to make a long run easier to navigate on a terminal.
+///
+/// Only the `banners` feature logs anything, so the default output stays
+/// unchanged for parsers; `color` additionally highlights the banner with
+/// ANSI escape codes.
+#[cfg_attr(not(feature = "banners"), allow(unused_variables))]
+pub fn banner(title: &str) {
+    #[cfg(all(feature = "banners", not(feature = "color")))]
+    log::info!("======= {title} =======");
+
+    #[cfg(feature = "color")]
+    log::info!("\u{001B}[1;36m======= {title} =======\u{001B}[0m");
+}
+
+/// Write raw bytes, bypassing the logger.
+pub fn write_bytes(bytes: &[u8]) {
+    #[cfg(not(feature = "rtt"))]
+    esp_println::Printer::write_bytes(bytes);
+
+    #[cfg(feature = "rtt")]
+    critical_section::with(|cs| {
+        if let Some(channel) = RAW_CHANNEL.borrow_ref_mut(cs).as_mut() {
+            channel.write(bytes);
+        }
+    });
+}
+
+#[cfg(feature = "rtt")]
+static RAW_CHANNEL: Mutex<RefCell<Option<UpChannel>>> = Mutex::new(RefCell::new(None));
+
+/// Tag logged at the start of every line, empty for none; `mac` stands for
+/// the eFuse MAC address.
+const BOARD_TAG: &str = match option_env!("BOARD_TAG") {
+    Some(tag) => tag,
+    None => "",
+};
+
+/// Formats [`BOARD_TAG`] as a line prefix, e.g. `[board-3] `, or as nothing
+/// without a tag.
+struct Tag;
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match BOARD_TAG {
+            "" => Ok(()),
+            "mac" => {
+                f.write_str("[")?;
+                for byte in Efuse::mac_address() {
+                    write!(f, "{byte:02x}")?;
+                }
+                f.write_str("] ")
+            }
+            tag => write!(f, "[{tag}] "),
+        }
+    }
+}
+
+/// Maximum log level, taken from the same `ESP_LOG` variable `esp-println`
+/// uses. Only a global level is supported; per-target filters fall back to
+/// `INFO`.
+const LEVEL_FILTER: log::LevelFilter = match option_env!("ESP_LOG") {
+    Some(level) => parse_level(level.as_bytes()),
+    None => log::LevelFilter::Off,
+};
+
+const fn parse_level(level: &[u8]) -> log::LevelFilter {
+    const LEVELS: [(&[u8], log::LevelFilter); 6] = [
+        (b"off", log::LevelFilter::Off),
+        (b"error", log::LevelFilter::Error),
+        (b"warn", log::LevelFilter::Warn),
+        (b"info", log::LevelFilter::Info),
+        (b"debug", log::LevelFilter::Debug),
+        (b"trace", log::LevelFilter::Trace),
+    ];
+
+    let mut i = 0;
+    while i < LEVELS.len() {
+        if level.eq_ignore_ascii_case(LEVELS[i].0) {
+            return LEVELS[i].1;
+        }
+        i += 1;
+    }
+    log::LevelFilter::Info
+}
+
+/// Logger that reproduces the `esp-println` record format over RTT, or on
+/// the serial console with a [`BOARD_TAG`] in front.
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= LEVEL_FILTER
+    }
+
+    fn log(&self, record: &log::Record) {
+        const RESET: &str = "\u{001B}[0m";
+
+        let color = match record.level() {
+            log::Level::Error => "\u{001B}[31m",
+            log::Level::Warn => "\u{001B}[33m",
+            log::Level::Info => "\u{001B}[32m",
+            log::Level::Debug => "\u{001B}[34m",
+            log::Level::Trace => "\u{001B}[35m",
+        };
+        #[cfg(feature = "rtt")]
+        rprintln!(
+            "{}{}{} - {}{}",
+            Tag,
+            color,
+            record.level(),
+            record.args(),
+            RESET
+        );
+        #[cfg(not(feature = "rtt"))]
+        esp_println::println!(
+            "{}{}{} - {}{}",
+            Tag,
+            color,
+            record.level(),
+            record.args(),
+            RESET
+        );
+    }
+
+    fn flush(&self) {}
+}
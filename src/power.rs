@@ -0,0 +1,89 @@
+//! Power measurement with an INA219 current sensor on I2C.
+//!
+//! The sensor sits in series with the board supply and reports the shunt
+//! and bus voltages; the power drawn is `bus voltage * shunt voltage / shunt
+//! resistance`. It uses its power-on configuration: continuous conversion
+//! with 12-bit samples, each averaging the last 532 µs, a ±320 mV shunt range
+//! and a 32 V bus range. The readings cover everything on the measured rail,
+//! not only the crypto peripherals.
+
+use esp_hal::i2c::master::{Error, I2c};
+use esp_hal::Blocking;
+
+/// 7-bit I2C address of the INA219 with A0 and A1 tied to ground.
+pub const ADDRESS: u8 = 0x40;
+
+/// Shunt resistor on common INA219 breakout boards. Change this to match the
+/// board in use.
+pub const SHUNT_RESISTANCE_OHMS: f64 = 0.1;
+
+const REGISTER_SHUNT_VOLTAGE: u8 = 0x01;
+const REGISTER_BUS_VOLTAGE: u8 = 0x02;
+
+/// Shunt voltage register LSB in volts.
+const SHUNT_VOLTAGE_LSB: f64 = 10e-6;
+
+/// Bus voltage register LSB in volts, after dropping the three status bits.
+const BUS_VOLTAGE_LSB: f64 = 4e-3;
+
+/// An INA219 on a blocking I2C bus.
+pub struct Ina219<'d> {
+    i2c: I2c<'d, Blocking>,
+}
+
+impl<'d> Ina219<'d> {
+    /// Use the sensor at [`ADDRESS`] on `i2c`.
+    pub fn new(i2c: I2c<'d, Blocking>) -> Self {
+        Self { i2c }
+    }
+
+    /// The power of the most recent conversion, in watts.
+    pub fn read_power(&mut self) -> Result<f64, Error> {
+        let shunt = self.read_register(REGISTER_SHUNT_VOLTAGE)? as i16 as f64 * SHUNT_VOLTAGE_LSB;
+        let bus = (self.read_register(REGISTER_BUS_VOLTAGE)? >> 3) as f64 * BUS_VOLTAGE_LSB;
+        Ok(bus * shunt / SHUNT_RESISTANCE_OHMS)
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u16, Error> {
+        let mut value = [0_u8; 2];
+        self.i2c.write_read(ADDRESS, &[register], &mut value)?;
+        Ok(u16::from_be_bytes(value))
+    }
+}
+
+/// Average of the power samples taken during one measurement.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnergyMeter {
+    sum_watts: f64,
+    samples: usize,
+    errors: usize,
+}
+
+impl EnergyMeter {
+    /// Take one sample from `sensor`. Failed reads are counted and skipped.
+    pub fn sample(&mut self, sensor: &mut Ina219<'_>) {
+        match sensor.read_power() {
+            Ok(watts) => {
+                self.sum_watts += watts;
+                self.samples += 1;
+            }
+            Err(_) => self.errors += 1,
+        }
+    }
+
+    /// Mean power over all successful samples in watts, or `None` without
+    /// any.
+    pub fn mean_power(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.sum_watts / self.samples as f64)
+    }
+
+    /// Number of successful samples.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Number of failed sensor reads.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+}
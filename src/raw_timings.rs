@@ -0,0 +1,40 @@
+//! Raw per-iteration timings streamed as compact binary frames.
+//!
+//! Every frame is written through [`output::write_bytes`]: on the serial
+//! console it is interleaved with the regular text log, over RTT it has its own
+//! channel. A host-side decoder scans the byte stream for the magic
+//! marker and then reads the fields below (all integers little endian):
+//!
+//! | Field       | Size          | Description                                 |
+//! |-------------|---------------|---------------------------------------------|
+//! | `magic`     | 4             | `b"RAWT"`                                   |
+//! | `length`    | 4 (`u32`)     | Number of bytes following this field        |
+//! | `label_len` | 1 (`u8`)      | Length of the algorithm label               |
+//! | `label`     | `label_len`   | ASCII algorithm label, e.g. `AES-CTR`       |
+//! | `data_size` | 4 (`u32`)     | Buffer size the samples were measured with  |
+//! | `count`     | 4 (`u32`)     | Number of samples in the frame              |
+//! | `samples`   | `4 * count`   | Per-iteration durations in microseconds     |
+
+use crate::output;
+
+/// Marker preceding every raw timing frame.
+pub const FRAME_MAGIC: [u8; 4] = *b"RAWT";
+
+/// Emit one frame containing the per-iteration `samples` (in microseconds)
+/// measured for `label` at `data_size` bytes.
+///
+/// Labels longer than 255 bytes are truncated.
+pub fn emit_frame(label: &str, data_size: usize, samples: &[u32]) {
+    let label = &label.as_bytes()[..label.len().min(u8::MAX as usize)];
+    let length = 1 + label.len() + 4 + 4 + samples.len() * 4;
+
+    output::write_bytes(&FRAME_MAGIC);
+    output::write_bytes(&(length as u32).to_le_bytes());
+    output::write_bytes(&[label.len() as u8]);
+    output::write_bytes(label);
+    output::write_bytes(&(data_size as u32).to_le_bytes());
+    output::write_bytes(&(samples.len() as u32).to_le_bytes());
+    for sample in samples {
+        output::write_bytes(&sample.to_le_bytes());
+    }
+}
@@ -0,0 +1,58 @@
+//! Collected machine-readable results, emitted in a stable order.
+//!
+//! With `csv-output`, `json-output`, `prometheus-output` or `baseline` every
+//! result is recorded while the benchmarks run instead of being logged at
+//! once. At the end of the run [`drain_sorted`] hands them out in
+//! [`report::sort`] order, so the `CSV: `, `JSON: ` and `PROM: ` lines of two
+//! runs can be compared with `diff`
+//! regardless of the order the benchmarks ran in.
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::report::{self, BenchmarkResult};
+
+/// A [`BenchmarkResult`] with an owned label.
+struct Recorded {
+    algorithm: String,
+    data_size: usize,
+    iterations: usize,
+    time_us: f64,
+}
+
+static RESULTS: Mutex<RefCell<Vec<Recorded>>> = Mutex::new(RefCell::new(Vec::new()));
+
+/// Record `result` for the end of the run.
+pub fn record(result: &BenchmarkResult<'_>) {
+    let recorded = Recorded {
+        algorithm: String::from(result.algorithm),
+        data_size: result.data_size,
+        iterations: result.iterations,
+        time_us: result.time_us,
+    };
+    critical_section::with(|cs| RESULTS.borrow_ref_mut(cs).push(recorded));
+}
+
+/// Remove every recorded result and pass them to `emit` sorted by
+/// [`report::sort`].
+pub fn drain_sorted(mut emit: impl FnMut(&BenchmarkResult<'_>)) {
+    let recorded = critical_section::with(|cs| core::mem::take(&mut *RESULTS.borrow_ref_mut(cs)));
+    let mut results: Vec<BenchmarkResult<'_>> = recorded
+        .iter()
+        .map(|r| BenchmarkResult {
+            algorithm: &r.algorithm,
+            data_size: r.data_size,
+            iterations: r.iterations,
+            time_us: r.time_us,
+        })
+        .collect();
+    report::sort(&mut results);
+    for result in &results {
+        emit(result);
+    }
+}
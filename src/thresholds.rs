@@ -0,0 +1,98 @@
+//! Performance regression thresholds.
+//!
+//! Each benchmark result is compared against the baseline tables below. A
+//! result that crosses its threshold is logged as `FAIL` and marks the whole
+//! run as failed; [`report`] then prints a sentinel line that CI can match on
+//! the serial console:
+//!
+//! ```text
+//! BENCHMARK RESULT: FAIL
+//! ```
+//!
+//! Results without a matching table entry are not checked. The baselines are
+//! the README figures at maximum CPU clock, minus roughly 15% headroom for
+//! run-to-run variation.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use log::{error, info};
+
+use crate::units::Throughput;
+
+/// Minimum throughput in decimal MB/s per (algorithm label, data size in
+/// bytes), whatever unit the log uses.
+pub const MIN_THROUGHPUT_MB_PER_S: &[(&str, usize, f64)] = &[
+    ("AES-CTR", 64, 3.7),
+    ("AES-CTR", 128, 6.4),
+    ("AES-CTR", 256, 10.0),
+    ("AES-CTR", 512, 13.8),
+    ("AES-CTR", 1024, 17.2),
+    ("AES-CTR", 2048, 19.5),
+    ("AES-CTR", 4096, 21.0),
+    ("AES-CTR", 8192, 21.7),
+    ("AES-CTR", 16 * 1024, 22.1),
+    ("AES-CTR", 32 * 1024, 22.4),
+];
+
+/// Maximum duration in milliseconds per (algorithm label, operand size in
+/// bytes).
+pub const MAX_DURATION_MS: &[(&str, usize, u64)] = &[("RSA-2048", 256, 252)];
+
+/// Set by the first result that crosses its threshold.
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Check a throughput result against [`MIN_THROUGHPUT_MB_PER_S`].
+///
+/// Returns `false` if the result is below its threshold.
+pub fn check_throughput(label: &str, data_size: usize, mb_per_s: f64) -> bool {
+    let Some(&(_, _, min)) = MIN_THROUGHPUT_MB_PER_S
+        .iter()
+        .find(|&&(l, size, _)| l == label && size == data_size)
+    else {
+        return true;
+    };
+
+    if mb_per_s < min {
+        error!(
+            "FAIL: {label}, DataSize: {data_size}, Throughput: {}, Minimum: {}",
+            Throughput(mb_per_s),
+            Throughput(min)
+        );
+        FAILED.store(true, Ordering::Relaxed);
+        return false;
+    }
+    true
+}
+
+/// Check a duration result against [`MAX_DURATION_MS`].
+///
+/// Returns `false` if the result is above its threshold.
+pub fn check_duration(label: &str, data_size: usize, millis: u64) -> bool {
+    let Some(&(_, _, max)) = MAX_DURATION_MS
+        .iter()
+        .find(|&&(l, size, _)| l == label && size == data_size)
+    else {
+        return true;
+    };
+
+    if millis > max {
+        error!("FAIL: {label}, DataSize: {data_size}, Time: {millis} ms, Maximum: {max} ms");
+        FAILED.store(true, Ordering::Relaxed);
+        return false;
+    }
+    true
+}
+
+/// Whether any result has crossed its threshold so far.
+pub fn failed() -> bool {
+    FAILED.load(Ordering::Relaxed)
+}
+
+/// Log the overall verdict as a single sentinel line.
+pub fn report() {
+    if failed() {
+        error!("BENCHMARK RESULT: FAIL");
+    } else {
+        info!("BENCHMARK RESULT: PASS");
+    }
+}